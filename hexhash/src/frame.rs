@@ -0,0 +1,151 @@
+//! Checksummed frames for region chunk payloads and network packets.
+//!
+//! A frame is a big-endian `u32` length, the payload, and the big-endian `u32` xxHash32 checksum of the payload.
+//! Reading a frame verifies the checksum before the payload is decoded, so corruption is reported as
+//! [Error::ChecksumMismatch] rather than as an error from somewhere inside the payload.
+//!
+//! This lives in `hexhash` rather than `hexio` because `hexhash` already depends on `hexio`.
+
+use std::io::{Cursor, Read, Write};
+
+use hexio::error::{Error, Result};
+use hexio::{NonByte, Readable, Writeable};
+use twox_hash::XxHash32;
+
+/// The seed of the checksum.
+const SEED: u32 = 0;
+
+/// The checksum of a frame payload.
+#[inline]
+pub fn checksum(payload: &[u8]) -> u32 {
+    XxHash32::oneshot(SEED, payload)
+}
+
+/// Writes `payload` as a frame, returning the number of bytes that were written.
+pub fn write_frame<W: Write>(writer: &mut W, payload: &[u8]) -> Result<u64> {
+    let length = u32::try_from(payload.len()).map_err(|_| Error::ArrayTooLong)?;
+    writer.write_all(&length.to_be_bytes())?;
+    writer.write_all(payload)?;
+    writer.write_all(&checksum(payload).to_be_bytes())?;
+    Ok(payload.len() as u64 + 8)
+}
+
+/// Reads a frame and verifies its checksum, returning the payload.
+pub fn read_frame<R: Read>(reader: &mut R) -> Result<Vec<u8>> {
+    read_frame_with_limit(reader, u32::MAX as usize)
+}
+
+/// Like [read_frame], but fails with [Error::ArrayTooLong] before reading a payload that is longer than `max_length`.
+/// Use this for frames from untrusted sources, such as network packets.
+pub fn read_frame_with_limit<R: Read>(reader: &mut R, max_length: usize) -> Result<Vec<u8>> {
+    let length = u32::read_from(reader)? as usize;
+    if length > max_length {
+        return Err(Error::ArrayTooLong);
+    }
+    // Read through `take` rather than allocating `length` up front, for the same reason as MAX_PREALLOCATE in hexio.
+    let mut payload = Vec::new();
+    reader.take(length as u64).read_to_end(&mut payload)?;
+    if payload.len() != length {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+    let expected = u32::read_from(reader)?;
+    let found = checksum(&payload);
+    if expected != found {
+        return Err(Error::ChecksumMismatch { expected, found });
+    }
+    Ok(payload)
+}
+
+/// Writes `value` as the payload of a frame.
+pub fn write_framed<T: Writeable, W: Write>(value: &T, writer: &mut W) -> Result<u64> {
+    let mut payload = Vec::new();
+    value.write_to(&mut payload)?;
+    write_frame(writer, &payload)
+}
+
+/// Reads a frame and decodes its payload as `T`, failing if the payload has bytes left over.
+pub fn read_framed<T: Readable, R: Read>(reader: &mut R) -> Result<T> {
+    decode_payload(&read_frame(reader)?)
+}
+
+/// Like [read_framed], but with the length limit of [read_frame_with_limit].
+pub fn read_framed_with_limit<T: Readable, R: Read>(reader: &mut R, max_length: usize) -> Result<T> {
+    decode_payload(&read_frame_with_limit(reader, max_length)?)
+}
+
+fn decode_payload<T: Readable>(payload: &[u8]) -> Result<T> {
+    let mut cursor = Cursor::new(payload);
+    let value = T::read_from(&mut cursor)?;
+    if cursor.position() != payload.len() as u64 {
+        return Err(Error::InvalidBinaryFormat);
+    }
+    Ok(value)
+}
+
+/// Reads and writes the inner value as a checksummed frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Framed<T>(pub T);
+
+impl<T: Readable> Readable for Framed<T> {
+    #[inline]
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        read_framed(reader).map(Framed)
+    }
+}
+
+impl<T: Writeable> Writeable for Framed<T> {
+    #[inline]
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        write_framed(&self.0, writer)
+    }
+}
+
+impl<T: Readable + Writeable> NonByte for Framed<T> {}
+impl<T: hexio::Deterministic> hexio::Deterministic for Framed<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_round_trip_test() {
+        let mut buffer = Vec::new();
+        let size = write_frame(&mut buffer, b"chunk").unwrap();
+        debug_assert_eq!(size, buffer.len() as u64);
+        debug_assert_eq!(&buffer[..4], [0, 0, 0, 5]);
+        debug_assert_eq!(&buffer[9..], checksum(b"chunk").to_be_bytes());
+        let packet = (7u8, String::from("hello"), vec![1.5f32, -2.0]);
+        Framed(packet.clone()).write_to(&mut buffer).unwrap();
+        write_frame(&mut buffer, &[]).unwrap();
+        let mut cursor = Cursor::new(&buffer);
+        debug_assert_eq!(read_frame(&mut cursor).unwrap(), b"chunk");
+        debug_assert_eq!(Framed::<(u8, String, Vec<f32>)>::read_from(&mut cursor).unwrap().0, packet);
+        debug_assert_eq!(read_frame(&mut cursor).unwrap(), []);
+        debug_assert_eq!(cursor.position(), buffer.len() as u64);
+    }
+
+    #[test]
+    fn frame_corruption_test() {
+        let mut buffer = Vec::new();
+        write_framed(&(1u32, 2u32), &mut buffer).unwrap();
+        let stored = checksum(&buffer[4..12]);
+        // Flip each bit of the payload in turn.
+        for bit in 32..96 {
+            let mut corrupt = buffer.clone();
+            corrupt[bit / 8] ^= 1 << (bit % 8);
+            match read_framed::<(u32, u32), _>(&mut Cursor::new(&corrupt)) {
+                Err(Error::ChecksumMismatch { expected, found }) => {
+                    debug_assert_eq!(expected, stored);
+                    debug_assert_eq!(found, checksum(&corrupt[4..12]));
+                }
+                other => panic!("Expected a checksum mismatch, found {other:?}"),
+            }
+        }
+        // The payload is fine, but it has a byte that `(u32, u32)` doesn't use.
+        let mut trailing = Vec::new();
+        write_frame(&mut trailing, &[0; 9]).unwrap();
+        debug_assert!(matches!(read_framed::<(u32, u32), _>(&mut Cursor::new(&trailing)), Err(Error::InvalidBinaryFormat)));
+        debug_assert!(matches!(read_frame_with_limit(&mut Cursor::new(&buffer), 7), Err(Error::ArrayTooLong)));
+        debug_assert!(matches!(read_frame(&mut Cursor::new([0xff, 0xff, 0xff, 0xff, 0])), Err(Error::IoError(_))));
+    }
+}
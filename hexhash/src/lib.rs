@@ -1,3 +1,4 @@
+pub mod frame;
 mod private;
 use std::{hash::{DefaultHasher, Hash, Hasher}, io::Write};
 use hexmacros::mark;
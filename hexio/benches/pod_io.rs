@@ -0,0 +1,63 @@
+//! Compares the bulk [hexio::pod] functions with writing and reading each element through `Writeable` and `Readable`.
+//!
+//! Run with `cargo bench -p hexio --bench pod_io`.
+
+use std::hint::black_box;
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+use hexio::pod::{read_pod_vec_with, write_pod_slice_with, Endian, PodScalar};
+use hexio::{LengthPrefix, Readable, Writeable};
+
+const LENGTH: usize = 1 << 20;
+const ITERATIONS: u32 = 20;
+
+fn time<F: FnMut()>(mut f: F) -> Duration {
+    // Warm up the caches and the allocator.
+    f();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn report(name: &str, bytes: usize, duration: Duration) {
+    let throughput = bytes as f64 / duration.as_secs_f64() / (1024.0 * 1024.0 * 1024.0);
+    println!("{name:<40} {:>10.3} ms {throughput:>8.2} GiB/s", duration.as_secs_f64() * 1000.0);
+}
+
+fn bench<T>(type_name: &str, values: Vec<T>)
+where
+    T: PodScalar + Readable + Writeable + hexio::NonByte,
+{
+    let bytes = size_of_val(values.as_slice());
+    let mut buffer = Vec::with_capacity(bytes + 3);
+    report(&format!("{type_name} write per element"), bytes, time(|| {
+        buffer.clear();
+        black_box(&values).write_to(&mut buffer).unwrap();
+    }));
+    for endian in [Endian::Big, Endian::Little] {
+        report(&format!("{type_name} write bulk {endian:?}"), bytes, time(|| {
+            buffer.clear();
+            write_pod_slice_with(&mut buffer, black_box(&values), LengthPrefix::U24, endian).unwrap();
+        }));
+    }
+    buffer.clear();
+    values.write_to(&mut buffer).unwrap();
+    report(&format!("{type_name} read per element"), bytes, time(|| {
+        black_box(Vec::<T>::read_from(&mut Cursor::new(black_box(&buffer))).unwrap());
+    }));
+    for endian in [Endian::Big, Endian::Little] {
+        report(&format!("{type_name} read bulk {endian:?}"), bytes, time(|| {
+            black_box(read_pod_vec_with::<T, _>(&mut Cursor::new(black_box(&buffer)), LengthPrefix::U24, endian).unwrap());
+        }));
+    }
+}
+
+fn main() {
+    // u24 lengths limit a Vec to 16 Mi elements.
+    bench("f32", (0..LENGTH).map(|i| i as f32 * 0.5).collect());
+    bench("u32", (0..LENGTH as u32).map(|i| i.wrapping_mul(0x9e3779b9)).collect());
+    bench("u16", (0..LENGTH).map(|i| i as u16).collect());
+}
@@ -0,0 +1,111 @@
+//! Impls for the bit flag types from `hexcore`, enabled by the `hexcore` feature.
+
+use std::io::{Read, Write};
+
+use hexcore::bit::{BitFlags128, BitFlags16, BitFlags32, BitFlags64, BitFlags8};
+use hexmacros::mark;
+
+use crate::error::Result;
+use crate::{read_byte_array, read_byte_vec, write_byte_array, write_byte_slice, Byte, NonByte, Readable, Writeable};
+
+macro_rules! bitflags_io {
+    ($type:ident($inner:ty)) => {
+        impl Readable for $type {
+            fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+                Ok(Self(<$inner>::read_from(reader)?))
+            }
+        }
+
+        impl Writeable for $type {
+            fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+                self.0.write_to(writer)
+            }
+        }
+    };
+}
+
+bitflags_io!(BitFlags8(u8));
+bitflags_io!(BitFlags16(u16));
+bitflags_io!(BitFlags32(u32));
+bitflags_io!(BitFlags64(u64));
+bitflags_io!(BitFlags128(u128));
+
+impl Byte for BitFlags8 {}
+impl NonByte for BitFlags16 {}
+impl NonByte for BitFlags32 {}
+impl NonByte for BitFlags64 {}
+impl NonByte for BitFlags128 {}
+
+impl Readable for Vec<BitFlags8> {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        read_byte_vec(reader, |byte| Ok(BitFlags8(byte)))
+    }
+}
+
+impl Writeable for Vec<BitFlags8> {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        write_byte_slice(writer, self, |flags| flags.0)
+    }
+}
+
+impl Readable for Box<[BitFlags8]> {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(read_byte_vec(reader, |byte| Ok(BitFlags8(byte)))?.into_boxed_slice())
+    }
+}
+
+impl Writeable for Box<[BitFlags8]> {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        write_byte_slice(writer, self, |flags| flags.0)
+    }
+}
+
+impl<const SIZE: usize> Readable for [BitFlags8; SIZE] {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        read_byte_array(reader, |byte| Ok(BitFlags8(byte)))
+    }
+}
+
+impl<const SIZE: usize> Writeable for [BitFlags8; SIZE] {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        write_byte_array(writer, self, |flags| flags.0)
+    }
+}
+
+mark!{trait = crate::Deterministic;
+    BitFlags8;
+    BitFlags16;
+    BitFlags32;
+    BitFlags64;
+    BitFlags128;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn bitflags_round_trip_test() {
+        let mut buffer = Vec::new();
+        BitFlags16(0x1234).write_to(&mut buffer).unwrap();
+        vec![BitFlags8(1), BitFlags8(0x80)].write_to(&mut buffer).unwrap();
+        vec![BitFlags128(u128::MAX)].write_to(&mut buffer).unwrap();
+        [BitFlags8(3), BitFlags8(4)].write_to(&mut buffer).unwrap();
+        Box::<[BitFlags8]>::from([BitFlags8(5)]).write_to(&mut buffer).unwrap();
+        debug_assert_eq!(&buffer[..7], [0x12, 0x34, 0, 0, 2, 1, 0x80]);
+        debug_assert_eq!(&buffer[buffer.len() - 6..], [3, 4, 0, 0, 1, 5]);
+        let mut cursor = Cursor::new(&buffer);
+        let flags16 = BitFlags16::read_from(&mut cursor).unwrap();
+        let flags8 = Vec::<BitFlags8>::read_from(&mut cursor).unwrap();
+        let flags128 = Vec::<BitFlags128>::read_from(&mut cursor).unwrap();
+        let array = <[BitFlags8; 2]>::read_from(&mut cursor).unwrap();
+        let boxed = Box::<[BitFlags8]>::read_from(&mut cursor).unwrap();
+        debug_assert_eq!(flags16, BitFlags16(0x1234));
+        debug_assert_eq!(flags8, [BitFlags8(1), BitFlags8(0x80)]);
+        debug_assert_eq!(flags128, [BitFlags128(u128::MAX)]);
+        debug_assert_eq!(array, [BitFlags8(3), BitFlags8(4)]);
+        debug_assert_eq!(*boxed, [BitFlags8(5)]);
+    }
+}
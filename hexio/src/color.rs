@@ -0,0 +1,143 @@
+//! Impls for the color types from `hexcolor`, enabled by the `hexcolor` feature.
+
+use std::io::{Read, Write};
+
+use hexcolor::{Color, Rgb, Rgba};
+use hexmacros::mark;
+
+use crate::error::{Error, Result};
+use crate::{read_byte_array, read_byte_vec, write_byte_array, write_byte_slice, Byte, NonByte, Readable, Writeable};
+
+fn color_from_byte(byte: u8) -> Result<Color> {
+    Color::from_byte(byte).ok_or(Error::InvalidBinaryFormat)
+}
+
+impl Byte for Color {}
+
+impl Readable for Color {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        color_from_byte(u8::read_from(reader)?)
+    }
+}
+
+impl Writeable for Color {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        self.to_byte().write_to(writer)
+    }
+}
+
+impl Readable for Vec<Color> {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        read_byte_vec(reader, color_from_byte)
+    }
+}
+
+impl Writeable for Vec<Color> {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        write_byte_slice(writer, self, |color| color.to_byte())
+    }
+}
+
+impl Readable for Box<[Color]> {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(read_byte_vec(reader, color_from_byte)?.into_boxed_slice())
+    }
+}
+
+impl Writeable for Box<[Color]> {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        write_byte_slice(writer, self, |color| color.to_byte())
+    }
+}
+
+impl<const SIZE: usize> Readable for [Color; SIZE] {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        read_byte_array(reader, color_from_byte)
+    }
+}
+
+impl<const SIZE: usize> Writeable for [Color; SIZE] {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        write_byte_array(writer, self, |color| color.to_byte())
+    }
+}
+
+impl NonByte for Rgb {}
+
+impl Readable for Rgb {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut buffer = [0u8; 3];
+        reader.read_exact(&mut buffer)?;
+        Ok(Rgb::from_bytes(buffer))
+    }
+}
+
+impl Writeable for Rgb {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        writer.write_all(self.as_bytes())?;
+        Ok(3)
+    }
+}
+
+impl NonByte for Rgba {}
+
+impl Readable for Rgba {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut buffer = [0u8; 4];
+        reader.read_exact(&mut buffer)?;
+        Ok(Rgba::from_bytes(buffer))
+    }
+}
+
+impl Writeable for Rgba {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        writer.write_all(self.as_bytes())?;
+        Ok(4)
+    }
+}
+
+mark!{trait = crate::Deterministic;
+    Color;
+    Rgb;
+    Rgba;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn color_round_trip_test() {
+        let colors = Color::SORTED_COLORS.to_vec();
+        let mut buffer = Vec::new();
+        colors.write_to(&mut buffer).unwrap();
+        debug_assert_eq!(buffer.len(), 3 + colors.len());
+        debug_assert_eq!(Vec::<Color>::read_from(&mut Cursor::new(&buffer)).unwrap(), colors);
+        debug_assert_eq!(Box::<[Color]>::read_from(&mut Cursor::new(&buffer)).unwrap(), colors.clone().into_boxed_slice());
+        let mut boxed = Vec::new();
+        colors.clone().into_boxed_slice().write_to(&mut boxed).unwrap();
+        debug_assert_eq!(boxed, buffer);
+        let palette: [Color; 4] = colors[..4].try_into().unwrap();
+        let mut buffer = Vec::new();
+        debug_assert_eq!(palette.write_to(&mut buffer).unwrap(), 4);
+        debug_assert_eq!(<[Color; 4]>::read_from(&mut Cursor::new(&buffer)).unwrap(), palette);
+        let pixels = vec![Rgba::new(1, 2, 3, 4), Rgba::new(255, 0, 128, 0)];
+        let mut buffer = Vec::new();
+        pixels.write_to(&mut buffer).unwrap();
+        debug_assert_eq!(&buffer[3..], [1, 2, 3, 4, 255, 0, 128, 0]);
+        debug_assert_eq!(Vec::<Rgba>::read_from(&mut Cursor::new(&buffer)).unwrap(), pixels);
+        let mut buffer = Vec::new();
+        Rgb::new(7, 8, 9).write_to(&mut buffer).unwrap();
+        debug_assert_eq!(Rgb::read_from(&mut Cursor::new(&buffer)).unwrap(), Rgb::new(7, 8, 9));
+    }
+
+    #[test]
+    fn color_invalid_test() {
+        debug_assert!(matches!(Color::read_from(&mut Cursor::new([140])), Err(Error::InvalidBinaryFormat)));
+        debug_assert!(matches!(Vec::<Color>::read_from(&mut Cursor::new([0, 0, 1, 200])), Err(Error::InvalidBinaryFormat)));
+        let error = <[Color; 2]>::read_from(&mut Cursor::new([0, 200])).unwrap_err();
+        debug_assert_eq!(error.to_string(), "[1]: Invalid binary format");
+    }
+}
@@ -10,8 +10,8 @@ pub enum Error {
     StringTooLong,
     #[error("Array too long")]
     ArrayTooLong,
-    // #[error("Invalid binary format")]
-    // InvalidBinaryFormat,
+    #[error("Invalid binary format")]
+    InvalidBinaryFormat,
     #[error("Invalid codepoint")]
     InvalidCodepoint,
     // #[error("Chunk was too large and did not fit into the buffer")]
@@ -36,8 +36,152 @@ pub enum Error {
     // GltfError(#[from] gltf::Error),
     // #[error("Mesh Extraction Error")]
     // MeshExtractionError,
+    #[error("Expected type id {expected:#010x}, found {found:#010x}")]
+    TypeIdMismatch { expected: u32, found: u32 },
+    #[error("Unsupported version {version} (the latest version is {latest})")]
+    UnsupportedVersion { version: u16, latest: u16 },
+    #[error("More than one upgrade from version {0} was registered")]
+    DuplicateUpgrade(u16),
+    #[error("Checksum mismatch (expected {expected:#010x}, found {found:#010x})")]
+    ChecksumMismatch { expected: u32, found: u32 },
     #[error("Custom Error: {0}")]
     Custom(&'static str),
+    /// An error with the path to the value that failed to read.
+    #[error("{0}")]
+    Context(Box<ErrorContext>),
+}
+
+/// A step in the path to a value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// A struct field, or a tuple field such as `0`.
+    Field(&'static str),
+    /// An element of a collection.
+    Index(usize),
+    /// An enum variant, such as `Packet::Move`.
+    Variant(&'static str),
+    /// A description of what was being read, added with [Error::context], such as `failed to load chunk (3, -7)`.
+    Message(String),
+}
+
+/// The path to the value that failed to read, the byte offset where it failed, and the error itself.
+#[derive(Debug)]
+pub struct ErrorContext {
+    /// The innermost segment is first, since segments are added as the error is returned.
+    path: Vec<PathSegment>,
+    offset: Option<u64>,
+    source: Error,
+}
+
+impl ErrorContext {
+    /// The segments of the path, from the outermost to the innermost.
+    pub fn path(&self) -> impl Iterator<Item = &PathSegment> {
+        self.path.iter().rev()
+    }
+
+    /// The number of bytes that had been read when reading failed.
+    pub fn offset(&self) -> Option<u64> {
+        self.offset
+    }
+
+    /// The error without its context.
+    pub fn error(&self) -> &Error {
+        &self.source
+    }
+}
+
+impl std::fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut previous: Option<&PathSegment> = None;
+        for segment in self.path() {
+            // Messages are separated from the rest of the path like the error is.
+            let separator = match (previous, segment) {
+                (None, _) => "",
+                (Some(PathSegment::Message(_)), _) | (Some(_), PathSegment::Message(_)) => ": ",
+                (Some(_), PathSegment::Field(_) | PathSegment::Variant(_)) => ".",
+                (Some(_), PathSegment::Index(_)) => "",
+            };
+            match segment {
+                PathSegment::Field(name) | PathSegment::Variant(name) => write!(f, "{separator}{name}")?,
+                PathSegment::Index(index) => write!(f, "{separator}[{index}]")?,
+                PathSegment::Message(message) => write!(f, "{separator}{message}")?,
+            }
+            previous = Some(segment);
+        }
+        if !self.path.is_empty() {
+            write!(f, ": ")?;
+        }
+        write!(f, "{}", self.source)?;
+        if let Some(offset) = self.offset {
+            write!(f, " at byte {offset}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ErrorContext {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl Error {
+    fn into_context(self) -> Box<ErrorContext> {
+        match self {
+            Error::Context(context) => context,
+            source => Box::new(ErrorContext {
+                path: Vec::new(),
+                offset: None,
+                source,
+            }),
+        }
+    }
+
+    /// Adds `segment` to the front of the path of the error.
+    pub fn at(self, segment: PathSegment) -> Self {
+        let mut context = self.into_context();
+        context.path.push(segment);
+        Error::Context(context)
+    }
+
+    #[inline]
+    pub fn at_field(self, name: &'static str) -> Self {
+        self.at(PathSegment::Field(name))
+    }
+
+    #[inline]
+    pub fn at_index(self, index: usize) -> Self {
+        self.at(PathSegment::Index(index))
+    }
+
+    #[inline]
+    pub fn at_variant(self, name: &'static str) -> Self {
+        self.at(PathSegment::Variant(name))
+    }
+
+    /// Adds a description of what was being read to the front of the path, such as `failed to load chunk (3, -7)`.
+    #[inline]
+    pub fn context<D: std::fmt::Display>(self, message: D) -> Self {
+        self.at(PathSegment::Message(message.to_string()))
+    }
+
+    /// Sets the byte offset where reading failed, unless it is already set.
+    pub fn at_offset(self, offset: u64) -> Self {
+        let mut context = self.into_context();
+        context.offset.get_or_insert(offset);
+        Error::Context(context)
+    }
+
+    /// The error without its context.
+    ///
+    /// Errors from reading a value inside another one, such as a field of a struct or an element of a [Vec], are
+    /// wrapped in [Error::Context], so match on this rather than on the error itself.
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Context(context) => &context.source,
+            error => error,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
\ No newline at end of file
@@ -0,0 +1,116 @@
+//! Little-endian encoding for numbers. Everything else in this crate is big-endian.
+//!
+//! Use the [LittleEndian] wrapper, or use this module with the derive macros:
+//! ```rust, no_run
+//! use hexio::{Readable, Writeable};
+//!
+//! #[derive(Readable, Writeable)]
+//! struct Header {
+//!     #[io(with = "hexio::le")]
+//!     magic: u32,
+//! }
+//! ```
+
+use std::io::{Read, Write};
+
+use hexmacros::for_each_int_type;
+
+use crate::error::Result;
+use crate::{Deterministic, NonByte, Readable, Writeable};
+
+/// Numbers that can be written in little-endian byte order.
+pub trait LeEncode: Sized {
+    fn read_le<R: Read>(reader: &mut R) -> Result<Self>;
+    fn write_le<W: Write>(&self, writer: &mut W) -> Result<u64>;
+}
+
+/// Writes the inner number in little-endian byte order.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LittleEndian<T>(pub T);
+
+impl<T: LeEncode> Readable for LittleEndian<T> {
+    #[inline]
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        T::read_le(reader).map(LittleEndian)
+    }
+}
+
+impl<T: LeEncode> Writeable for LittleEndian<T> {
+    #[inline]
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        self.0.write_le(writer)
+    }
+}
+
+impl<T: LeEncode> NonByte for LittleEndian<T> {}
+impl<T: LeEncode + Deterministic> Deterministic for LittleEndian<T> {}
+
+/// Reads a little-endian number, for `#[io(with = "hexio::le")]`.
+#[inline]
+pub fn read_from<T: LeEncode, R: Read>(reader: &mut R) -> Result<T> {
+    T::read_le(reader)
+}
+
+/// Writes a little-endian number, for `#[io(with = "hexio::le")]`.
+#[inline]
+pub fn write_to<T: LeEncode, W: Write>(value: &T, writer: &mut W) -> Result<u64> {
+    value.write_le(writer)
+}
+
+macro_rules! le_encode {
+    ($type:ty) => {
+        impl LeEncode for $type {
+            #[inline]
+            fn read_le<R: Read>(reader: &mut R) -> Result<Self> {
+                let mut buffer = [0u8; std::mem::size_of::<$type>()];
+                reader.read_exact(&mut buffer)?;
+                Ok(<$type>::from_le_bytes(buffer))
+            }
+
+            #[inline]
+            fn write_le<W: Write>(&self, writer: &mut W) -> Result<u64> {
+                writer.write_all(&self.to_le_bytes())?;
+                Ok(std::mem::size_of::<$type>() as u64)
+            }
+        }
+    };
+}
+
+for_each_int_type!(le_encode; all !sized);
+le_encode!(f32);
+le_encode!(f64);
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    macro_rules! boundary_test {
+        ($type:ty) => {
+            for value in [<$type>::MIN, <$type>::MIN + 1, 0 as $type, 1 as $type, <$type>::MAX - 1, <$type>::MAX] {
+                let mut buffer = Vec::new();
+                LittleEndian(value).write_to(&mut buffer).unwrap();
+                debug_assert_eq!(buffer, value.to_le_bytes());
+                debug_assert_eq!(LittleEndian::<$type>::read_from(&mut Cursor::new(&buffer)).unwrap().0, value);
+            }
+        };
+    }
+
+    #[test]
+    fn little_endian_test() {
+        for_each_int_type!(boundary_test; all !sized);
+        let mut buffer = Vec::new();
+        write_to(&0x01020304u32, &mut buffer).unwrap();
+        write_to(&1.5f32, &mut buffer).unwrap();
+        debug_assert_eq!(&buffer[..4], [4, 3, 2, 1]);
+        let mut cursor = Cursor::new(&buffer);
+        debug_assert_eq!(read_from::<u32, _>(&mut cursor).unwrap(), 0x01020304);
+        debug_assert_eq!(read_from::<f32, _>(&mut cursor).unwrap(), 1.5);
+        for value in [f64::MIN, f64::MIN_POSITIVE, -0.0, f64::INFINITY, f64::MAX] {
+            let mut buffer = Vec::new();
+            LittleEndian(value).write_to(&mut buffer).unwrap();
+            debug_assert_eq!(LittleEndian::<f64>::read_from(&mut Cursor::new(&buffer)).unwrap().0.to_bits(), value.to_bits());
+        }
+    }
+}
@@ -1,17 +1,42 @@
+//! Binary reading and writing with [Readable] and [Writeable].
+//!
+//! When reading a value inside another one fails, such as a field of a struct or an element of a [Vec], the error is
+//! wrapped in [Error::Context] with the path to that value. Use [Error::root] to match on the error itself.
+//! ```rust
+//! use hexio::error::Error;
+//! use hexio::Readable;
+//!
+//! // A length of 1, followed by a surrogate, which isn't a valid char.
+//! let bytes = [0u8, 0, 1, 0, 0, 0xd8, 0];
+//! let error = Vec::<char>::read_from(&mut bytes.as_slice()).unwrap_err();
+//! assert!(matches!(error.root(), Error::InvalidCodepoint));
+//! assert_eq!(error.to_string(), "[0]: Invalid codepoint");
+//! ```
+
 // pub mod region;
 pub mod error;
+pub mod le;
+pub mod pod;
+pub mod varint;
+pub mod versioned;
+#[cfg(feature = "hexcore")]
+mod bit;
+#[cfg(feature = "hexcolor")]
+mod color;
+#[cfg(feature = "hexorient")]
+mod orient;
+// Lets the derive macros refer to this crate as `::hexio` from inside it as well.
+extern crate self as hexio;
+pub use hexmacros::{Readable, Writeable};
 use crate::error::{Error, Result};
 // use crate::math::axis_flags::AxisFlags;
 // use crate::voxel::block::block_property::Property;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::{BuildHasher, Hash};
 use std::io::{Read, Write};
 use hexmacros::for_each_int_type;
-// use crate::math::bit::*;
-// use crate::rendering::color::{
-//     Color, Rgb, Rgba
-// };
 // use crate::prelude::{
-//     Axis, Cardinal, Direction, FaceFlags, Flip, Increment, Orientation, PropertyArray, Rotation
+//     FaceFlags, PropertyArray
 // };
 // use crate::tag::{
 //     NonByte,
@@ -43,6 +68,9 @@ use paste::paste;
 use hexmacros::mark;
 
 const MAX_ARRAY_LENGTH: usize = 0xffffff;
+/// The most elements that are allocated up front when reading a collection, so that a corrupt length
+/// doesn't allocate more than the stream actually contains.
+const MAX_PREALLOCATE: usize = 4096;
 
 /// A marker trait that describes a Writeable type as being deterministic, which means that equal values of that type
 /// will always result in the same binary representation.
@@ -90,6 +118,44 @@ impl<W: Write> WriteExt for W {
     }
 }
 
+/// Counts the bytes that are read from the inner reader.
+#[derive(Debug)]
+pub struct OffsetReader<R> {
+    inner: R,
+    offset: u64,
+}
+
+impl<R: Read> OffsetReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner, offset: 0 }
+    }
+
+    /// The number of bytes that have been read.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for OffsetReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.offset += count as u64;
+        Ok(count)
+    }
+}
+
+/// Reads a value, adding the byte offset where reading failed to the error.
+/// Together with the path that nested reads add, this gives errors such as
+/// `section[4].tags[12].value: Invalid codepoint at byte 5512`.
+pub fn read_with_context<T: Readable, R: Read>(reader: &mut R) -> Result<T> {
+    let mut reader = OffsetReader::new(reader);
+    T::read_from(&mut reader).map_err(|error| error.at_offset(reader.offset()))
+}
+
 pub fn read_u24<R: Read>(reader: &mut R) -> Result<u32> {
     let mut buf = [0u8; 4];
     reader.read_exact(&mut buf[1..4])?;
@@ -109,8 +175,8 @@ pub fn write_u24<W: Write>(writer: &mut W, value: u32) -> Result<u64> {
 pub fn read_vec<T: Readable, R: std::io::Read>(reader: &mut R) -> Result<Vec<T>> {
     let length = read_u24(reader)?;
     let mut result = Vec::with_capacity(length as usize);
-    for _ in 0..length {
-        result.push(T::read_from(reader)?);
+    for index in 0..length as usize {
+        result.push(T::read_from(reader).map_err(|error| error.at_index(index))?);
     }
     Ok(result)
 }
@@ -123,6 +189,63 @@ pub fn write_slice<T: Writeable, W: std::io::Write>(writer: &mut W, slice: &[T])
     Ok(length)
 }
 
+/// The encoding of the length before the elements of a collection.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LengthPrefix {
+    /// A big-endian `u24`, as used by [read_vec] and [write_slice].
+    #[default]
+    U24,
+    /// A big-endian `u32`.
+    U32,
+    /// A little-endian `u32`.
+    U32Le,
+    /// A [varint](varint), which is a single byte for lengths below 128.
+    VarInt,
+}
+
+impl LengthPrefix {
+    pub fn read_length<R: Read>(self, reader: &mut R) -> Result<usize> {
+        let length = match self {
+            LengthPrefix::U24 => read_u24(reader)?,
+            LengthPrefix::U32 => u32::read_from(reader)?,
+            LengthPrefix::U32Le => le::read_from::<u32, _>(reader)?,
+            LengthPrefix::VarInt => varint::read_from::<u64, _>(reader)?
+                .try_into()
+                .map_err(|_| Error::ArrayTooLong)?,
+        };
+        usize::try_from(length).map_err(|_| Error::ArrayTooLong)
+    }
+
+    pub fn write_length<W: Write>(self, writer: &mut W, length: usize) -> Result<u64> {
+        let length = u32::try_from(length).map_err(|_| Error::ArrayTooLong);
+        match self {
+            LengthPrefix::U24 => write_u24(writer, length?),
+            LengthPrefix::U32 => length?.write_to(writer),
+            LengthPrefix::U32Le => le::write_to(&length?, writer),
+            LengthPrefix::VarInt => varint::write_to(&length?, writer),
+        }
+    }
+}
+
+/// Like [read_vec], but with the length encoded as `prefix`.
+pub fn read_vec_with<T: Readable, R: Read>(reader: &mut R, prefix: LengthPrefix) -> Result<Vec<T>> {
+    let length = prefix.read_length(reader)?;
+    let mut result = Vec::with_capacity(length.min(MAX_PREALLOCATE));
+    for index in 0..length {
+        result.push(T::read_from(reader).map_err(|error| error.at_index(index))?);
+    }
+    Ok(result)
+}
+
+/// Like [write_slice], but with the length encoded as `prefix`.
+pub fn write_slice_with<T: Writeable, W: Write>(writer: &mut W, slice: &[T], prefix: LengthPrefix) -> Result<u64> {
+    let mut length = prefix.write_length(writer, slice.len())?;
+    for item in slice {
+        length += item.write_to(writer)?;
+    }
+    Ok(length)
+}
+
 pub fn write_zeros<W: Write>(writer: &mut W, count: u64) -> Result<u64> {
     const ZEROS: [u8; 4096] = [0; 4096];
     let mut count = count;
@@ -147,6 +270,69 @@ pub fn write_bytes<W: Write>(writer: &mut W, data: &[u8]) -> Result<u64> {
     Ok(writer.write_all(data).map(|_| data.len() as u64)?)
 }
 
+/// Reads a `u24` length followed by one byte per element, converting each byte with `decode`.
+pub fn read_byte_vec<T, R: Read, F: FnMut(u8) -> Result<T>>(reader: &mut R, decode: F) -> Result<Vec<T>> {
+    let length = read_u24(reader)?;
+    read_bytes(reader, length as usize)?.into_iter().map(decode).collect()
+}
+
+/// Writes a `u24` length followed by one byte per element, converting each element with `encode`.
+pub fn write_byte_slice<T, W: Write, F: FnMut(&T) -> u8>(writer: &mut W, slice: &[T], encode: F) -> Result<u64> {
+    if slice.len() > MAX_ARRAY_LENGTH {
+        return Err(Error::ArrayTooLong);
+    }
+    let bytes = slice.iter().map(encode).collect::<Vec<u8>>();
+    Ok(write_u24(writer, slice.len() as u32)? + write_bytes(writer, &bytes)?)
+}
+
+/// Reads one byte per element of an array, converting each byte with `decode`.
+pub fn read_byte_array<T, R: Read, F: FnMut(u8) -> Result<T>, const SIZE: usize>(reader: &mut R, mut decode: F) -> Result<[T; SIZE]> {
+    let mut buffer = [0u8; SIZE];
+    reader.read_exact(&mut buffer)?;
+    let items = buffer.into_iter()
+        .enumerate()
+        .map(|(index, byte)| decode(byte).map_err(|error| error.at_index(index)))
+        .collect::<Result<Vec<T>>>()?;
+    Ok(items.try_into().unwrap_or_else(|_| unreachable!("the Vec has exactly SIZE items")))
+}
+
+/// Writes one byte per element of an array, converting each element with `encode`.
+pub fn write_byte_array<T, W: Write, F: FnMut(&T) -> u8, const SIZE: usize>(writer: &mut W, array: &[T; SIZE], encode: F) -> Result<u64> {
+    write_bytes(writer, &array.each_ref().map(encode))
+}
+
+/// Reads a `u24` length followed by elements that are packed into `bits` bits each, least significant bit first.
+pub fn read_packed_vec<T, R: Read, F: FnMut(u8) -> Result<T>>(reader: &mut R, bits: u32, mut decode: F) -> Result<Vec<T>> {
+    debug_assert!((1..=8).contains(&bits));
+    let length = read_u24(reader)? as usize;
+    let bytes = read_bytes(reader, (length * bits as usize).div_ceil(8))?;
+    let mask = (1u16 << bits) - 1;
+    (0..length).map(|index| {
+        let bit = index * bits as usize;
+        let low = bytes[bit / 8] as u16;
+        let high = bytes.get(bit / 8 + 1).copied().unwrap_or(0) as u16;
+        decode((((low | high << 8) >> (bit % 8)) & mask) as u8)
+    }).collect()
+}
+
+/// Writes a `u24` length followed by elements that are packed into `bits` bits each, least significant bit first.
+pub fn write_packed_slice<T, W: Write, F: FnMut(&T) -> u8>(writer: &mut W, slice: &[T], bits: u32, mut encode: F) -> Result<u64> {
+    debug_assert!((1..=8).contains(&bits));
+    if slice.len() > MAX_ARRAY_LENGTH {
+        return Err(Error::ArrayTooLong);
+    }
+    let mut bytes = vec![0u8; (slice.len() * bits as usize).div_ceil(8)];
+    for (index, item) in slice.iter().enumerate() {
+        let bit = index * bits as usize;
+        let value = (encode(item) as u16 & ((1u16 << bits) - 1)) << (bit % 8);
+        bytes[bit / 8] |= value as u8;
+        if value > 0xff {
+            bytes[bit / 8 + 1] |= (value >> 8) as u8;
+        }
+    }
+    Ok(write_u24(writer, slice.len() as u32)? + write_bytes(writer, &bytes)?)
+}
+
 macro_rules! num_io {
     ($type:ty) => {
         impl Readable for $type {
@@ -248,70 +434,6 @@ impl Writeable for char {
 //     }
 // }
 
-// impl Readable for BitFlags8 {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         let mut buffer = [0u8; 1];
-//         reader.read_exact(&mut buffer)?;
-//         Ok(BitFlags8(buffer[0]))
-//     }
-// }
-
-// impl Writeable for BitFlags8 {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         let buffer = [self.0];
-//         writer.write_all(&buffer)?;
-//         Ok(1)
-//     }
-// }
-
-// impl Readable for BitFlags16 {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         Ok(Self(u16::read_from(reader)?))
-//     }
-// }
-
-// impl Writeable for BitFlags16 {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         self.0.write_to(writer)
-//     }
-// }
-
-// impl Readable for BitFlags32 {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         Ok(Self(u32::read_from(reader)?))
-//     }
-// }
-
-// impl Writeable for BitFlags32 {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         self.0.write_to(writer)
-//     }
-// }
-
-// impl Readable for BitFlags64 {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         Ok(Self(u64::read_from(reader)?))
-//     }
-// }
-
-// impl Writeable for BitFlags64 {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         self.0.write_to(writer)
-//     }
-// }
-
-// impl Readable for BitFlags128 {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         Ok(Self(u128::read_from(reader)?))
-//     }
-// }
-
-// impl Writeable for BitFlags128 {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         self.0.write_to(writer)
-//     }
-// }
-
 impl_nonbyte!(f32);
 
 impl Readable for f32 {
@@ -350,168 +472,6 @@ impl Writeable for f64 {
     }
 }
 
-// impl Readable for Direction {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         let dir: u8 = u8::read_from(reader)?;
-//         // NegX = 4,
-//         // NegY = 3,
-//         // NegZ = 5,
-//         // PosX = 1,
-//         // PosY = 0,
-//         // PosZ = 2,
-//         const NEGX: u8 = Direction::NegX as u8;
-//         const NEGY: u8 = Direction::NegY as u8;
-//         const NEGZ: u8 = Direction::NegZ as u8;
-//         const POSX: u8 = Direction::PosX as u8;
-//         const POSY: u8 = Direction::PosY as u8;
-//         const POSZ: u8 = Direction::PosZ as u8;
-//         use Direction::*;
-//         Ok(match dir {
-//             NEGX => NegX,
-//             NEGY => NegY,
-//             NEGZ => NegZ,
-//             POSX => PosX,
-//             POSY => PosY,
-//             POSZ => PosZ,
-//             _ => return Err(Error::InvalidBinaryFormat),
-//         })
-//     }
-// }
-
-// impl Writeable for Direction {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         (*self as u8).write_to(writer)
-//     }
-// }
-
-// impl Readable for Cardinal {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         let id = u8::read_from(reader)?;
-//         Ok(match id {
-//             0 => Cardinal::West,
-//             1 => Cardinal::North,
-//             2 => Cardinal::East,
-//             3 => Cardinal::South,
-//             _ => return Err(Error::InvalidBinaryFormat),
-//         })
-//     }
-// }
-
-// impl Writeable for Cardinal {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         match self {
-//             Cardinal::West => 0u8.write_to(writer),
-//             Cardinal::North => 1u8.write_to(writer),
-//             Cardinal::East => 2u8.write_to(writer),
-//             Cardinal::South => 3u8.write_to(writer),
-//         }
-//     }
-// }
-
-// impl Readable for Rotation {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         Ok(Rotation(u8::read_from(reader)?))
-//     }
-// }
-
-// impl Writeable for Rotation {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         self.0.write_to(writer)
-//     }
-// }
-
-// impl Readable for Flip {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         Ok(Flip(u8::read_from(reader)?))
-//     }
-// }
-
-// impl Writeable for Flip {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         self.0.write_to(writer)
-//     }
-// }
-
-// impl Readable for Orientation {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         let packed = u8::read_from(reader)?;
-//         Ok(Orientation(packed))
-//     }
-// }
-
-// impl Writeable for Orientation {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         self.0.write_to(writer)
-//     }
-// }
-
-// impl Readable for Axis {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         let axis: u8 = u8::read_from(reader)?;
-//         const X: u8 = Axis::X as u8;
-//         const Y: u8 = Axis::Y as u8;
-//         const Z: u8 = Axis::Z as u8;
-//         Ok(match axis {
-//             X => Axis::X,
-//             Y => Axis::Y,
-//             Z => Axis::Z,
-//             _ => return Err(Error::InvalidBinaryFormat)
-//         })
-//     }
-// }
-
-// impl Writeable for Axis {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         (*self as u8).write_to(writer)
-//     }
-// }
-
-// impl Readable for Color {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         Ok(Color::from_byte(u8::read_from(reader)?).unwrap_or(Color::Black))
-//     }
-// }
-
-// impl Writeable for Color {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         self.to_byte().write_to(writer)
-//     }
-// }
-
-// impl Readable for Rgb {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         let mut rgb = [0u8; 3];
-//         reader.read_exact(&mut rgb)?;
-//         let [r,g,b] = rgb;
-//         Ok(Rgb::new(r,g,b))
-//     }
-// }
-
-// impl Writeable for Rgb {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         let buf = [self.r, self.g, self.b];
-//         writer.write_all(&buf)?;
-//         Ok(3)
-//     }
-// }
-
-// impl Readable for Rgba {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         let mut buffer = [0u8; 4];
-//         reader.read_exact(&mut buffer)?;
-//         let [r, g, b, a] = buffer;
-//         Ok(Rgba::new(r, g, b, a))
-//     }
-// }
-
-// impl Writeable for Rgba {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         let buf = [self.r, self.g, self.b, self.a];
-//         writer.write_all(&buf)?;
-//         Ok(4)
-//     }
-// }
-
 impl_nonbyte!(IVec2);
 
 impl Readable for IVec2 {
@@ -972,6 +932,203 @@ impl<T: Writeable> Writeable for Box<T> {
     }
 }
 
+impl<T: Readable + Writeable> NonByte for Box<[T]> {}
+
+impl<T: Readable + NonByte> Readable for Box<[T]> {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(read_vec::<T, R>(reader)?.into_boxed_slice())
+    }
+}
+
+impl<T: Writeable + NonByte> Writeable for Box<[T]> {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        write_slice(writer, self)
+    }
+}
+
+// Boxed slices of bytes are written like a Vec of them, in a single call.
+// Other Byte types implement this next to their Vec impls, since a generic impl would overlap the one for NonByte types.
+macro_rules! byte_boxed_slice_io {
+    ($($type:ty),*) => {
+        $(
+            impl Readable for Box<[$type]> {
+                fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+                    Ok(Vec::<$type>::read_from(reader)?.into_boxed_slice())
+                }
+            }
+
+            impl Writeable for Box<[$type]> {
+                fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+                    if self.len() > MAX_ARRAY_LENGTH {
+                        return Err(Error::ArrayTooLong);
+                    }
+                    Ok(write_u24(writer, self.len() as u32)? + write_bytes(writer, bytemuck::cast_slice(self))?)
+                }
+            }
+        )*
+    };
+}
+
+byte_boxed_slice_io!(u8, i8, bool);
+
+impl<T: Readable + Writeable> NonByte for Option<T> {}
+
+/// Written as a `0` byte for [None], or a `1` byte followed by the value for [Some].
+impl<T: Readable> Readable for Option<T> {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        match u8::read_from(reader)? {
+            0 => Ok(None),
+            1 => Ok(Some(T::read_from(reader)?)),
+            _ => Err(Error::InvalidBinaryFormat),
+        }
+    }
+}
+
+impl<T: Writeable> Writeable for Option<T> {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        match self {
+            None => 0u8.write_to(writer),
+            Some(value) => Ok(1u8.write_to(writer)? + value.write_to(writer)?),
+        }
+    }
+}
+
+impl<T: Readable + Writeable, E: Readable + Writeable> NonByte for std::result::Result<T, E> {}
+
+/// Written as a `0` byte followed by the value for [Ok], or a `1` byte followed by the error for [Err].
+impl<T: Readable, E: Readable> Readable for std::result::Result<T, E> {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        match u8::read_from(reader)? {
+            0 => Ok(Ok(T::read_from(reader)?)),
+            1 => Ok(Err(E::read_from(reader)?)),
+            _ => Err(Error::InvalidBinaryFormat),
+        }
+    }
+}
+
+impl<T: Writeable, E: Writeable> Writeable for std::result::Result<T, E> {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        match self {
+            Ok(value) => Ok(0u8.write_to(writer)? + value.write_to(writer)?),
+            Err(error) => Ok(1u8.write_to(writer)? + error.write_to(writer)?),
+        }
+    }
+}
+
+impl<T: Readable + Writeable, const SIZE: usize> NonByte for [T; SIZE] {}
+
+/// Arrays have a fixed size, so unlike [Vec] they are written without a length.
+impl<T: Readable + NonByte, const SIZE: usize> Readable for [T; SIZE] {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let items = (0..SIZE)
+            .map(|index| T::read_from(reader).map_err(|error| error.at_index(index)))
+            .collect::<Result<Vec<T>>>()?;
+        Ok(items.try_into().unwrap_or_else(|_| unreachable!("the Vec has exactly SIZE items")))
+    }
+}
+
+impl<T: Writeable + NonByte, const SIZE: usize> Writeable for [T; SIZE] {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        self.iter().try_fold(0u64, |length, item| Ok(length + item.write_to(writer)?))
+    }
+}
+
+// Arrays of bytes are read and written in a single call rather than element by element.
+// Other Byte types use read_byte_array and write_byte_array.
+macro_rules! byte_array_io {
+    ($type:ty: $decode:expr) => {
+        impl<const SIZE: usize> Readable for [$type; SIZE] {
+            fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+                let mut buffer = [0u8; SIZE];
+                reader.read_exact(&mut buffer)?;
+                Ok(buffer.map($decode))
+            }
+        }
+
+        impl<const SIZE: usize> Writeable for [$type; SIZE] {
+            fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+                write_bytes(writer, bytemuck::cast_slice(self.as_slice()))
+            }
+        }
+    };
+}
+
+byte_array_io!(u8: |byte| byte);
+byte_array_io!(i8: |byte| byte as i8);
+byte_array_io!(bool: |byte| byte != 0);
+
+impl<K: Readable + Writeable, V: Readable + Writeable, S> NonByte for HashMap<K, V, S> {}
+
+/// Written as a `u24` length followed by each key and value. Duplicate keys are rejected when reading.
+impl<K: Readable + Eq + Hash, V: Readable, S: BuildHasher + Default> Readable for HashMap<K, V, S> {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let length = read_u24(reader)? as usize;
+        let mut map = HashMap::with_capacity_and_hasher(length.min(MAX_PREALLOCATE), S::default());
+        for index in 0..length {
+            let key = K::read_from(reader).map_err(|error| error.at_field("key").at_index(index))?;
+            let value = V::read_from(reader).map_err(|error| error.at_field("value").at_index(index))?;
+            if map.insert(key, value).is_some() {
+                return Err(Error::InvalidBinaryFormat);
+            }
+        }
+        Ok(map)
+    }
+}
+
+impl<K: Writeable, V: Writeable, S> Writeable for HashMap<K, V, S> {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        if self.len() > MAX_ARRAY_LENGTH {
+            return Err(Error::ArrayTooLong);
+        }
+        self.iter().try_fold(write_u24(writer, self.len() as u32)?, |length, (key, value)| {
+            Ok(length + key.write_to(writer)? + value.write_to(writer)?)
+        })
+    }
+}
+
+impl<K: Readable + Writeable, V: Readable + Writeable> NonByte for BTreeMap<K, V> {}
+
+/// Written as a `u24` length followed by each key and value in order. Duplicate keys are rejected when reading.
+impl<K: Readable + Ord, V: Readable> Readable for BTreeMap<K, V> {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let length = read_u24(reader)?;
+        let mut map = BTreeMap::new();
+        for index in 0..length as usize {
+            let key = K::read_from(reader).map_err(|error| error.at_field("key").at_index(index))?;
+            let value = V::read_from(reader).map_err(|error| error.at_field("value").at_index(index))?;
+            if map.insert(key, value).is_some() {
+                return Err(Error::InvalidBinaryFormat);
+            }
+        }
+        Ok(map)
+    }
+}
+
+impl<K: Writeable, V: Writeable> Writeable for BTreeMap<K, V> {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        if self.len() > MAX_ARRAY_LENGTH {
+            return Err(Error::ArrayTooLong);
+        }
+        self.iter().try_fold(write_u24(writer, self.len() as u32)?, |length, (key, value)| {
+            Ok(length + key.write_to(writer)? + value.write_to(writer)?)
+        })
+    }
+}
+
+impl NonByte for () {}
+
+impl Readable for () {
+    fn read_from<R: Read>(_reader: &mut R) -> Result<Self> {
+        Ok(())
+    }
+}
+
+impl Writeable for () {
+    fn write_to<W: Write>(&self, _writer: &mut W) -> Result<u64> {
+        Ok(0)
+    }
+}
+
 impl Readable for Vec<bool> {
     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
         let mut buf = [0u8; 4];
@@ -1106,455 +1263,6 @@ impl Writeable for Vec<i8> {
     }
 }
 
-// impl Readable for Vec<Color> {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         let mut buf = [0u8; 4];
-//         reader.read_exact(&mut buf[1..4])?;
-//         let length = u32::from_be_bytes(buf);
-//         let bytes = read_bytes(reader, length as usize)?;
-//         Ok(bytes.into_iter().map(|byte| Color::from_byte(byte).unwrap_or(Color::Black)).collect())
-//     }
-// }
-
-// impl Writeable for Vec<Color> {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         if self.len() > MAX_ARRAY_LENGTH {
-//             return Err(Error::ArrayTooLong);
-//         }
-//         let buf = (self.len() as u32).to_be_bytes();
-//         writer.write_all(&buf[1..4])?;
-//         writer.write_all(bytemuck::cast_slice(self.as_slice()))?;
-//         Ok(self.len() as u64 + 3)
-//     }
-// }
-
-// impl Readable for Vec<Direction> {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         let mut buf = [0u8; 4];
-//         reader.read_exact(&mut buf[1..4])?;
-//         let length = u32::from_be_bytes(buf);
-//         let bytes = read_bytes(reader, length as usize)?;
-//         const NEGX: u8 = Direction::NegX as u8;
-//         const NEGY: u8 = Direction::NegY as u8;
-//         const NEGZ: u8 = Direction::NegZ as u8;
-//         const POSX: u8 = Direction::PosX as u8;
-//         const POSY: u8 = Direction::PosY as u8;
-//         const POSZ: u8 = Direction::PosZ as u8;
-//         Ok(bytes.into_iter().map(|b| match b {
-//             NEGX => Direction::NegX,
-//             NEGY => Direction::NegY,
-//             NEGZ => Direction::NegZ,
-//             POSX => Direction::PosX,
-//             POSY => Direction::PosY,
-//             POSZ => Direction::PosZ,
-//             _ => panic!("Invalid binary format for Direction (sorry for the panic)"),
-//         }).collect())
-//     }
-// }
-
-// impl Writeable for Vec<Direction> {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         if self.len() > MAX_ARRAY_LENGTH {
-//             return Err(Error::ArrayTooLong);
-//         }
-//         let buf = (self.len() as u32).to_be_bytes();
-//         writer.write_all(&buf[1..4])?;
-//         writer.write_all(bytemuck::cast_slice(self.as_slice()))?;
-//         Ok(self.len() as u64 + 3)
-//     }
-// }
-
-// impl Readable for Vec<Cardinal> {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         let length = read_u24(reader)?;
-//         if length == 0 {
-//             return Ok(Vec::new());
-//         }
-//         let bit_len = length * 2;
-//         let byte_count = if bit_len % 8 != 0 {
-//             bit_len / 8 + 1
-//         } else {
-//             bit_len / 8
-//         } as usize;
-//         let bytes = read_bytes(reader, byte_count as usize)?;
-//         // I'm taking advantage of the fact that Cardinal only takes up
-//         // 2 bits, which means that 4 of them fit in a byte
-//         // and there's no cross-byte splitting.
-//         struct BitReader<'a> {
-//             bytes: &'a [u8],
-//             top: usize,
-//         }
-//         impl<'a> BitReader<'a> {
-//             fn new(bytes: &'a [u8]) -> Self {
-//                 Self {
-//                     bytes,
-//                     top: 0,
-//                 }
-//             }
-//             fn read_next(&mut self) -> Result<Cardinal> {
-//                 let byte_index = self.top / 8;
-//                 let byte = self.bytes[byte_index];
-//                 let sub_index = self.top % 8;
-//                 let bits = (byte >> sub_index) & 0b11;
-//                 self.top += 2;
-//                 Ok(match bits {
-//                     0 => Cardinal::West,
-//                     1 => Cardinal::North,
-//                     2 => Cardinal::East,
-//                     3 => Cardinal::South,
-//                     // We know this is unreachable because bits was & with 0b11.
-//                     _ => unreachable!(),
-//                 })
-//             }
-//         }
-//         let mut bit_reader = BitReader::new(&bytes);
-//         let mut accum = Vec::with_capacity(length as usize);
-//         for _ in 0..length {
-//             accum.push(bit_reader.read_next()?);
-//         }
-//         Ok(accum)
-//     }
-// }
-
-// impl Writeable for Vec<Cardinal> {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         if self.len() > MAX_ARRAY_LENGTH {
-//             return Err(Error::ArrayTooLong);
-//         }
-//         struct BitWriter {
-//             top: u32,
-//             accum: u16,
-//             bytes: Box<[u8]>,
-//             index: usize,
-//         }
-//         impl BitWriter {
-//             fn new(count: usize) -> Self {
-//                 let bit_len = count * 2;
-//                 let byte_count = if bit_len % 8 != 0 {
-//                     bit_len / 8 + 1
-//                 } else {
-//                     bit_len / 8
-//                 };
-//                 Self {
-//                     top: 0,
-//                     accum: 0,
-//                     index: 0,
-//                     bytes: (0..byte_count).map(|_| 0u8).collect(),
-//                 }
-//             }
-
-//             fn push_byte(&mut self, byte: u8) {
-//                 self.bytes[self.index.post_increment()] = byte;
-//             }
-
-//             fn push_bits(&mut self, bits: u8) {
-//                 let bits = bits as u16;
-//                 self.accum |= bits << self.top;
-//                 self.top += 2;
-//                 if self.top >= 8 {
-//                     let byte = (self.accum & 0xff) as u8;
-//                     self.push_byte(byte);
-//                     self.accum >>= 8;
-//                     self.top -= 8;
-//                 }
-//             }
-
-//             fn finish(mut self) -> Box<[u8]> {
-//                 if self.top % 8 != 0 {
-//                     self.push_byte((self.accum & 0xff) as u8);
-//                 }
-//                 self.bytes
-//             }
-//         }
-//         let mut bit_writer = BitWriter::new(self.len());
-//         self.iter().map(|&cardinal| {
-//             match cardinal {
-//                 Cardinal::West => 0u8,
-//                 Cardinal::North => 1u8,
-//                 Cardinal::East => 2u8,
-//                 Cardinal::South => 3u8,
-//             }
-//         }).for_each(|bits| {
-//             bit_writer.push_bits(bits);
-//         });
-//         Ok(
-//             write_u24(writer, self.len() as u32)? +
-//             write_bytes(writer, &bit_writer.finish())?
-//         )
-//     }
-// }
-
-// impl Readable for Vec<Rotation> {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         let mut buf = [0u8; 4];
-//         reader.read_exact(&mut buf[1..4])?;
-//         let length = u32::from_be_bytes(buf);
-//         let bytes = read_bytes(reader, length as usize)?;
-//         Ok(bytes.into_iter().map(|b| Rotation(b)).collect())
-//     }
-// }
-
-// impl Writeable for Vec<Rotation> {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         if self.len() > MAX_ARRAY_LENGTH {
-//             return Err(Error::ArrayTooLong);
-//         }
-//         let buf = (self.len() as u32).to_be_bytes();
-//         writer.write_all(&buf[1..4])?;
-//         writer.write_all(bytemuck::cast_slice(self.as_slice()))?;
-//         Ok(self.len() as u64 + 3)
-//     }
-// }
-
-// impl Readable for Vec<Flip> {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         let length = read_u24(reader)?;
-//         let bit_width = 3 * length;
-//         let byte_count = bit_width / 8 + (bit_width % 8 != 0) as u32;
-//         let bytes = read_bytes(reader, byte_count as usize)?;
-//         let mut flips = (0..length).map(|_| Flip::NONE).collect_vec();
-//         struct BitReader<'a> {
-//             flips: &'a mut [Flip],
-//             index: usize,
-//             accum: u8,
-//             accum_size: u32,
-//         }
-//         impl<'a> BitReader<'a> {
-//             const BIT_WIDTH: u32 = 3;
-//             fn push_value(&mut self, value: u8) {
-//                 self.flips[self.index] = Flip(value);
-//                 self.index += 1;
-//             }
-
-//             fn push_bits(&mut self, bits: u8, count: u32) {
-//                 if self.index == self.flips.len() {
-//                     return;
-//                 }
-//                 let space = Self::BIT_WIDTH - self.accum_size;
-//                 if space >= count {
-//                     let start = self.accum_size;
-//                     let end = start + count;
-//                     self.accum = self.accum.set_bitmask(start..end, bits);
-//                     self.accum_size += count;
-//                     if self.accum_size == Self::BIT_WIDTH {
-//                         self.push_value(self.accum);
-//                         self.accum = 0;
-//                         self.accum_size = 0;
-//                     }
-//                 } else {
-//                     self.push_bits(bits, space);
-//                     self.push_bits(bits >> space, count - space);
-//                 }
-//             }
-//         }
-//         let mut bitreader = BitReader {
-//             flips: &mut flips,
-//             index: 0,
-//             accum: 0,
-//             accum_size: 0
-//         };
-//         bytes.into_iter().for_each(|byte| bitreader.push_bits(byte, 8));
-//         debug_assert_eq!(bitreader.flips.len(), bitreader.index);
-//         Ok(flips)
-//     }
-// }
-
-// impl Writeable for Vec<Flip> {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         if self.len() > 0xffffff {
-//             return Err(Error::ArrayTooLong);
-//         }
-//         let length = write_u24(writer, self.len() as u32)?;
-//         let bit_width = 3 * self.len() as u32;
-//         let byte_count = bit_width / 8 + (bit_width % 8 != 0) as u32;
-//         let mut bytes = (0..byte_count).map(|_| 0u8).collect::<Box<_>>();
-//         struct BitWriter<'a> {
-//             bytes: &'a mut [u8],
-//             index: usize,
-//             accum: u8,
-//             accum_size: u32,
-//         }
-//         impl<'a> BitWriter<'a> {
-//             const BIT_WIDTH: u32 = 3;
-//             fn push_byte(&mut self, byte: u8) {
-//                 self.bytes[self.index] = byte;
-//                 self.index += 1;
-//             }
-
-//             fn push_flip(&mut self, Flip(flip): Flip) {
-//                 let end_size = 8 - self.accum_size;
-//                 if end_size < Self::BIT_WIDTH {
-//                     let mask = flip.get_bitmask(0..end_size) as u8;
-//                     self.push_byte(self.accum | mask << self.accum_size);
-//                     self.accum = flip >> end_size;
-//                     self.accum_size = Self::BIT_WIDTH - end_size;
-//                 } else { // end_size >= bit_width
-//                     let start = self.accum_size;
-//                     let end = start + Self::BIT_WIDTH;
-//                     self.accum = self.accum.set_bitmask(start..end, flip);
-//                     self.accum_size += Self::BIT_WIDTH;
-//                     if self.accum_size == 8 {
-//                         self.push_byte(self.accum);
-//                         self.accum = 0;
-//                         self.accum_size = 0;
-//                     }
-//                 }
-//             }
-//         }
-//         let mut bitwriter = BitWriter {
-//             bytes: &mut bytes,
-//             index: 0,
-//             accum: 0,
-//             accum_size: 0
-//         };
-//         self.iter().cloned().for_each(|flip| {
-//             bitwriter.push_flip(flip);
-//         });
-//         if bitwriter.accum_size > 0 {
-//             bitwriter.push_byte(bitwriter.accum);
-//         }
-//         Ok(length + write_bytes(writer, &bytes)?)
-//     }
-// }
-
-// impl Readable for Vec<Orientation> {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         let length = read_u24(reader)?;
-//         let bytes = read_bytes(reader, length as usize)?;
-//         Ok(bytes.into_iter().map(|packed| Orientation(packed)).collect())
-//     }
-// }
-
-// impl Writeable for Vec<Orientation> {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         if self.len() > MAX_ARRAY_LENGTH {
-//             return Err(Error::ArrayTooLong);
-//         }
-//         let length = write_u24(writer, self.len() as u32)?;
-//         let bytes = self.iter().cloned().map(|orientation| orientation.0).collect_vec();
-//         Ok(length + write_bytes(writer, &bytes)?)
-//     }
-// }
-
-// impl Readable for Vec<Axis> {
-//     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-//         let length = read_u24(reader)?;
-//         if length == 0 {
-//             return Ok(Vec::new());
-//         }
-//         let bit_len = length * 2;
-//         let byte_count = if bit_len % 8 != 0 {
-//             bit_len / 8 + 1
-//         } else {
-//             bit_len / 8
-//         } as usize;
-//         let bytes = read_bytes(reader, byte_count as usize)?;
-//         // I'm taking advantage of the fact that Axis only takes up
-//         // 2 bits, which means that 4 of them fit in a byte
-//         // and there's no cross-byte splitting.
-//         struct BitReader<'a> {
-//             bytes: &'a [u8],
-//             top: usize,
-//         }
-//         impl<'a> BitReader<'a> {
-//             fn new(bytes: &'a [u8]) -> Self {
-//                 Self {
-//                     bytes,
-//                     top: 0,
-//                 }
-//             }
-//             fn read_next(&mut self) -> Result<Axis> {
-//                 let byte_index = self.top / 8;
-//                 let byte = self.bytes[byte_index];
-//                 let sub_index = self.top % 8;
-//                 let bits = (byte >> sub_index) & 0b11;
-//                 self.top += 2;
-//                 Ok(match bits {
-//                     0 => Axis::X,
-//                     1 => Axis::Y,
-//                     2 => Axis::Z,
-//                     3 => return Err(Error::InvalidBinaryFormat),
-//                     // We know this is unreachable because bits was & with 0b11.
-//                     _ => unreachable!(),
-//                 })
-//             }
-//         }
-//         let mut bit_reader = BitReader::new(&bytes);
-//         let mut accum = Vec::with_capacity(length as usize);
-//         for _ in 0..length {
-//             accum.push(bit_reader.read_next()?);
-//         }
-//         Ok(accum)
-//     }
-// }
-
-// impl Writeable for Vec<Axis> {
-//     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
-//         if self.len() > MAX_ARRAY_LENGTH {
-//             return Err(Error::ArrayTooLong);
-//         }
-//         struct BitWriter {
-//             top: u32,
-//             accum: u16,
-//             bytes: Box<[u8]>,
-//             index: usize,
-//         }
-//         impl BitWriter {
-//             fn new(count: usize) -> Self {
-//                 let bit_len = count * 2;
-//                 let byte_count = if bit_len % 8 != 0 {
-//                     bit_len / 8 + 1
-//                 } else {
-//                     bit_len / 8
-//                 };
-//                 Self {
-//                     top: 0,
-//                     accum: 0,
-//                     index: 0,
-//                     bytes: (0..byte_count).map(|_| 0u8).collect(),
-//                 }
-//             }
-
-//             fn push_byte(&mut self, byte: u8) {
-//                 self.bytes[self.index.post_increment()] = byte;
-//             }
-
-//             fn push_bits(&mut self, bits: u8) {
-//                 let bits = bits as u16;
-//                 self.accum |= bits << self.top;
-//                 self.top += 2;
-//                 if self.top >= 8 {
-//                     let byte = (self.accum & 0xff) as u8;
-//                     self.push_byte(byte);
-//                     self.accum >>= 8;
-//                     self.top -= 8;
-//                 }
-//             }
-
-//             fn finish(mut self) -> Box<[u8]> {
-//                 if self.top % 8 != 0 {
-//                     self.push_byte((self.accum & 0xff) as u8);
-//                 }
-//                 self.bytes
-//             }
-//         }
-//         let mut bit_writer = BitWriter::new(self.len());
-//         self.iter().map(|&axis| {
-//             match axis {
-//                 Axis::X => 0u8,
-//                 Axis::Y => 1u8,
-//                 Axis::Z => 2u8,
-//             }
-//         }).for_each(|bits| {
-//             bit_writer.push_bits(bits);
-//         });
-//         Ok(
-//             write_u24(writer, self.len() as u32)? +
-//             write_bytes(writer, &bit_writer.finish())?
-//         )
-//     }
-// }
-
 // impl Readable for hashbrown::HashMap<String, Tag> {
 //     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
 //         // read name, read tag, use 255 as Stop
@@ -1751,21 +1459,10 @@ mark!{trait = Deterministic;
     <T: Deterministic> for BTreeSet<T>;
     <T: Deterministic> for std::ops::Range<T>;
     <T: Deterministic> for std::ops::RangeInclusive<T>;
-    // hexahedron types.
-    // Axis;
-    // Orientation;
-    // Flip;
-    // Rotation;
-    // Cardinal;
-    // Direction;
-    // Color;
-    // Rgb;
-    // Rgba;
-    // BitFlags8;
-    // BitFlags16;
-    // BitFlags32;
-    // BitFlags64;
-    // BitFlags128;
+    <T: Deterministic> for Option<T>;
+    <T, E> for std::result::Result<T, E> where (T, E): Deterministic;
+    ();
+    // hexahedron types. The types from hexorient, hexcolor and hexcore are marked in their feature modules.
     // FaceFlags;
     // AxisFlags;
     // Property;
@@ -1777,4 +1474,241 @@ mark!{trait = Deterministic;
     // rollgrid
     // Bounds2D;
     // Bounds3D;
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::error::PathSegment;
+
+    #[derive(Debug, Clone, PartialEq, Readable, Writeable)]
+    struct Header {
+        id: u32,
+        name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Readable, Writeable)]
+    struct Message<T> {
+        header: Header,
+        #[io(with = "u16_as_u8")]
+        count: u16,
+        payload: T,
+        #[io(skip)]
+        received: bool,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Readable, Writeable)]
+    struct Position(i32, i32);
+
+    #[derive(Debug, Clone, PartialEq, Readable, Writeable)]
+    #[repr(u8)]
+    enum Packet {
+        Ping,
+        #[io(tag = 5)]
+        Move { x: f32, y: f32 },
+        Chat(String),
+        Teleport(Position) = 10,
+    }
+
+    /// Writes a `u16` that is known to be small as a single byte.
+    mod u16_as_u8 {
+        use super::*;
+
+        pub fn read_from<R: Read>(reader: &mut R) -> Result<u16> {
+            Ok(u8::read_from(reader)? as u16)
+        }
+
+        pub fn write_to<W: Write>(value: &u16, writer: &mut W) -> Result<u64> {
+            u8::try_from(*value).map_err(|_| Error::Custom("value too large"))?.write_to(writer)
+        }
+    }
+
+    fn round_trip<T: Readable + Writeable>(value: &T) -> (T, Vec<u8>) {
+        let mut buffer = Vec::new();
+        let size = value.write_to(&mut buffer).unwrap();
+        debug_assert_eq!(size, buffer.len() as u64);
+        (T::read_from(&mut Cursor::new(&buffer)).unwrap(), buffer)
+    }
+
+    #[test]
+    fn derive_struct_test() {
+        let message = Message {
+            header: Header { id: 7, name: "hi".to_owned() },
+            count: 3,
+            payload: Position(-1, 2),
+            received: true,
+        };
+        let (read, bytes) = round_trip(&message);
+        debug_assert_eq!(read, Message { received: false, ..message });
+        // Fields are written in order, and `count` is a single byte.
+        debug_assert_eq!(bytes, [
+            0, 0, 0, 7,
+            0, 0, 2, b'h', b'i',
+            3,
+            0xff, 0xff, 0xff, 0xff,
+            0, 0, 0, 2,
+        ]);
+    }
+
+    #[test]
+    fn derive_enum_test() {
+        for (packet, tag) in [
+            (Packet::Ping, 0u8),
+            (Packet::Move { x: 1.0, y: -2.5 }, 5),
+            (Packet::Chat("hello".to_owned()), 6),
+            (Packet::Teleport(Position(4, 5)), 10),
+        ] {
+            let (read, bytes) = round_trip(&packet);
+            debug_assert_eq!(read, packet);
+            debug_assert_eq!(bytes[0], tag);
+        }
+        let result = Packet::read_from(&mut Cursor::new([1u8]));
+        debug_assert!(matches!(result, Err(Error::InvalidBinaryFormat)));
+    }
+
+    #[test]
+    fn length_prefix_test() {
+        let values = vec![1u16, 2, 3];
+        for (prefix, length) in [
+            (LengthPrefix::U24, 3),
+            (LengthPrefix::U32, 4),
+            (LengthPrefix::U32Le, 4),
+            (LengthPrefix::VarInt, 1),
+        ] {
+            let mut buffer = Vec::new();
+            let size = write_slice_with(&mut buffer, &values, prefix).unwrap();
+            debug_assert_eq!(size, length + 6);
+            debug_assert_eq!(size, buffer.len() as u64);
+            debug_assert_eq!(read_vec_with::<u16, _>(&mut Cursor::new(&buffer), prefix).unwrap(), values);
+        }
+        let mut buffer = Vec::new();
+        write_slice_with(&mut buffer, &[0u8; 300], LengthPrefix::VarInt).unwrap();
+        debug_assert_eq!(&buffer[..2], [0xac, 0x02]);
+        // The default prefix is the one used by `read_vec` and `write_slice`.
+        let mut buffer = Vec::new();
+        write_slice(&mut buffer, &values).unwrap();
+        debug_assert_eq!(read_vec_with::<u16, _>(&mut Cursor::new(&buffer), LengthPrefix::default()).unwrap(), values);
+        // A length that is larger than the stream fails without allocating all of it.
+        let bytes = [0xff, 0xff, 0xff, 0x0f];
+        debug_assert!(read_vec_with::<u64, _>(&mut Cursor::new(bytes), LengthPrefix::VarInt).is_err());
+    }
+
+    #[test]
+    fn generic_impls_test() {
+        let (value, bytes) = round_trip(&(Some(5u16), None::<u16>));
+        debug_assert_eq!(value, (Some(5), None));
+        debug_assert_eq!(bytes, [1, 0, 5, 0]);
+        let results: Vec<std::result::Result<u8, String>> = vec![Ok(1), Err(String::from("no"))];
+        debug_assert_eq!(round_trip(&results).0, results);
+        debug_assert_eq!(round_trip(&[[1u16, 2], [3, 4]]), ([[1, 2], [3, 4]], vec![0, 1, 0, 2, 0, 3, 0, 4]));
+        // Byte arrays are written as is.
+        debug_assert_eq!(round_trip(&[1u8, 2, 3]).1, [1, 2, 3]);
+        debug_assert_eq!(round_trip(&[-1i8, 2]).1, [0xff, 2]);
+        debug_assert_eq!(round_trip(&[true, false]).1, [1, 0]);
+        debug_assert_eq!(round_trip(&[0u8; 0]).1, []);
+        let boxed: Box<[u32]> = Box::new([7, 8]);
+        debug_assert_eq!(round_trip(&boxed), (boxed.clone(), vec![0, 0, 2, 0, 0, 0, 7, 0, 0, 0, 8]));
+        let boxed: Box<[u8]> = Box::new([7, 8]);
+        debug_assert_eq!(round_trip(&boxed), (boxed.clone(), vec![0, 0, 2, 7, 8]));
+        let boxed: Box<[i8]> = Box::new([-1, 8]);
+        debug_assert_eq!(round_trip(&boxed), (boxed.clone(), vec![0, 0, 2, 0xff, 8]));
+        let boxed: Box<[bool]> = Box::new([true, false]);
+        debug_assert_eq!(round_trip(&boxed), (boxed.clone(), vec![0, 0, 2, 1, 0]));
+        let tree = BTreeMap::from([((1u8, 2u8), vec![String::from("a")]), ((0, 9), vec![])]);
+        let (value, bytes) = round_trip(&tree);
+        debug_assert_eq!(value, tree);
+        // Ordered by key.
+        debug_assert_eq!(&bytes[..6], [0, 0, 2, 0, 9, 0]);
+        let map = HashMap::from([(1i32, Some(1.5f32)), (-4, None), (9, Some(0.0))]);
+        debug_assert_eq!(round_trip(&map).0, map);
+        debug_assert_eq!(round_trip(&()).1, []);
+    }
+
+    #[test]
+    fn generic_impls_invalid_test() {
+        debug_assert!(matches!(Option::<u8>::read_from(&mut Cursor::new([2, 0])), Err(Error::InvalidBinaryFormat)));
+        debug_assert!(matches!(
+            std::result::Result::<u8, u8>::read_from(&mut Cursor::new([2, 0])),
+            Err(Error::InvalidBinaryFormat)
+        ));
+        // The same key twice.
+        let bytes = [0, 0, 2, 1, 10, 1, 20];
+        debug_assert!(matches!(BTreeMap::<u8, u8>::read_from(&mut Cursor::new(bytes)), Err(Error::InvalidBinaryFormat)));
+        debug_assert!(matches!(HashMap::<u8, u8>::read_from(&mut Cursor::new(bytes)), Err(Error::InvalidBinaryFormat)));
+        debug_assert!(matches!(<[u8; 4]>::read_from(&mut Cursor::new([1, 2, 3])), Err(Error::IoError(_))));
+    }
+
+    #[derive(Debug, Readable, Writeable)]
+    struct Chunk {
+        section: Vec<Section>,
+    }
+
+    #[derive(Debug, Readable, Writeable)]
+    struct Section {
+        tags: Vec<Tag>,
+    }
+
+    #[derive(Debug, Readable, Writeable)]
+    struct Tag {
+        name: String,
+        value: char,
+    }
+
+    impl NonByte for Section {}
+    impl NonByte for Tag {}
+    impl NonByte for Packet {}
+
+    #[test]
+    fn error_context_test() {
+        let section = || Section {
+            tags: (0..16).map(|_| Tag { name: String::from("tag"), value: 'x' }).collect(),
+        };
+        let chunk = Chunk { section: (0..8).map(|_| section()).collect() };
+        let mut bytes = Vec::new();
+        chunk.write_to(&mut bytes).unwrap();
+        // Each section is 3 bytes of length and 16 tags of 10 bytes.
+        let value = 3 + 4 * 163 + 3 + 12 * 10 + 6;
+        debug_assert_eq!(u32::read_from(&mut Cursor::new(&bytes[value..])).unwrap(), 'x' as u32);
+        bytes[value..value + 4].copy_from_slice(&0xd800u32.to_be_bytes());
+        let error = read_with_context::<Chunk, _>(&mut Cursor::new(&bytes)).unwrap_err();
+        debug_assert!(matches!(error.root(), Error::InvalidCodepoint));
+        debug_assert_eq!(error.to_string(), format!("section[4].tags[12].value: Invalid codepoint at byte {}", value + 4));
+        let Error::Context(context) = &error else {
+            panic!("Expected an error with context, found {error:?}");
+        };
+        debug_assert_eq!(context.path().collect::<Vec<_>>(), [
+            &PathSegment::Field("section"),
+            &PathSegment::Index(4),
+            &PathSegment::Field("tags"),
+            &PathSegment::Index(12),
+            &PathSegment::Field("value"),
+        ]);
+        debug_assert_eq!(context.offset(), Some(value as u64 + 4));
+        // Without `read_with_context` there is a path but no offset.
+        let error = Chunk::read_from(&mut Cursor::new(&bytes)).unwrap_err();
+        debug_assert_eq!(error.to_string(), "section[4].tags[12].value: Invalid codepoint");
+        // Enum variants and tuple fields.
+        let error = read_with_context::<Vec<Packet>, _>(&mut Cursor::new([0, 0, 2, 0, 10, 0, 0, 0, 1])).unwrap_err();
+        debug_assert!(matches!(error.root(), Error::IoError(_)));
+        debug_assert!(error.to_string().starts_with("[1].Packet::Teleport.0.1: "));
+        debug_assert!(error.to_string().ends_with(" at byte 9"));
+        // A message describing what was being read goes before the path.
+        let error = read_with_context::<Chunk, _>(&mut Cursor::new(&bytes))
+            .map_err(|error| error.context(format_args!("failed to load chunk ({}, {})", 3, -7)))
+            .unwrap_err();
+        debug_assert_eq!(
+            error.to_string(),
+            format!("failed to load chunk (3, -7): section[4].tags[12].value: Invalid codepoint at byte {}", value + 4),
+        );
+        debug_assert!(matches!(error.root(), Error::InvalidCodepoint));
+        let error = Error::InvalidBinaryFormat.context("inner").at_index(2).context("outer");
+        debug_assert_eq!(error.to_string(), "outer: [2]: inner: Invalid binary format");
+        // Map entries say whether the key or the value failed.
+        let error = BTreeMap::<u8, char>::read_from(&mut Cursor::new([0, 0, 1, 7, 0, 0, 0xd8, 0])).unwrap_err();
+        debug_assert_eq!(error.to_string(), "[0].value: Invalid codepoint");
+        let error = HashMap::<char, u8>::read_from(&mut Cursor::new([0, 0, 1, 0, 0, 0xd8, 0])).unwrap_err();
+        debug_assert_eq!(error.to_string(), "[0].key: Invalid codepoint");
+    }
+}
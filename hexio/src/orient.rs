@@ -0,0 +1,252 @@
+//! Impls for the orientation types from `hexorient`, enabled by the `hexorient` feature.
+//!
+//! Vecs and boxed slices of [Cardinal], [Axis] and [Flip] are bit packed, the rest use a byte per element.
+//! Arrays always use a byte per element.
+
+use std::io::{Read, Write};
+
+use hexmacros::mark;
+use hexorient::{cardinal::Cardinal, Axis, Direction, Flip, Orientation, Rotation};
+
+use crate::error::{Error, Result};
+use crate::{
+    read_byte_array, read_byte_vec, read_packed_vec, write_byte_array, write_byte_slice, write_packed_slice, Byte, Readable,
+    Writeable,
+};
+
+/// The number of distinct rotations.
+const ROTATION_COUNT: u8 = 24;
+
+fn direction_from_byte(byte: u8) -> Result<Direction> {
+    const NEGX: u8 = Direction::NegX as u8;
+    const NEGY: u8 = Direction::NegY as u8;
+    const NEGZ: u8 = Direction::NegZ as u8;
+    const POSX: u8 = Direction::PosX as u8;
+    const POSY: u8 = Direction::PosY as u8;
+    const POSZ: u8 = Direction::PosZ as u8;
+    Ok(match byte {
+        NEGX => Direction::NegX,
+        NEGY => Direction::NegY,
+        NEGZ => Direction::NegZ,
+        POSX => Direction::PosX,
+        POSY => Direction::PosY,
+        POSZ => Direction::PosZ,
+        _ => return Err(Error::InvalidBinaryFormat),
+    })
+}
+
+fn cardinal_from_byte(byte: u8) -> Result<Cardinal> {
+    Ok(match byte {
+        0 => Cardinal::West,
+        1 => Cardinal::North,
+        2 => Cardinal::East,
+        3 => Cardinal::South,
+        _ => return Err(Error::InvalidBinaryFormat),
+    })
+}
+
+fn axis_from_byte(byte: u8) -> Result<Axis> {
+    Ok(match byte {
+        0 => Axis::X,
+        1 => Axis::Y,
+        2 => Axis::Z,
+        _ => return Err(Error::InvalidBinaryFormat),
+    })
+}
+
+fn rotation_from_byte(byte: u8) -> Result<Rotation> {
+    if byte < ROTATION_COUNT {
+        Ok(Rotation(byte))
+    } else {
+        Err(Error::InvalidBinaryFormat)
+    }
+}
+
+fn flip_from_byte(byte: u8) -> Result<Flip> {
+    if byte <= Flip::ALL.0 {
+        Ok(Flip(byte))
+    } else {
+        Err(Error::InvalidBinaryFormat)
+    }
+}
+
+/// The flip is in the low 3 bits and the rotation is in the high 5 bits.
+fn orientation_from_byte(byte: u8) -> Result<Orientation> {
+    Ok(Orientation::new(rotation_from_byte(byte >> 3)?, Flip(byte & 0b111)))
+}
+
+fn orientation_to_byte(orientation: &Orientation) -> u8 {
+    orientation.flip().0 | orientation.rotation().0 << 3
+}
+
+macro_rules! byte_io {
+    ($type:ty: $decode:expr, $encode:expr) => {
+        impl Byte for $type {}
+
+        impl Readable for $type {
+            fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+                $decode(u8::read_from(reader)?)
+            }
+        }
+
+        impl Writeable for $type {
+            fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+                $encode(self).write_to(writer)
+            }
+        }
+
+        impl<const SIZE: usize> Readable for [$type; SIZE] {
+            fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+                read_byte_array(reader, $decode)
+            }
+        }
+
+        impl<const SIZE: usize> Writeable for [$type; SIZE] {
+            fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+                write_byte_array(writer, self, $encode)
+            }
+        }
+    };
+}
+
+byte_io!(Direction: direction_from_byte, |direction: &Direction| *direction as u8);
+byte_io!(Cardinal: cardinal_from_byte, |cardinal: &Cardinal| *cardinal as u8);
+byte_io!(Axis: axis_from_byte, |axis: &Axis| *axis as u8);
+byte_io!(Rotation: rotation_from_byte, |rotation: &Rotation| rotation.0);
+byte_io!(Flip: flip_from_byte, |flip: &Flip| flip.0);
+byte_io!(Orientation: orientation_from_byte, orientation_to_byte);
+
+macro_rules! byte_vec_io {
+    ($type:ty: $decode:expr, $encode:expr) => {
+        impl Readable for Vec<$type> {
+            fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+                read_byte_vec(reader, $decode)
+            }
+        }
+
+        impl Writeable for Vec<$type> {
+            fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+                write_byte_slice(writer, self, $encode)
+            }
+        }
+
+        impl Readable for Box<[$type]> {
+            fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+                Ok(read_byte_vec(reader, $decode)?.into_boxed_slice())
+            }
+        }
+
+        impl Writeable for Box<[$type]> {
+            fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+                write_byte_slice(writer, self, $encode)
+            }
+        }
+    };
+}
+
+byte_vec_io!(Direction: direction_from_byte, |direction: &Direction| *direction as u8);
+byte_vec_io!(Rotation: rotation_from_byte, |rotation: &Rotation| rotation.0);
+byte_vec_io!(Orientation: orientation_from_byte, orientation_to_byte);
+
+macro_rules! packed_vec_io {
+    ($type:ty: $bits:literal, $decode:expr, $encode:expr) => {
+        impl Readable for Vec<$type> {
+            fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+                read_packed_vec(reader, $bits, $decode)
+            }
+        }
+
+        impl Writeable for Vec<$type> {
+            fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+                write_packed_slice(writer, self, $bits, $encode)
+            }
+        }
+
+        impl Readable for Box<[$type]> {
+            fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+                Ok(read_packed_vec(reader, $bits, $decode)?.into_boxed_slice())
+            }
+        }
+
+        impl Writeable for Box<[$type]> {
+            fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+                write_packed_slice(writer, self, $bits, $encode)
+            }
+        }
+    };
+}
+
+packed_vec_io!(Cardinal: 2, cardinal_from_byte, |cardinal: &Cardinal| *cardinal as u8);
+packed_vec_io!(Axis: 2, axis_from_byte, |axis: &Axis| *axis as u8);
+packed_vec_io!(Flip: 3, flip_from_byte, |flip: &Flip| flip.0);
+
+mark!{trait = crate::Deterministic;
+    Direction;
+    Cardinal;
+    Axis;
+    Rotation;
+    Flip;
+    Orientation;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn round_trip<T: Readable + Writeable + PartialEq + std::fmt::Debug>(value: T) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        let size = value.write_to(&mut buffer).unwrap();
+        debug_assert_eq!(size, buffer.len() as u64);
+        debug_assert_eq!(T::read_from(&mut Cursor::new(&buffer)).unwrap(), value);
+        buffer
+    }
+
+    #[test]
+    fn orient_round_trip_test() {
+        let directions = vec![Direction::NegX, Direction::NegY, Direction::NegZ, Direction::PosX, Direction::PosY, Direction::PosZ];
+        for &direction in &directions {
+            debug_assert_eq!(round_trip(direction), [direction as u8]);
+        }
+        debug_assert_eq!(round_trip(directions).len(), 9);
+        let orientations = (0..ROTATION_COUNT)
+            .flat_map(|rotation| (0..8).map(move |flip| Orientation::new(Rotation(rotation), Flip(flip))))
+            .collect::<Vec<_>>();
+        for &orientation in &orientations {
+            round_trip(orientation);
+        }
+        round_trip(orientations);
+        // 2 bits each, so 5 cardinals take 2 bytes.
+        let cardinals = vec![Cardinal::West, Cardinal::North, Cardinal::East, Cardinal::South, Cardinal::South];
+        debug_assert_eq!(round_trip(cardinals), [0, 0, 5, 0b11100100, 0b11]);
+        round_trip(vec![Axis::Z, Axis::Y, Axis::X]);
+        // 3 bits each, so the flips cross byte boundaries.
+        let flips = (0..8).chain(0..8).map(Flip).collect::<Vec<_>>();
+        debug_assert_eq!(round_trip(flips).len(), 3 + 6);
+        round_trip(Vec::<Flip>::new());
+        // Arrays and boxed slices use the same bytes as a Vec of bytes, without the length for arrays.
+        let faces = [Direction::PosY, Direction::NegX, Direction::PosZ, Direction::NegY];
+        debug_assert_eq!(round_trip(faces), faces.map(|direction| direction as u8));
+        debug_assert_eq!(round_trip([Cardinal::South; 2]), [3, 3]);
+        debug_assert_eq!(round_trip(Box::<[Cardinal]>::from([Cardinal::North, Cardinal::South])), [0, 0, 2, 0b1101]);
+        debug_assert_eq!(round_trip(Box::<[Direction]>::from(faces)), round_trip(faces.to_vec()));
+    }
+
+    #[test]
+    fn orient_invalid_test() {
+        fn invalid<T: Readable>(bytes: &[u8]) -> bool {
+            matches!(T::read_from(&mut Cursor::new(bytes)), Err(error) if matches!(error.root(), Error::InvalidBinaryFormat))
+        }
+        debug_assert!(invalid::<Direction>(&[6]));
+        debug_assert!(invalid::<Cardinal>(&[4]));
+        debug_assert!(invalid::<Axis>(&[3]));
+        debug_assert!(invalid::<Rotation>(&[ROTATION_COUNT]));
+        debug_assert!(invalid::<Flip>(&[8]));
+        debug_assert!(invalid::<Orientation>(&[ROTATION_COUNT << 3]));
+        debug_assert!(invalid::<Vec<Direction>>(&[0, 0, 2, 0, 255]));
+        debug_assert!(invalid::<Vec<Axis>>(&[0, 0, 2, 0b1100]));
+        debug_assert!(invalid::<[Direction; 2]>(&[0, 6]));
+        debug_assert!(invalid::<Box<[Axis]>>(&[0, 0, 1, 0b11]));
+    }
+}
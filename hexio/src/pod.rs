@@ -0,0 +1,174 @@
+//! Bulk reading and writing of slices of plain old data with an explicit byte order.
+//!
+//! When the byte order matches the host, a slice is written with a single `write_all` and read with a single
+//! `read_exact`. Otherwise the bytes of each element are swapped, in chunks on write and in place on read.
+//! With [Endian::Big] the bytes are the same as writing each element with [Writeable](crate::Writeable).
+//! ```rust, no_run
+//! use hexio::pod::{read_pod_vec, write_pod_slice, Endian};
+//!
+//! let heights = vec![1.0f32, 2.5, -3.0];
+//! let mut buffer = Vec::new();
+//! write_pod_slice(&mut buffer, &heights, Endian::Little).unwrap();
+//! let read: Vec<f32> = read_pod_vec(&mut buffer.as_slice(), heights.len(), Endian::Little).unwrap();
+//! ```
+
+use std::io::{Read, Write};
+
+use bytemuck::Pod;
+use hexmacros::for_each_int_type;
+
+use crate::error::Result;
+use crate::{LengthPrefix, MAX_PREALLOCATE};
+
+/// The number of bytes that are swapped at a time when writing in the non-native byte order.
+const SWAP_CHUNK_BYTES: usize = 8192;
+
+/// A byte order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endian {
+    Big,
+    Little,
+}
+
+impl Endian {
+    /// The byte order of the host.
+    #[cfg(target_endian = "little")]
+    pub const NATIVE: Endian = Endian::Little;
+    /// The byte order of the host.
+    #[cfg(target_endian = "big")]
+    pub const NATIVE: Endian = Endian::Big;
+
+    #[inline]
+    pub const fn is_native(self) -> bool {
+        matches!((self, Self::NATIVE), (Endian::Big, Endian::Big) | (Endian::Little, Endian::Little))
+    }
+}
+
+/// A [Pod] type whose byte order is reversed as a whole, such as an integer or a float.
+///
+/// Types that are made of several numbers, such as `[f32; 3]`, can't implement this because each number would have to
+/// be swapped on its own. Write them as a slice of their numbers with [bytemuck::cast_slice] instead.
+pub trait PodScalar: Pod {
+    fn swap_bytes(self) -> Self;
+}
+
+macro_rules! int_pod_scalar {
+    ($type:ty) => {
+        impl PodScalar for $type {
+            #[inline]
+            fn swap_bytes(self) -> Self {
+                <$type>::swap_bytes(self)
+            }
+        }
+    };
+}
+
+for_each_int_type!(int_pod_scalar; all !sized);
+
+impl PodScalar for f32 {
+    #[inline]
+    fn swap_bytes(self) -> Self {
+        f32::from_bits(self.to_bits().swap_bytes())
+    }
+}
+
+impl PodScalar for f64 {
+    #[inline]
+    fn swap_bytes(self) -> Self {
+        f64::from_bits(self.to_bits().swap_bytes())
+    }
+}
+
+/// Writes each element of `slice` in the `endian` byte order, without a length.
+pub fn write_pod_slice<T: PodScalar, W: Write>(writer: &mut W, slice: &[T], endian: Endian) -> Result<u64> {
+    if endian.is_native() {
+        writer.write_all(bytemuck::cast_slice(slice))?;
+    } else {
+        let chunk_length = (SWAP_CHUNK_BYTES / size_of::<T>()).max(1);
+        let mut buffer = Vec::with_capacity(chunk_length.min(slice.len()));
+        for chunk in slice.chunks(chunk_length) {
+            buffer.clear();
+            buffer.extend(chunk.iter().map(|value| value.swap_bytes()));
+            writer.write_all(bytemuck::cast_slice(&buffer))?;
+        }
+    }
+    Ok(size_of_val(slice) as u64)
+}
+
+/// Fills `slice` with elements in the `endian` byte order.
+pub fn read_pod_slice<T: PodScalar, R: Read>(reader: &mut R, slice: &mut [T], endian: Endian) -> Result<()> {
+    reader.read_exact(bytemuck::cast_slice_mut(slice))?;
+    if !endian.is_native() {
+        slice.iter_mut().for_each(|value| *value = value.swap_bytes());
+    }
+    Ok(())
+}
+
+/// Reads `length` elements in the `endian` byte order.
+pub fn read_pod_vec<T: PodScalar, R: Read>(reader: &mut R, length: usize, endian: Endian) -> Result<Vec<T>> {
+    // Grow the Vec by at most MAX_PREALLOCATE elements at a time.
+    let mut result = Vec::with_capacity(length.min(MAX_PREALLOCATE));
+    while result.len() < length {
+        let start = result.len();
+        let end = length.min(start + result.capacity().max(MAX_PREALLOCATE));
+        result.resize(end, T::zeroed());
+        read_pod_slice(reader, &mut result[start..], endian)?;
+    }
+    Ok(result)
+}
+
+/// Writes the length of `slice` as `prefix`, followed by its elements in the `endian` byte order.
+pub fn write_pod_slice_with<T: PodScalar, W: Write>(writer: &mut W, slice: &[T], prefix: LengthPrefix, endian: Endian) -> Result<u64> {
+    Ok(prefix.write_length(writer, slice.len())? + write_pod_slice(writer, slice, endian)?)
+}
+
+/// Reads a length encoded as `prefix`, followed by that many elements in the `endian` byte order.
+pub fn read_pod_vec_with<T: PodScalar, R: Read>(reader: &mut R, prefix: LengthPrefix, endian: Endian) -> Result<Vec<T>> {
+    let length = prefix.read_length(reader)?;
+    read_pod_vec(reader, length, endian)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{error::Error, Writeable};
+
+    #[test]
+    fn pod_matches_per_element_test() {
+        let values = (0..10_000u32).map(|i| i.wrapping_mul(0x9e3779b9)).collect::<Vec<_>>();
+        let mut expected = Vec::new();
+        values.write_to(&mut expected).unwrap();
+        let mut buffer = Vec::new();
+        let size = write_pod_slice_with(&mut buffer, &values, LengthPrefix::U24, Endian::Big).unwrap();
+        debug_assert_eq!(size, buffer.len() as u64);
+        debug_assert_eq!(buffer, expected);
+        debug_assert_eq!(read_pod_vec_with::<u32, _>(&mut Cursor::new(&buffer), LengthPrefix::U24, Endian::Big).unwrap(), values);
+        let floats = vec![1.5f32, -0.0, f32::INFINITY, f32::MIN_POSITIVE];
+        let mut expected = Vec::new();
+        floats.iter().for_each(|value| _ = crate::le::write_to(value, &mut expected).unwrap());
+        let mut buffer = Vec::new();
+        write_pod_slice(&mut buffer, &floats, Endian::Little).unwrap();
+        debug_assert_eq!(buffer, expected);
+        let read: Vec<f32> = read_pod_vec(&mut Cursor::new(&buffer), floats.len(), Endian::Little).unwrap();
+        debug_assert_eq!(bytemuck::cast_slice::<f32, u8>(&read), bytemuck::cast_slice::<f32, u8>(&floats));
+    }
+
+    #[test]
+    fn pod_round_trip_test() {
+        // Long enough to take more than one chunk and more than one preallocation.
+        let values = (0..20_000i64).map(|i| i * -7919).collect::<Vec<_>>();
+        for endian in [Endian::Big, Endian::Little] {
+            let mut buffer = Vec::new();
+            write_pod_slice(&mut buffer, &values, endian).unwrap();
+            debug_assert_eq!(buffer.len(), values.len() * 8);
+            debug_assert_eq!(read_pod_vec::<i64, _>(&mut Cursor::new(&buffer), values.len(), endian).unwrap(), values);
+            let mut slice = [0i64; 3];
+            read_pod_slice(&mut Cursor::new(&buffer), &mut slice, endian).unwrap();
+            debug_assert_eq!(slice, values[..3]);
+        }
+        // A length that is larger than the stream fails without allocating all of it.
+        debug_assert!(matches!(read_pod_vec::<u64, _>(&mut Cursor::new([0u8; 16]), usize::MAX / 8, Endian::Big), Err(Error::IoError(_))));
+    }
+}
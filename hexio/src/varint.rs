@@ -0,0 +1,220 @@
+//! LEB128 variable length integers. Small values take fewer bytes: values below 128 take a single byte.
+//!
+//! Signed integers are zigzag encoded first so that small negative values are small as well.
+//! Use the [VarInt] wrapper, or use this module with the derive macros:
+//! ```rust, no_run
+//! use hexio::{Readable, Writeable};
+//!
+//! #[derive(Readable, Writeable)]
+//! struct Packet {
+//!     #[io(with = "hexio::varint")]
+//!     id: u32,
+//! }
+//! ```
+
+use std::io::{Read, Write};
+
+use hexmacros::for_each_int_type;
+
+use crate::error::{Error, Result};
+use crate::{Deterministic, NonByte, Readable, Writeable};
+
+/// Integers that can be written as a varint.
+pub trait VarIntEncode: Sized {
+    /// The most bytes that the varint of this type can take.
+    const MAX_LENGTH: usize;
+
+    fn read_varint<R: Read>(reader: &mut R) -> Result<Self>;
+    fn write_varint<W: Write>(&self, writer: &mut W) -> Result<u64>;
+}
+
+/// Writes the inner integer as a varint.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarInt<T>(pub T);
+
+impl<T: VarIntEncode> Readable for VarInt<T> {
+    #[inline]
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        T::read_varint(reader).map(VarInt)
+    }
+}
+
+impl<T: VarIntEncode> Writeable for VarInt<T> {
+    #[inline]
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        self.0.write_varint(writer)
+    }
+}
+
+impl<T: VarIntEncode> NonByte for VarInt<T> {}
+impl<T: VarIntEncode> Deterministic for VarInt<T> {}
+
+/// Reads a varint, for `#[io(with = "hexio::varint")]`.
+#[inline]
+pub fn read_from<T: VarIntEncode, R: Read>(reader: &mut R) -> Result<T> {
+    T::read_varint(reader)
+}
+
+/// Writes a varint, for `#[io(with = "hexio::varint")]`.
+#[inline]
+pub fn write_to<T: VarIntEncode, W: Write>(value: &T, writer: &mut W) -> Result<u64> {
+    value.write_varint(writer)
+}
+
+fn read_unsigned<R: Read>(reader: &mut R, bits: u32) -> Result<u128> {
+    let mut value = 0u128;
+    let mut shift = 0u32;
+    loop {
+        let byte = u8::read_from(reader)?;
+        let low = (byte & 0x7f) as u128;
+        // Reject varints that are too long or that have bits that don't fit in the type.
+        if shift >= bits || (bits - shift < 7 && low >> (bits - shift) != 0) {
+            return Err(Error::InvalidBinaryFormat);
+        }
+        value |= low << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+fn write_unsigned<W: Write>(writer: &mut W, mut value: u128) -> Result<u64> {
+    let mut buffer = [0u8; 19];
+    let mut length = 0;
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buffer[length] = byte;
+            length += 1;
+            break;
+        }
+        buffer[length] = byte | 0x80;
+        length += 1;
+    }
+    writer.write_all(&buffer[..length])?;
+    Ok(length as u64)
+}
+
+macro_rules! unsigned_varint {
+    ($type:ty) => {
+        impl VarIntEncode for $type {
+            const MAX_LENGTH: usize = (<$type>::BITS as usize).div_ceil(7);
+
+            #[inline]
+            fn read_varint<R: Read>(reader: &mut R) -> Result<Self> {
+                Ok(read_unsigned(reader, <$type>::BITS)? as $type)
+            }
+
+            #[inline]
+            fn write_varint<W: Write>(&self, writer: &mut W) -> Result<u64> {
+                write_unsigned(writer, *self as u128)
+            }
+        }
+    };
+}
+
+macro_rules! signed_varint {
+    ($type:ty) => {
+        impl VarIntEncode for $type {
+            const MAX_LENGTH: usize = (<$type>::BITS as usize).div_ceil(7);
+
+            #[inline]
+            fn read_varint<R: Read>(reader: &mut R) -> Result<Self> {
+                let zigzag = read_unsigned(reader, <$type>::BITS)?;
+                Ok(((zigzag >> 1) as $type) ^ -((zigzag & 1) as $type))
+            }
+
+            #[inline]
+            fn write_varint<W: Write>(&self, writer: &mut W) -> Result<u64> {
+                let zigzag = (*self << 1) ^ (*self >> (<$type>::BITS - 1));
+                // The cast sign extends negative values, so the bits above the width of the type are masked off.
+                write_unsigned(writer, zigzag as u128 & (u128::MAX >> (128 - <$type>::BITS)))
+            }
+        }
+    };
+}
+
+for_each_int_type!(unsigned_varint; unsigned !8);
+for_each_int_type!(signed_varint; signed !8);
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn round_trip<T: VarIntEncode + Copy + PartialEq + std::fmt::Debug>(value: T) -> usize {
+        let mut buffer = Vec::new();
+        let length = VarInt(value).write_to(&mut buffer).unwrap();
+        debug_assert_eq!(length, buffer.len() as u64);
+        debug_assert!(buffer.len() <= T::MAX_LENGTH);
+        let mut cursor = Cursor::new(&buffer);
+        debug_assert_eq!(VarInt::<T>::read_from(&mut cursor).unwrap().0, value);
+        debug_assert_eq!(cursor.position(), length);
+        buffer.len()
+    }
+
+    macro_rules! boundary_test {
+        ($type:ty) => {{
+            // Every value next to a boundary between varint lengths, and the ends of the range.
+            let mut values = vec![<$type>::MIN, <$type>::MIN + 1, <$type>::MAX - 1, <$type>::MAX, 0 as $type, 1 as $type];
+            for shift in (7..<$type>::BITS).step_by(7) {
+                let boundary = 1u128 << shift;
+                for value in [boundary - 1, boundary, boundary + 1] {
+                    if let Ok(value) = <$type>::try_from(value) {
+                        values.push(value);
+                        values.push(value.wrapping_neg());
+                    }
+                    // The boundaries of zigzag encoded values are at half the unsigned ones.
+                    if let Ok(value) = <$type>::try_from(value / 2) {
+                        values.push(value);
+                        values.push(value.wrapping_neg());
+                        values.push(value.wrapping_neg().wrapping_sub(1));
+                    }
+                }
+            }
+            for value in values {
+                round_trip(value);
+            }
+            let max_length = round_trip(<$type>::MAX).max(round_trip(<$type>::MIN));
+            debug_assert_eq!(max_length, <$type as VarIntEncode>::MAX_LENGTH);
+        }};
+    }
+
+    #[test]
+    // `try_from` can't fail for u128.
+    #[allow(irrefutable_let_patterns)]
+    fn varint_boundary_test() {
+        for_each_int_type!(boundary_test; all !8);
+        debug_assert_eq!(round_trip(127u32), 1);
+        debug_assert_eq!(round_trip(128u32), 2);
+        debug_assert_eq!(round_trip(16383u32), 2);
+        debug_assert_eq!(round_trip(16384u32), 3);
+        debug_assert_eq!(round_trip(-64i32), 1);
+        debug_assert_eq!(round_trip(-65i32), 2);
+        debug_assert_eq!(round_trip(63i32), 1);
+        debug_assert_eq!(round_trip(64i32), 2);
+        for value in u16::MIN..=u16::MAX {
+            round_trip(value);
+        }
+        for value in i16::MIN..=i16::MAX {
+            round_trip(value);
+        }
+    }
+
+    #[test]
+    fn varint_invalid_test() {
+        // Too long.
+        let bytes = [0x80, 0x80, 0x80, 0x80, 0x80, 0x01];
+        debug_assert!(matches!(u32::read_varint(&mut Cursor::new(bytes)), Err(Error::InvalidBinaryFormat)));
+        // Bits past the width of the type.
+        let bytes = [0xff, 0xff, 0xff, 0xff, 0x10];
+        debug_assert!(matches!(u32::read_varint(&mut Cursor::new(bytes)), Err(Error::InvalidBinaryFormat)));
+        let bytes = [0xff, 0xff, 0xff, 0xff, 0x0f];
+        debug_assert_eq!(u32::read_varint(&mut Cursor::new(bytes)).unwrap(), u32::MAX);
+        // Unterminated.
+        debug_assert!(matches!(u64::read_varint(&mut Cursor::new([0x80])), Err(Error::IoError(_))));
+    }
+}
@@ -0,0 +1,300 @@
+//! Versioned payloads that are upgraded to the latest version when they are read.
+//!
+//! A payload is written after a [Header] with the type id, the version and the length of the payload.
+//! Writers always write the latest version. When an older version is read, the upgrade steps that the
+//! type registers in [Versioned::migrations] are run one after another, from that version up to the latest.
+//! ```rust, no_run
+//! use hexio::{Readable, Writeable};
+//! use hexio::versioned::{Migrations, Versioned};
+//!
+//! #[derive(Readable, Writeable)]
+//! struct PlayerV1 {
+//!     name: String,
+//! }
+//!
+//! #[derive(Readable, Writeable)]
+//! struct Player {
+//!     name: String,
+//!     health: u32,
+//! }
+//!
+//! impl Versioned for Player {
+//!     const TYPE_ID: u32 = 0x504c5952;
+//!     const VERSION: u16 = 2;
+//!
+//!     fn migrations(migrations: &mut Migrations) {
+//!         migrations.upgrade(1, |old: PlayerV1| Player { name: old.name, health: 100 });
+//!     }
+//! }
+//! ```
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read, Write};
+
+use crate::error::{Error, Result};
+use crate::{Deterministic, NonByte, Readable, Writeable};
+
+/// Precedes a versioned payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Readable, Writeable)]
+pub struct Header {
+    /// Identifies the type of the payload.
+    pub type_id: u32,
+    /// The version of the payload.
+    pub version: u16,
+    /// The number of bytes in the payload.
+    pub length: u32,
+}
+
+type Step = Box<dyn Fn(Vec<u8>) -> Result<Vec<u8>>>;
+
+/// The upgrade steps of a [Versioned] type, each upgrading a payload from one version to the next.
+#[derive(Default)]
+pub struct Migrations {
+    steps: BTreeMap<u16, Step>,
+    /// The first version that more than one step was registered for.
+    duplicate: Option<u16>,
+}
+
+impl Migrations {
+    /// Registers an upgrade of the raw payload from version `from` to version `from + 1`.
+    ///
+    /// If an upgrade from `from` was already registered, reading an older version fails with [Error::DuplicateUpgrade].
+    pub fn step<F: Fn(Vec<u8>) -> Result<Vec<u8>> + 'static>(&mut self, from: u16, step: F) -> &mut Self {
+        match self.steps.entry(from) {
+            Entry::Vacant(entry) => {
+                entry.insert(Box::new(step));
+            }
+            Entry::Occupied(_) => {
+                self.duplicate.get_or_insert(from);
+            }
+        }
+        self
+    }
+
+    /// Registers an upgrade from version `from` to version `from + 1`, where `Old` is the type of the payload in version
+    /// `from` and `New` is the type of the payload in version `from + 1`.
+    ///
+    /// If an upgrade from `from` was already registered, reading an older version fails with [Error::DuplicateUpgrade].
+    pub fn upgrade<Old, New, F>(&mut self, from: u16, upgrade: F) -> &mut Self
+    where
+        Old: Readable,
+        New: Writeable,
+        F: Fn(Old) -> New + 'static,
+    {
+        self.step(from, move |payload| {
+            let old = read_payload::<Old>(&payload)?;
+            let mut buffer = Vec::new();
+            upgrade(old).write_to(&mut buffer)?;
+            Ok(buffer)
+        })
+    }
+
+    fn run(&self, from: u16, to: u16, mut payload: Vec<u8>) -> Result<Vec<u8>> {
+        if let Some(version) = self.duplicate {
+            return Err(Error::DuplicateUpgrade(version));
+        }
+        for version in from..to {
+            let step = self.steps.get(&version).ok_or(Error::UnsupportedVersion { version, latest: to })?;
+            payload = step(payload)?;
+        }
+        Ok(payload)
+    }
+}
+
+/// A type that is written with a [Header] and can be read from older versions of itself.
+pub trait Versioned: Readable + Writeable {
+    /// Identifies the type, so that a payload of another type isn't read as this one.
+    const TYPE_ID: u32;
+    /// The latest version, which is the one that is written.
+    const VERSION: u16;
+
+    /// Registers the upgrade steps from each older version that can still be read.
+    /// This is only called when an older version is read.
+    fn migrations(_migrations: &mut Migrations) {}
+}
+
+/// Reads a whole payload as `T`, failing if any bytes are left over.
+fn read_payload<T: Readable>(payload: &[u8]) -> Result<T> {
+    let mut cursor = Cursor::new(payload);
+    let value = T::read_from(&mut cursor)?;
+    if cursor.position() != payload.len() as u64 {
+        return Err(Error::InvalidBinaryFormat);
+    }
+    Ok(value)
+}
+
+/// Reads a versioned payload, upgrading it to the latest version of `T` if it is older.
+pub fn read_versioned<T: Versioned, R: Read>(reader: &mut R) -> Result<T> {
+    let header = Header::read_from(reader)?;
+    if header.type_id != T::TYPE_ID {
+        return Err(Error::TypeIdMismatch { expected: T::TYPE_ID, found: header.type_id });
+    }
+    if header.version > T::VERSION {
+        return Err(Error::UnsupportedVersion { version: header.version, latest: T::VERSION });
+    }
+    let mut payload = Vec::new();
+    reader.take(header.length as u64).read_to_end(&mut payload)?;
+    if payload.len() != header.length as usize {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+    if header.version < T::VERSION {
+        let mut migrations = Migrations::default();
+        T::migrations(&mut migrations);
+        payload = migrations.run(header.version, T::VERSION, payload)?;
+    }
+    read_payload(&payload)
+}
+
+/// Writes `value` as the latest version of `T`.
+pub fn write_versioned<T: Versioned, W: Write>(value: &T, writer: &mut W) -> Result<u64> {
+    let mut payload = Vec::new();
+    value.write_to(&mut payload)?;
+    let header = Header {
+        type_id: T::TYPE_ID,
+        version: T::VERSION,
+        length: u32::try_from(payload.len()).map_err(|_| Error::ArrayTooLong)?,
+    };
+    Ok(header.write_to(writer)? + crate::write_bytes(writer, &payload)?)
+}
+
+/// Reads and writes the inner value as a versioned payload.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Envelope<T>(pub T);
+
+impl<T: Versioned> Readable for Envelope<T> {
+    #[inline]
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        read_versioned(reader).map(Envelope)
+    }
+}
+
+impl<T: Versioned> Writeable for Envelope<T> {
+    #[inline]
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        write_versioned(&self.0, writer)
+    }
+}
+
+impl<T: Versioned> NonByte for Envelope<T> {}
+impl<T: Versioned + Deterministic> Deterministic for Envelope<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Readable, Writeable)]
+    struct SaveV1 {
+        name: String,
+        health: u8,
+    }
+
+    #[derive(Debug, PartialEq, Readable, Writeable)]
+    struct SaveV2 {
+        name: String,
+        health: u16,
+        level: u8,
+    }
+
+    #[derive(Debug, PartialEq, Readable, Writeable)]
+    struct Save {
+        name: String,
+        health: u16,
+        level: u8,
+        position: (i32, i32),
+    }
+
+    impl Versioned for Save {
+        const TYPE_ID: u32 = 0x53415645;
+        const VERSION: u16 = 3;
+
+        fn migrations(migrations: &mut Migrations) {
+            migrations
+                .upgrade(1, |old: SaveV1| SaveV2 {
+                    name: old.name,
+                    health: old.health as u16 * 10,
+                    level: 1,
+                })
+                // A raw step: version 3 appends the position, which starts at the origin.
+                .step(2, |mut payload| {
+                    payload.extend_from_slice(&[0; 8]);
+                    Ok(payload)
+                });
+        }
+    }
+
+    fn write_old<T: Writeable>(version: u16, value: &T) -> Vec<u8> {
+        let mut payload = Vec::new();
+        value.write_to(&mut payload).unwrap();
+        let mut buffer = Vec::new();
+        Header { type_id: Save::TYPE_ID, version, length: payload.len() as u32 }.write_to(&mut buffer).unwrap();
+        buffer.extend_from_slice(&payload);
+        buffer
+    }
+
+    #[test]
+    fn versioned_upgrade_test() {
+        let v1 = write_old(1, &SaveV1 { name: String::from("hex"), health: 7 });
+        let save: Save = read_versioned(&mut Cursor::new(&v1)).unwrap();
+        debug_assert_eq!(save, Save {
+            name: String::from("hex"),
+            health: 70,
+            level: 1,
+            position: (0, 0),
+        });
+        let v2 = write_old(2, &SaveV2 { name: String::from("hex"), health: 5, level: 9 });
+        let Envelope(save) = Envelope::<Save>::read_from(&mut Cursor::new(&v2)).unwrap();
+        debug_assert_eq!((save.health, save.level, save.position), (5, 9, (0, 0)));
+        // Writing always writes the latest version.
+        let mut buffer = Vec::new();
+        let size = Envelope(save).write_to(&mut buffer).unwrap();
+        debug_assert_eq!(size, buffer.len() as u64);
+        let header = Header::read_from(&mut Cursor::new(&buffer)).unwrap();
+        debug_assert_eq!((header.type_id, header.version), (Save::TYPE_ID, 3));
+        debug_assert_eq!(read_versioned::<Save, _>(&mut Cursor::new(&buffer)).unwrap().level, 9);
+    }
+
+    #[test]
+    fn versioned_invalid_test() {
+        let v1 = write_old(1, &SaveV1 { name: String::from("hex"), health: 7 });
+        // A version newer than the latest.
+        let mut newer = v1.clone();
+        newer[5] = 4;
+        debug_assert!(matches!(
+            read_versioned::<Save, _>(&mut Cursor::new(&newer)),
+            Err(Error::UnsupportedVersion { version: 4, latest: 3 })
+        ));
+        // A version without an upgrade step.
+        let mut unknown = v1.clone();
+        unknown[5] = 0;
+        debug_assert!(matches!(
+            read_versioned::<Save, _>(&mut Cursor::new(&unknown)),
+            Err(Error::UnsupportedVersion { version: 0, latest: 3 })
+        ));
+        let mut other = v1.clone();
+        other[0] = 0;
+        debug_assert!(matches!(
+            read_versioned::<Save, _>(&mut Cursor::new(&other)),
+            Err(Error::TypeIdMismatch { expected: Save::TYPE_ID, found: 0x00415645 })
+        ));
+        // Bytes that the payload doesn't use.
+        let mut trailing = write_old(3, &Save { name: String::new(), health: 0, level: 0, position: (0, 0) });
+        trailing[9] += 1;
+        trailing.push(0);
+        debug_assert!(matches!(read_versioned::<Save, _>(&mut Cursor::new(&trailing)), Err(Error::InvalidBinaryFormat)));
+        debug_assert!(matches!(read_versioned::<Save, _>(&mut Cursor::new(&v1[..v1.len() - 1])), Err(Error::IoError(_))));
+        // The version whose upgrade is missing is reported, rather than the version that was read.
+        let mut migrations = Migrations::default();
+        migrations.step(1, Ok);
+        debug_assert!(matches!(migrations.run(1, 3, Vec::new()), Err(Error::UnsupportedVersion { version: 2, latest: 3 })));
+    }
+
+    #[test]
+    fn versioned_duplicate_step_test() {
+        let mut migrations = Migrations::default();
+        migrations.step(1, Ok).step(2, Ok).upgrade(1, |old: SaveV1| old.health);
+        debug_assert!(matches!(migrations.run(1, 3, Vec::new()), Err(Error::DuplicateUpgrade(1))));
+        // Even when the duplicated step isn't needed for the version that was read.
+        debug_assert!(matches!(migrations.run(2, 3, Vec::new()), Err(Error::DuplicateUpgrade(1))));
+    }
+}
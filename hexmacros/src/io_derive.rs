@@ -0,0 +1,310 @@
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse_quote, Attribute, Data, DeriveInput, Expr, Fields, Generics, Ident, Lit, LitInt, LitStr, Path
+};
+
+/*
+#[derive(Readable, Writeable)]
+#[io(crate = "::hexio")]
+struct Message<T> {
+    id: u32,
+    #[io(with = "hexio::varint")]
+    count: u64,
+    payload: T,
+    #[io(skip)]
+    received: Option<std::time::Instant>,
+}
+
+#[derive(Readable, Writeable)]
+enum Packet {
+    // Discriminant 0
+    Ping,
+    #[io(tag = 5)]
+    Move { x: f32, y: f32 },
+    // Discriminant 6
+    Chat(String),
+}
+*/
+
+struct FieldAttrs {
+    skip: bool,
+    with: Option<Path>,
+}
+
+fn parse_container_attrs(attrs: &[Attribute]) -> syn::Result<Option<Path>> {
+    let mut krate = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("io")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                krate = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown container attribute, expected `crate`"))
+            }
+        })?;
+    }
+    Ok(krate)
+}
+
+fn parse_field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+    let mut result = FieldAttrs {
+        skip: false,
+        with: None,
+    };
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("io")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                result.skip = true;
+            } else if meta.path.is_ident("with") {
+                result.with = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+            } else {
+                return Err(meta.error("unknown field attribute, expected `skip` or `with`"));
+            }
+            Ok(())
+        })?;
+    }
+    if result.skip && result.with.is_some() {
+        return Err(syn::Error::new_spanned(&attrs[0], "a field can't be both `skip` and `with`"));
+    }
+    Ok(result)
+}
+
+fn parse_variant_tag(variant: &syn::Variant) -> syn::Result<Option<u8>> {
+    let mut tag = None;
+    for attr in variant.attrs.iter().filter(|attr| attr.path().is_ident("io")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("tag") {
+                tag = Some(meta.value()?.parse::<LitInt>()?.base10_parse::<u8>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown variant attribute, expected `tag`"))
+            }
+        })?;
+    }
+    if tag.is_some() {
+        return Ok(tag);
+    }
+    // Fall back to an explicit integer discriminant, such as `Variant = 3`.
+    match &variant.discriminant {
+        Some((_, Expr::Lit(expr))) => match &expr.lit {
+            Lit::Int(int) => Ok(Some(int.base10_parse::<u8>()?)),
+            _ => Ok(None),
+        },
+        Some((_, expr)) => Err(syn::Error::new_spanned(expr, "use `#[io(tag = N)]` for discriminants that aren't integer literals")),
+        None => Ok(None),
+    }
+}
+
+/// The discriminant of each variant. Variants without a tag use the previous tag plus one, starting at 0.
+fn variant_tags(data: &syn::DataEnum) -> syn::Result<Vec<u8>> {
+    let mut tags: Vec<u8> = Vec::with_capacity(data.variants.len());
+    let mut next = Some(0u8);
+    for variant in &data.variants {
+        let tag = match parse_variant_tag(variant)? {
+            Some(tag) => tag,
+            None => next.ok_or_else(|| syn::Error::new_spanned(variant, "the discriminant doesn't fit in a u8"))?,
+        };
+        if tags.contains(&tag) {
+            return Err(syn::Error::new_spanned(variant, format!("the discriminant {tag} is used more than once")));
+        }
+        tags.push(tag);
+        next = tag.checked_add(1);
+    }
+    Ok(tags)
+}
+
+struct IoField<'a> {
+    field: &'a syn::Field,
+    member: syn::Member,
+    binding: Ident,
+    attrs: FieldAttrs,
+}
+
+fn io_fields(fields: &Fields) -> syn::Result<Vec<IoField<'_>>> {
+    fields.iter().enumerate().map(|(index, field)| {
+        let (member, binding) = match &field.ident {
+            Some(ident) => (
+                syn::Member::Named(ident.clone()),
+                format_ident!("__{}", ident.to_string().trim_start_matches("r#")),
+            ),
+            None => (syn::Member::Unnamed(index.into()), format_ident!("__{}", index)),
+        };
+        Ok(IoField {
+            field,
+            member,
+            binding,
+            attrs: parse_field_attrs(&field.attrs)?,
+        })
+    }).collect()
+}
+
+fn mentions(tokens: TokenStream, ident: &Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(other) => &other == ident,
+        TokenTree::Group(group) => mentions(group.stream(), ident),
+        _ => false,
+    })
+}
+
+/// Adds `bound` to each type parameter that is used by a field that is encoded with its own impl.
+fn add_bounds<'a, It: IntoIterator<Item = &'a IoField<'a>>>(generics: &Generics, fields: It, bound: &Path) -> Generics {
+    let mut generics = generics.clone();
+    let encoded = fields.into_iter()
+        .filter(|field| !field.attrs.skip && field.attrs.with.is_none())
+        .map(|field| field.field.ty.to_token_stream())
+        .collect::<Vec<_>>();
+    let params = generics.type_params()
+        .map(|param| param.ident.clone())
+        .filter(|param| encoded.iter().any(|ty| mentions(ty.clone(), param)))
+        .collect::<Vec<_>>();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause.predicates.push(parse_quote!(#param: #bound));
+    }
+    generics
+}
+
+fn bindings_pattern(fields: &Fields, io_fields: &[IoField]) -> TokenStream {
+    let members = io_fields.iter().map(|field| &field.member);
+    let bindings = io_fields.iter().map(|field| &field.binding);
+    match fields {
+        Fields::Unit => quote!(),
+        _ => quote!({ #(#members: #bindings),* }),
+    }
+}
+
+fn write_fields(krate: &Path, io_fields: &[IoField]) -> TokenStream {
+    let writes = io_fields.iter().filter(|field| !field.attrs.skip).map(|field| {
+        let binding = &field.binding;
+        match &field.attrs.with {
+            Some(with) => quote!(__size += #with::write_to(#binding, writer)?;),
+            None => quote!(__size += #krate::Writeable::write_to(#binding, writer)?;),
+        }
+    });
+    quote!(#(#writes)*)
+}
+
+/// Reads each field, adding the field name and the variant name to the path of any error.
+/// The name of a struct isn't added, since the path already starts at the value that was read.
+fn read_fields(krate: &Path, constructor: TokenStream, variant: Option<String>, io_fields: &[IoField]) -> TokenStream {
+    let values = io_fields.iter().map(|field| {
+        let member = &field.member;
+        let ty = &field.field.ty;
+        let read = if let Some(with) = &field.attrs.with {
+            quote!(#with::read_from(reader))
+        } else {
+            quote!(<#ty as #krate::Readable>::read_from(reader))
+        };
+        let name = match member {
+            syn::Member::Named(ident) => ident.to_string().trim_start_matches("r#").to_owned(),
+            syn::Member::Unnamed(index) => index.index.to_string(),
+        };
+        let variant = variant.as_ref().map(|variant| quote!(.at_variant(#variant)));
+        let value = if field.attrs.skip {
+            quote!(::std::default::Default::default())
+        } else {
+            quote!(#read.map_err(|error: #krate::error::Error| error.at_field(#name) #variant)?)
+        };
+        quote!(#member: #value)
+    });
+    quote!(#constructor { #(#values),* })
+}
+
+pub fn derive_readable(input: DeriveInput) -> syn::Result<TokenStream> {
+    let krate = parse_container_attrs(&input.attrs)?.unwrap_or_else(|| parse_quote!(::hexio));
+    let name = &input.ident;
+    let (body, generics) = match &input.data {
+        Data::Struct(data) => {
+            let fields = io_fields(&data.fields)?;
+            let value = read_fields(&krate, quote!(Self), None, &fields);
+            let generics = add_bounds(&input.generics, &fields, &parse_quote!(#krate::Readable));
+            (quote!(::std::result::Result::Ok(#value)), generics)
+        }
+        Data::Enum(data) => {
+            let tags = variant_tags(data)?;
+            let variants = data.variants.iter()
+                .map(|variant| Ok((variant, io_fields(&variant.fields)?)))
+                .collect::<syn::Result<Vec<_>>>()?;
+            let arms = variants.iter().zip(&tags).map(|((variant, fields), tag)| {
+                let ident = &variant.ident;
+                let value = read_fields(&krate, quote!(Self::#ident), Some(format!("{name}::{ident}")), fields);
+                quote!(#tag => #value)
+            });
+            let generics = add_bounds(
+                &input.generics,
+                variants.iter().flat_map(|(_, fields)| fields),
+                &parse_quote!(#krate::Readable),
+            );
+            (quote!(
+                ::std::result::Result::Ok(match <u8 as #krate::Readable>::read_from(reader)? {
+                    #(#arms,)*
+                    _ => return ::std::result::Result::Err(#krate::error::Error::InvalidBinaryFormat),
+                })
+            ), generics)
+        }
+        Data::Union(data) => return Err(syn::Error::new_spanned(data.union_token, "Readable can't be derived for unions")),
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote!(
+        impl #impl_generics #krate::Readable for #name #ty_generics #where_clause {
+            fn read_from<R: ::std::io::Read>(reader: &mut R) -> #krate::error::Result<Self> {
+                #body
+            }
+        }
+    ))
+}
+
+pub fn derive_writeable(input: DeriveInput) -> syn::Result<TokenStream> {
+    let krate = parse_container_attrs(&input.attrs)?.unwrap_or_else(|| parse_quote!(::hexio));
+    let name = &input.ident;
+    let (body, generics) = match &input.data {
+        Data::Struct(data) => {
+            let fields = io_fields(&data.fields)?;
+            let pattern = bindings_pattern(&data.fields, &fields);
+            let writes = write_fields(&krate, &fields);
+            let generics = add_bounds(&input.generics, &fields, &parse_quote!(#krate::Writeable));
+            (quote!(
+                let Self #pattern = self;
+                #writes
+            ), generics)
+        }
+        Data::Enum(data) => {
+            let tags = variant_tags(data)?;
+            let variants = data.variants.iter()
+                .map(|variant| Ok((variant, io_fields(&variant.fields)?)))
+                .collect::<syn::Result<Vec<_>>>()?;
+            let arms = variants.iter().zip(&tags).map(|((variant, fields), tag)| {
+                let ident = &variant.ident;
+                let pattern = bindings_pattern(&variant.fields, fields);
+                let writes = write_fields(&krate, fields);
+                quote!(Self::#ident #pattern => {
+                    __size += #krate::Writeable::write_to(&#tag, writer)?;
+                    #writes
+                })
+            });
+            let generics = add_bounds(
+                &input.generics,
+                variants.iter().flat_map(|(_, fields)| fields),
+                &parse_quote!(#krate::Writeable),
+            );
+            (quote!(
+                match self {
+                    #(#arms)*
+                }
+            ), generics)
+        }
+        Data::Union(data) => return Err(syn::Error::new_spanned(data.union_token, "Writeable can't be derived for unions")),
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote!(
+        impl #impl_generics #krate::Writeable for #name #ty_generics #where_clause {
+            #[allow(unused_variables, unused_mut)]
+            fn write_to<W: ::std::io::Write>(&self, writer: &mut W) -> #krate::error::Result<u64> {
+                let mut __size = 0u64;
+                #body
+                ::std::result::Result::Ok(__size)
+            }
+        }
+    ))
+}
@@ -4,13 +4,68 @@ use std::sync::atomic::AtomicU64;
 use proc_macro::TokenStream;
 use proc_macro2::Literal;
 use quote::quote;
-use syn::{parse::Parse, parse_macro_input, Attribute};
+use syn::{parse::Parse, parse_macro_input, Attribute, DeriveInput};
 mod util;
 mod marker;
 mod inttypes;
 mod foreach;
 mod table;
 mod define;
+mod tag_derive;
+mod io_derive;
+
+/// Derives `IntoTag` from `hexahedron::tag::convert`.
+/// 
+/// Structs with named fields become a map, tuple structs become an array (or the inner value if there is one field),
+/// and enums become the variant name or a map with the variant name as the only key.
+/// # Attributes
+/// - `#[tag(crate = "path")]` on the type: the path to `hexahedron`.
+/// - `#[tag(rename = "name")]` on a field or variant.
+/// - `#[tag(skip)]` on a field: the field isn't written.
+#[proc_macro_derive(IntoTag, attributes(tag))]
+pub fn derive_into_tag(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    tag_derive::derive_into_tag(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Derives `FromTag` from `hexahedron::tag::convert`. Accepts the same attributes as `IntoTag`, and also:
+/// - `#[tag(default)]` on a field: use `Default::default()` when the field is missing.
+/// - `#[tag(default = "path")]` on a field: call `path()` when the field is missing.
+/// 
+/// Skipped fields are always `Default::default()`.
+#[proc_macro_derive(FromTag, attributes(tag))]
+pub fn derive_from_tag(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    tag_derive::derive_from_tag(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Derives `Readable` from `hexio`.
+/// 
+/// Struct fields are read in order. Enums read a `u8` discriminant first, which is `#[io(tag = N)]`, the integer
+/// discriminant of the variant, or the previous discriminant plus one (starting at 0).
+/// Type parameters that are used by read fields get a `Readable` bound.
+/// Errors from reading a field get the field name, and the variant name for enums, added to their path.
+/// Only fields and variants are recorded, not the name of the struct, so a path reads like `section[4].tags[12].value`.
+/// # Attributes
+/// - `#[io(crate = "path")]` on the type: the path to `hexio`.
+/// - `#[io(tag = N)]` on a variant: the discriminant of the variant.
+/// - `#[io(skip)]` on a field: the field isn't read, and is `Default::default()`.
+/// - `#[io(with = "module")]` on a field: read the field with `module::read_from(reader)` instead of its `Readable`
+///   impl, such as for an alternate encoding.
+#[proc_macro_derive(Readable, attributes(io))]
+pub fn derive_readable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    io_derive::derive_readable(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Derives `Writeable` from `hexio`. Accepts the same attributes as `Readable`, and writes in the same order.
+/// 
+/// Fields with `#[io(with = "module")]` are written with `module::write_to(&field, writer)`.
+#[proc_macro_derive(Writeable, attributes(io))]
+pub fn derive_writeable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    io_derive::derive_writeable(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
 
 /// Marks types with marker trait.
 /// 
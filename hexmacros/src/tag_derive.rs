@@ -0,0 +1,334 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    parse_quote, Attribute, Data, DeriveInput, Fields, Generics, Ident, LitStr, Path
+};
+
+/*
+#[derive(IntoTag, FromTag)]
+#[tag(crate = "::hexahedron")]
+struct Chest {
+    #[tag(rename = "id")]
+    name: String,
+    #[tag(default)]
+    open: bool,
+    #[tag(default = "default_items")]
+    items: Vec<Item>,
+    #[tag(skip)]
+    viewers: u32,
+}
+*/
+
+#[derive(Default)]
+struct ContainerAttrs {
+    krate: Option<Path>,
+}
+
+enum FieldDefault {
+    /// The field is required.
+    None,
+    /// `#[tag(default)]`
+    Default,
+    /// `#[tag(default = "path")]`
+    Path(Path),
+}
+
+struct FieldAttrs {
+    rename: Option<String>,
+    default: FieldDefault,
+    skip: bool,
+}
+
+fn parse_container_attrs(attrs: &[Attribute]) -> syn::Result<ContainerAttrs> {
+    let mut result = ContainerAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("tag")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                result.krate = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown container attribute, expected `crate`"))
+            }
+        })?;
+    }
+    Ok(result)
+}
+
+fn parse_field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+    let mut result = FieldAttrs {
+        rename: None,
+        default: FieldDefault::None,
+        skip: false,
+    };
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("tag")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                result.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("default") {
+                result.default = if meta.input.peek(syn::Token![=]) {
+                    FieldDefault::Path(meta.value()?.parse::<LitStr>()?.parse()?)
+                } else {
+                    FieldDefault::Default
+                };
+            } else if meta.path.is_ident("skip") {
+                result.skip = true;
+            } else {
+                return Err(meta.error("unknown field attribute, expected `rename`, `default`, or `skip`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(result)
+}
+
+/// A field with its parsed attributes. `member` is the name used to access it (`name` or `0`), and `binding` is the
+/// name of the variable it's bound to in patterns.
+struct TagField<'a> {
+    field: &'a syn::Field,
+    member: syn::Member,
+    binding: Ident,
+    key: String,
+    attrs: FieldAttrs,
+}
+
+fn tag_fields(fields: &Fields) -> syn::Result<Vec<TagField<'_>>> {
+    fields.iter().enumerate().map(|(index, field)| {
+        let attrs = parse_field_attrs(&field.attrs)?;
+        let (member, name) = match &field.ident {
+            Some(ident) => (syn::Member::Named(ident.clone()), ident.to_string().trim_start_matches("r#").to_owned()),
+            None => (syn::Member::Unnamed(index.into()), index.to_string()),
+        };
+        let binding = format_ident!("__{}", name);
+        let key = attrs.rename.clone().unwrap_or(name);
+        Ok(TagField { field, member, binding, key, attrs })
+    }).collect()
+}
+
+fn variant_name(variant: &syn::Variant) -> syn::Result<String> {
+    let attrs = parse_field_attrs(&variant.attrs)?;
+    if attrs.skip || !matches!(attrs.default, FieldDefault::None) {
+        return Err(syn::Error::new_spanned(variant, "variants only accept the `rename` attribute"));
+    }
+    Ok(attrs.rename.unwrap_or_else(|| variant.ident.to_string()))
+}
+
+fn add_bounds(generics: &Generics, bound: &Path) -> Generics {
+    let mut generics = generics.clone();
+    let params = generics.type_params().map(|param| param.ident.clone()).collect::<Vec<_>>();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause.predicates.push(parse_quote!(#param: #bound));
+    }
+    generics
+}
+
+fn crate_path(input: &DeriveInput) -> syn::Result<Path> {
+    Ok(parse_container_attrs(&input.attrs)?.krate.unwrap_or_else(|| parse_quote!(::hexahedron)))
+}
+
+/// Builds a tag from fields that are bound to their `binding` names.
+fn fields_into_tag(krate: &Path, fields: &Fields, tag_fields: &[TagField]) -> TokenStream {
+    let written = tag_fields.iter().filter(|field| !field.attrs.skip).collect::<Vec<_>>();
+    match fields {
+        Fields::Named(_) => {
+            let keys = written.iter().map(|field| &field.key);
+            let bindings = written.iter().map(|field| &field.binding);
+            quote!({
+                let mut __tag = #krate::tag::Tag::Map(::std::boxed::Box::default());
+                #(
+                    let __value = #krate::tag::convert::IntoTag::to_tag(#bindings);
+                    if !__value.is_null() {
+                        __tag[#keys] = __value;
+                    }
+                )*
+                __tag
+            })
+        }
+        Fields::Unnamed(_) if written.len() == 1 => {
+            let binding = &written[0].binding;
+            quote!(#krate::tag::convert::IntoTag::to_tag(#binding))
+        }
+        Fields::Unnamed(_) => {
+            let bindings = written.iter().map(|field| &field.binding);
+            quote!(#krate::tag::Tag::from(::std::vec![#(#krate::tag::convert::IntoTag::to_tag(#bindings)),*]))
+        }
+        Fields::Unit => quote!(#krate::tag::Tag::Null),
+    }
+}
+
+fn default_value(field: &TagField) -> Option<TokenStream> {
+    if field.attrs.skip {
+        return Some(quote!(::std::default::Default::default()));
+    }
+    match &field.attrs.default {
+        FieldDefault::None => None,
+        FieldDefault::Default => Some(quote!(::std::default::Default::default())),
+        FieldDefault::Path(path) => Some(quote!(#path())),
+    }
+}
+
+/// Reads the fields from `__tag` and constructs `constructor` with them.
+fn fields_from_tag(krate: &Path, constructor: TokenStream, fields: &Fields, tag_fields: &[TagField]) -> TokenStream {
+    let read = tag_fields.iter().filter(|field| !field.attrs.skip).collect::<Vec<_>>();
+    let values = tag_fields.iter().map(|field| {
+        let member = &field.member;
+        let ty = &field.field.ty;
+        let value = if field.attrs.skip {
+            default_value(field).unwrap()
+        } else {
+            let key = &field.key;
+            let missing = default_value(field).unwrap_or_else(|| quote!(
+                <#ty as #krate::tag::convert::FromTag>::from_tag(&#krate::tag::Tag::Null)
+                    .map_err(|_| #krate::tag::convert::FromTagError::missing(#key))?
+            ));
+            match fields {
+                Fields::Named(_) => quote!(
+                    match __map.get(#key) {
+                        ::std::option::Option::Some(__value) if !__value.is_null() => {
+                            <#ty as #krate::tag::convert::FromTag>::from_tag(__value).map_err(|err| err.at_key(#key))?
+                        }
+                        _ => #missing,
+                    }
+                ),
+                Fields::Unnamed(_) if read.len() == 1 => quote!(
+                    <#ty as #krate::tag::convert::FromTag>::from_tag(__tag)?
+                ),
+                _ => {
+                    let index = read.iter().position(|read| read.binding == field.binding).unwrap();
+                    quote!(
+                        <#ty as #krate::tag::convert::FromTag>::from_tag(&__tags[#index]).map_err(|err| err.at_index(#index))?
+                    )
+                }
+            }
+        };
+        quote!(#member: #value)
+    });
+    let body = quote!(#constructor { #(#values),* });
+    let count = read.len();
+    match fields {
+        Fields::Named(_) => quote!({
+            let #krate::tag::Tag::Map(__map) = __tag else {
+                return ::std::result::Result::Err(#krate::tag::convert::FromTagError::wrong_type("Map", __tag));
+            };
+            #body
+        }),
+        Fields::Unnamed(_) if count != 1 => quote!({
+            let __tags = <::std::vec::Vec<#krate::tag::Tag> as #krate::tag::convert::FromTag>::from_tag(__tag)?;
+            if __tags.len() != #count {
+                return ::std::result::Result::Err(#krate::tag::convert::FromTagError::wrong_length(#count, __tags.len()));
+            }
+            #body
+        }),
+        _ => body,
+    }
+}
+
+fn bindings_pattern(fields: &Fields, tag_fields: &[TagField]) -> TokenStream {
+    let members = tag_fields.iter().map(|field| &field.member);
+    let bindings = tag_fields.iter().map(|field| &field.binding);
+    match fields {
+        Fields::Unit => quote!(),
+        _ => quote!({ #(#members: #bindings),* }),
+    }
+}
+
+pub fn derive_into_tag(input: DeriveInput) -> syn::Result<TokenStream> {
+    let krate = crate_path(&input)?;
+    let name = &input.ident;
+    let generics = add_bounds(&input.generics, &parse_quote!(#krate::tag::convert::IntoTag));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let fields = tag_fields(&data.fields)?;
+            let pattern = bindings_pattern(&data.fields, &fields);
+            let tag = fields_into_tag(&krate, &data.fields, &fields);
+            quote!({
+                let Self #pattern = self;
+                #tag
+            })
+        }
+        Data::Enum(data) => {
+            let arms = data.variants.iter().map(|variant| {
+                let ident = &variant.ident;
+                let variant_name = variant_name(variant)?;
+                let fields = tag_fields(&variant.fields)?;
+                let pattern = bindings_pattern(&variant.fields, &fields);
+                if let Fields::Unit = variant.fields {
+                    return Ok(quote!(Self::#ident => #krate::tag::Tag::from(#variant_name)));
+                }
+                let tag = fields_into_tag(&krate, &variant.fields, &fields);
+                Ok(quote!(Self::#ident #pattern => {
+                    let mut __tag = #krate::tag::Tag::Map(::std::boxed::Box::default());
+                    __tag[#variant_name] = #tag;
+                    __tag
+                }))
+            }).collect::<syn::Result<Vec<_>>>()?;
+            quote!(match self {
+                #(#arms,)*
+            })
+        }
+        Data::Union(data) => return Err(syn::Error::new_spanned(data.union_token, "IntoTag can't be derived for unions")),
+    };
+    Ok(quote!(
+        impl #impl_generics #krate::tag::convert::IntoTag for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn to_tag(&self) -> #krate::tag::Tag {
+                #body
+            }
+        }
+    ))
+}
+
+pub fn derive_from_tag(input: DeriveInput) -> syn::Result<TokenStream> {
+    let krate = crate_path(&input)?;
+    let name = &input.ident;
+    let generics = add_bounds(&input.generics, &parse_quote!(#krate::tag::convert::FromTag));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let fields = tag_fields(&data.fields)?;
+            let value = fields_from_tag(&krate, quote!(Self), &data.fields, &fields);
+            quote!(::std::result::Result::Ok(#value))
+        }
+        Data::Enum(data) => {
+            let mut unit_arms = Vec::new();
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let variant_name = variant_name(variant)?;
+                if let Fields::Unit = variant.fields {
+                    unit_arms.push(quote!(#variant_name => ::std::result::Result::Ok(Self::#ident)));
+                    continue;
+                }
+                let fields = tag_fields(&variant.fields)?;
+                let value = fields_from_tag(&krate, quote!(Self::#ident), &variant.fields, &fields);
+                arms.push(quote!(#variant_name => (|| ::std::result::Result::Ok(#value))().map_err(
+                    |err: #krate::tag::convert::FromTagError| err.at_key(#variant_name)
+                )));
+            }
+            quote!(match __tag {
+                #krate::tag::Tag::String(__name) => match __name.as_str() {
+                    #(#unit_arms,)*
+                    __name => ::std::result::Result::Err(#krate::tag::convert::FromTagError::unknown_variant(__name)),
+                },
+                #krate::tag::Tag::Map(__variant) if __variant.len() == 1 => {
+                    let (__name, __tag) = __variant.iter().next().unwrap();
+                    match __name.as_str() {
+                        #(#arms,)*
+                        __name => ::std::result::Result::Err(#krate::tag::convert::FromTagError::unknown_variant(__name)),
+                    }
+                }
+                __tag => ::std::result::Result::Err(#krate::tag::convert::FromTagError::wrong_type("String or Map with one entry", __tag)),
+            })
+        }
+        Data::Union(data) => return Err(syn::Error::new_spanned(data.union_token, "FromTag can't be derived for unions")),
+    };
+    Ok(quote!(
+        impl #impl_generics #krate::tag::convert::FromTag for #name #ty_generics #where_clause {
+            #[allow(unused_variables, clippy::redundant_closure_call)]
+            fn from_tag(__tag: &#krate::tag::Tag) -> #krate::tag::convert::Result<Self> {
+                #body
+            }
+        }
+    ))
+}
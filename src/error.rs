@@ -10,8 +10,8 @@ pub enum Error {
     IoError(#[from]std::io::Error),
     #[error("From UTF-8 Error: {0}")]
     FromUtf8Error(#[from]std::string::FromUtf8Error),
-    // #[error("Block Error: {0}")]
-    // BlockError(#[from]crate::voxel::block::error::Error),
+    #[error("Block Error: {0}")]
+    BlockError(#[from]crate::voxel::block::error::Error),
     #[error("String too long")]
     StringTooLong,
     #[error("Array too long")]
@@ -34,12 +34,20 @@ pub enum Error {
     ChunkNotFound,
     #[error("No parent directory. Likely indicates root diretory or something.")]
     ParentNotFound,
+    #[error("Tag nesting exceeded the maximum depth of {0}")]
+    TagTooDeep(usize),
+    #[error("Tag exceeded the maximum size of {0} bytes")]
+    TagTooLarge(u64),
+    #[error("Tag exceeded the maximum of {0} elements")]
+    TooManyTagElements(usize),
+    #[error("Tag string exceeded the maximum length of {0} bytes")]
+    TagStringTooLong(usize),
     #[error("u24 was out of range")]
     U24OutOfRange,
     #[error("Json Error: {0}")]
     JsonError(#[from] serde_json::Error),
-    #[error("GLTF Error: {0}")]
-    GltfError(#[from] gltf::Error),
+    // #[error("GLTF Error: {0}")]
+    // GltfError(#[from] gltf::Error),
     #[error("Mesh Extraction Error")]
     MeshExtractionError,
     #[error("Custom Error: {0}")]
@@ -12,7 +12,9 @@ use crate::rendering::color::{
 use crate::prelude::{
     Axis, Cardinal, Direction, FaceFlags, Flip, Increment, Orientation, PropertyArray, Rotation
 };
+use crate::util::identifier::Identifier;
 use crate::tag::{
+    limits::TagReadLimits,
     NonByte,
     Tag,
 };
@@ -42,6 +44,8 @@ use itertools::*;
 use hexmacros::mark;
 
 const MAX_ARRAY_LENGTH: usize = 0xffffff;
+/// The most elements that are allocated up front when reading a length prefix, the same limit as `MAX_PREALLOCATE` in [hexio].
+pub(crate) const MAX_PREALLOCATE: usize = 4096;
 
 /// A marker trait that describes a Writeable type as being deterministic, which means that equal values of that type
 /// will always result in the same binary representation.
@@ -105,7 +109,7 @@ pub fn write_u24<W: Write>(writer: &mut W, value: u32) -> Result<u64> {
 
 pub fn read_vec<T: Readable, R: std::io::Read>(reader: &mut R) -> Result<Vec<T>> {
     let length = read_u24(reader)?;
-    let mut result = Vec::with_capacity(length as usize);
+    let mut result = Vec::with_capacity((length as usize).min(MAX_PREALLOCATE));
     for _ in 0..length {
         result.push(T::read_from(reader)?);
     }
@@ -134,8 +138,11 @@ pub fn write_zeros<W: Write>(writer: &mut W, count: u64) -> Result<u64> {
 
 /// Reads an exact number of bytes from a reader, returning them as a [Vec].
 pub fn read_bytes<R: Read>(reader: &mut R, length: usize) -> Result<Vec<u8>> {
-    let mut buf: Vec<u8> = vec![0u8; length];
-    reader.read_exact(&mut buf)?;
+    let mut buf: Vec<u8> = Vec::with_capacity(length.min(MAX_PREALLOCATE));
+    reader.by_ref().take(length as u64).read_to_end(&mut buf)?;
+    if buf.len() != length {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
     Ok(buf)
 }
 
@@ -800,6 +807,19 @@ impl Writeable for String {
     }
 }
 
+impl Readable for Identifier {
+    fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let text = String::read_from(reader)?;
+        Identifier::parse(&text).map_err(|_| Error::InvalidBinaryFormat)
+    }
+}
+
+impl Writeable for Identifier {
+    fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        self.as_str().write_to(writer)
+    }
+}
+
 impl Writeable for &str {
     fn write_to<W: Write>(&self, writer: &mut W) -> Result<u64> {
         if self.len() > MAX_ARRAY_LENGTH {
@@ -1500,18 +1520,7 @@ impl Writeable for Vec<Axis> {
 impl Readable for hashbrown::HashMap<String, Tag> {
     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
         // read name, read tag, use 255 as Stop
-        let mut map = hashbrown::HashMap::new();
-        loop {
-            let id: u8 = u8::read_from(reader)?;
-            // Stop
-            if id == 255 {
-                break;
-            }
-            let name: String = String::read_from(reader)?;
-            let tag: Tag = Tag::read_with_id(id, reader)?;
-            map.insert(name, tag);
-        }
-        Ok(map)
+        TagReadLimits::current().read_map(reader)
     }
 }
 
@@ -1530,17 +1539,7 @@ impl Writeable for hashbrown::HashMap<String, Tag> {
 
 impl Readable for BTreeMap<String, Tag> {
     fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-        let mut map = BTreeMap::new();
-        loop {
-            let id = u8::read_from(reader)?;
-            if id == 255 {
-                break;
-            }
-            let name = String::read_from(reader)?;
-            let tag = Tag::read_with_id(id, reader)?;
-            map.insert(name, tag);
-        }
-        Ok(map)
+        TagReadLimits::current().read_map(reader)
     }
 }
 
@@ -1750,4 +1749,15 @@ mod tests {
         let axes2 = Vec::<Axis>::read_from(&mut buffer).unwrap();
         debug_assert!(axes == axes2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn identifier_write_read_test() {
+        let mut buffer = std::io::Cursor::new(Vec::<u8>::new());
+        let id = Identifier::parse("my_pack:lamps/red_lamp").unwrap();
+        id.write_to(&mut buffer).unwrap();
+        "Bad Identifier".write_to(&mut buffer).unwrap();
+        buffer.seek(SeekFrom::Start(0)).unwrap();
+        debug_assert_eq!(Identifier::read_from(&mut buffer).unwrap(), id);
+        debug_assert!(Identifier::read_from(&mut buffer).is_err());
+    }
+}
@@ -15,18 +15,21 @@
 //! * (Maybe) Multiplayer server architecture.
 //! * Scripting API.
 
-// pub mod collections;
+// Lets macros refer to this crate as `::hexahedron` from inside it as well.
+extern crate self as hexahedron;
+
+pub mod collections;
 pub mod engine;
-// pub mod io;
-// pub mod math;
+pub mod io;
+pub mod math;
 // pub mod meshing;
-// pub mod rendering;
-// pub mod tag;
+pub mod rendering;
+pub mod tag;
 pub mod util;
-// pub mod voxel;
-// pub mod error;
-// pub mod macros;
-// pub mod prelude;
+pub mod voxel;
+pub mod error;
+pub mod macros;
+pub mod prelude;
 pub(crate) mod private;
 
 pub use log;
@@ -1,7 +1,7 @@
 use serde::{Serialize, Deserialize};
 use bytemuck::NoUninit;
 
-use crate::prelude::Direction;
+use crate::prelude::{Direction, Orientation};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NoUninit, Serialize, Deserialize)]
 #[repr(u8)]
@@ -29,4 +29,10 @@ impl Axis {
             Axis::Z => Direction::NegZ,
         }
     }
+
+    /// The [Axis] that this axis is parallel to after `orientation` is applied.
+    #[inline]
+    pub const fn reorient(self, orientation: Orientation) -> Self {
+        orientation.reface(self.pos()).axis()
+    }
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
 use serde::{Serialize, Deserialize};
 use bytemuck::NoUninit;
 
+use crate::voxel::orientation::Orientation;
+
 use super::axis::Axis;
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, NoUninit, Serialize, Deserialize)]
@@ -65,6 +67,15 @@ impl AxisFlags {
             (self.0 & AxisFlags::Z.0) != 0,
         ]
     }
+
+    /// Moves each flag to the axis that its axis is parallel to after `orientation` is applied.
+    pub fn reorient(self, orientation: Orientation) -> Self {
+        let mut result = AxisFlags::NONE;
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            result.set_axis(axis.reorient(orientation), self.get_axis(axis));
+        }
+        result
+    }
 }
 
 impl Into<(bool, bool, bool)> for AxisFlags {
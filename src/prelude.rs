@@ -20,11 +20,48 @@ pub use crate::{
                 BlockRegistry,
                 RefOrOwned,
             },
+            block_migration::{
+                DataVersion,
+                MigrationRule,
+                MigrationTable,
+            },
+            block_palette::{
+                BlockPalette,
+                MissingBlockPolicy,
+                StateRemap,
+            },
+            frozen_registry::FrozenBlockRegistry,
+            block_tag::{
+                BlockSet,
+                TagEntry,
+            },
+            block_material::{
+                BlockMaterial,
+                Transparency,
+            },
+            block_shape::{
+                BlockShape,
+                ShapeBox,
+            },
+            data_block::{
+                BlockDefinition,
+                DataBlock,
+            },
+            block_schema::{
+                BlockSchema,
+                PropertyDef,
+                PropertyValues,
+            },
             block_state::{
                 BlockState,
                 blockstate,
             },
             block::BlockBehavior,
+            block_context::{
+                BlockContext,
+                BlockWorld,
+                UseResult,
+            },
             id::{
                 BlockId,
                 StateId,
@@ -100,6 +137,10 @@ pub use crate::{
         gray,
     },
     util::{
+        identifier::{
+            Identifier,
+            IdentifierMap,
+        },
         extensions::{
             BoolExtension,
             Decrement,
@@ -0,0 +1,502 @@
+//! Conversion between Rust types and [Tag]s.
+//!
+//! [IntoTag] and [FromTag] can be derived with `#[derive(IntoTag, FromTag)]` from `hexmacros`.
+//! Structs with named fields become a [Tag::Map] with a key for each field, tuple structs become an array of tags
+//! (or the inner value for a struct with a single field), and unit structs become [Tag::Null].
+//! Unit enum variants become a [Tag::String] of the variant name, and other variants become a map with the variant
+//! name as the only key.
+//!
+//! The derives accept these attributes:
+//! - `#[tag(crate = "path")]` on the type: the path to this crate, `::hexahedron` by default.
+//! - `#[tag(rename = "name")]` on a field or variant: the key or name used in the tag.
+//! - `#[tag(default)]` on a field: use [Default::default] when the field is missing.
+//! - `#[tag(default = "path")]` on a field: call the function at `path` when the field is missing.
+//! - `#[tag(skip)]` on a field: never write the field, and use [Default::default] when reading.
+//!
+//! A missing [Option] field is read as [None].
+//! ```rust
+//! use hexahedron::tag::convert::{FromTag, FromTagError, IntoTag};
+//! use hexmacros::{FromTag, IntoTag};
+//!
+//! #[derive(IntoTag, FromTag)]
+//! struct Item {
+//!     id: String,
+//!     count: Option<u8>,
+//! }
+//!
+//! #[derive(IntoTag, FromTag)]
+//! struct Chest {
+//!     #[tag(rename = "id")]
+//!     name: String,
+//!     #[tag(default)]
+//!     open: bool,
+//!     items: Vec<Item>,
+//!     #[tag(skip)]
+//!     viewers: u32,
+//! }
+//!
+//! # fn main() -> Result<(), FromTagError> {
+//! let chest = Chest {
+//!     name: "chest".to_owned(),
+//!     open: true,
+//!     items: vec![Item { id: "apple".to_owned(), count: Some(3) }],
+//!     viewers: 2,
+//! };
+//! let read = Chest::from_tag(&chest.to_tag())?;
+//! assert_eq!(read.items[0].count, Some(3));
+//! assert_eq!(read.viewers, 0);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+
+use hashbrown::HashMap;
+use thiserror::Error;
+
+use super::path::{PathSegment, TagPath};
+use super::{tag_table, Array, Tag};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FromTagErrorKind {
+    #[error("the field is missing")]
+    Missing,
+    #[error("expected {expected}, found {found}")]
+    WrongType {
+        expected: &'static str,
+        found: &'static str,
+    },
+    #[error("expected {expected} elements, found {found}")]
+    WrongLength {
+        expected: usize,
+        found: usize,
+    },
+    #[error("unknown variant \"{0}\"")]
+    UnknownVariant(String),
+    #[error("{0}")]
+    Custom(String),
+}
+
+/// The error returned by [FromTag], with the path to the value that failed to convert.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub struct FromTagError {
+    pub path: TagPath,
+    pub kind: FromTagErrorKind,
+}
+
+pub type Result<T> = std::result::Result<T, FromTagError>;
+
+impl FromTagError {
+    #[inline]
+    pub fn new(kind: FromTagErrorKind) -> Self {
+        Self {
+            path: TagPath::ROOT,
+            kind,
+        }
+    }
+
+    /// The field `key` is missing from a map.
+    #[inline]
+    pub fn missing(key: &str) -> Self {
+        Self::new(FromTagErrorKind::Missing).at_key(key)
+    }
+
+    #[inline]
+    pub fn wrong_type(expected: &'static str, found: &Tag) -> Self {
+        Self::new(FromTagErrorKind::WrongType {
+            expected,
+            found: found.type_name(),
+        })
+    }
+
+    #[inline]
+    pub fn wrong_length(expected: usize, found: usize) -> Self {
+        Self::new(FromTagErrorKind::WrongLength { expected, found })
+    }
+
+    #[inline]
+    pub fn unknown_variant(name: &str) -> Self {
+        Self::new(FromTagErrorKind::UnknownVariant(name.to_owned()))
+    }
+
+    #[inline]
+    pub fn custom<S: std::fmt::Display>(message: S) -> Self {
+        Self::new(FromTagErrorKind::Custom(message.to_string()))
+    }
+
+    /// Prepends `segment` to the path of the error. Used when the error is returned from a nested value.
+    pub fn at(self, segment: PathSegment) -> Self {
+        Self {
+            path: TagPath::new(std::iter::once(segment).chain(self.path.segments().iter().cloned())),
+            kind: self.kind,
+        }
+    }
+
+    #[inline]
+    pub fn at_key(self, key: &str) -> Self {
+        self.at(PathSegment::Key(key.to_owned()))
+    }
+
+    #[inline]
+    pub fn at_index(self, index: usize) -> Self {
+        self.at(PathSegment::Index(index))
+    }
+}
+
+impl std::fmt::Display for FromTagError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "Failed to convert tag: {}.", self.kind)
+        } else {
+            write!(f, "Failed to convert tag at \"{}\": {}.", self.path, self.kind)
+        }
+    }
+}
+
+/// Converts a value into a [Tag]. Can be derived with `#[derive(IntoTag)]`.
+pub trait IntoTag {
+    fn to_tag(&self) -> Tag;
+
+    /// Converts a slice of values. Types that have a typed [Array] use it instead of an array of tags.
+    #[doc(hidden)]
+    fn slice_to_tag(values: &[Self]) -> Tag
+    where Self: Sized {
+        Tag::from(values.iter().map(IntoTag::to_tag).collect::<Vec<Tag>>())
+    }
+}
+
+/// Converts a [Tag] into a value. Can be derived with `#[derive(FromTag)]`.
+pub trait FromTag: Sized {
+    fn from_tag(tag: &Tag) -> Result<Self>;
+
+    /// Reads the typed [Array] of this type, if there is one.
+    #[doc(hidden)]
+    fn from_typed_array(_array: &Array) -> Option<Vec<Self>> {
+        None
+    }
+}
+
+macro_rules! convert_impls {
+    ($({$id:literal $name:ident $impl:ident $box:ident <$type:ty> $($end:tt)*})*) => {
+        impl Tag {
+            /// The name of the variant, such as `"I32"` or `"Null"`.
+            pub fn type_name(&self) -> &'static str {
+                match self {
+                    Tag::Null => "Null",
+                    $(
+                        Tag::$name(_) => stringify!($name),
+                    )*
+                }
+            }
+        }
+
+        $(
+            convert_impls!(@impl $name $box $type);
+        )*
+    };
+    // Maps are converted by the generic impl for `HashMap<String, T>`.
+    (@impl Map $box:ident $type:ty) => {};
+    (@impl $name:ident $box:ident $type:ty) => {
+        impl IntoTag for $type {
+            #[inline]
+            fn to_tag(&self) -> Tag {
+                Tag::from(self.clone())
+            }
+
+            fn slice_to_tag(values: &[Self]) -> Tag {
+                Tag::Array(Box::new(Array::$name(values.to_vec())))
+            }
+        }
+
+        impl FromTag for $type {
+            fn from_tag(tag: &Tag) -> Result<Self> {
+                match tag {
+                    Tag::$name(value) => Ok(convert_impls!(@unbox $box value)),
+                    _ => Err(FromTagError::wrong_type(stringify!($name), tag)),
+                }
+            }
+
+            fn from_typed_array(array: &Array) -> Option<Vec<Self>> {
+                match array {
+                    Array::$name(values) => Some(values.clone()),
+                    _ => None,
+                }
+            }
+        }
+    };
+    (@unbox box $value:ident) => {
+        (**$value).clone()
+    };
+    (@unbox unbox $value:ident) => {
+        $value.clone()
+    };
+}
+
+tag_table!(convert_impls);
+
+impl IntoTag for Tag {
+    #[inline]
+    fn to_tag(&self) -> Tag {
+        self.clone()
+    }
+}
+
+impl FromTag for Tag {
+    #[inline]
+    fn from_tag(tag: &Tag) -> Result<Self> {
+        Ok(tag.clone())
+    }
+}
+
+impl IntoTag for str {
+    #[inline]
+    fn to_tag(&self) -> Tag {
+        Tag::from(self)
+    }
+}
+
+impl<T: IntoTag + ?Sized> IntoTag for &T {
+    #[inline]
+    fn to_tag(&self) -> Tag {
+        T::to_tag(self)
+    }
+}
+
+impl<T: IntoTag + ?Sized> IntoTag for Box<T> {
+    #[inline]
+    fn to_tag(&self) -> Tag {
+        T::to_tag(self)
+    }
+}
+
+impl<T: FromTag> FromTag for Box<T> {
+    #[inline]
+    fn from_tag(tag: &Tag) -> Result<Self> {
+        T::from_tag(tag).map(Box::new)
+    }
+}
+
+/// [None] is [Tag::Null].
+impl<T: IntoTag> IntoTag for Option<T> {
+    fn to_tag(&self) -> Tag {
+        match self {
+            Some(value) => value.to_tag(),
+            None => Tag::Null,
+        }
+    }
+}
+
+impl<T: FromTag> FromTag for Option<T> {
+    fn from_tag(tag: &Tag) -> Result<Self> {
+        match tag {
+            Tag::Null => Ok(None),
+            tag => T::from_tag(tag).map(Some),
+        }
+    }
+}
+
+impl<T: IntoTag> IntoTag for [T] {
+    #[inline]
+    fn to_tag(&self) -> Tag {
+        T::slice_to_tag(self)
+    }
+}
+
+impl<T: IntoTag> IntoTag for Vec<T> {
+    #[inline]
+    fn to_tag(&self) -> Tag {
+        T::slice_to_tag(self)
+    }
+}
+
+impl<T: FromTag> FromTag for Vec<T> {
+    fn from_tag(tag: &Tag) -> Result<Self> {
+        let Tag::Array(array) = tag else {
+            return Err(FromTagError::wrong_type("Array", tag));
+        };
+        match array.as_ref() {
+            Array::Empty => Ok(Vec::new()),
+            Array::Tag(tags) => tags.iter()
+                .enumerate()
+                .map(|(index, tag)| T::from_tag(tag).map_err(|err| err.at_index(index)))
+                .collect(),
+            typed => T::from_typed_array(typed).ok_or_else(|| FromTagError::new(FromTagErrorKind::WrongType {
+                expected: "Array",
+                found: "typed array of another type",
+            })),
+        }
+    }
+}
+
+impl<T: IntoTag, const SIZE: usize> IntoTag for [T; SIZE] {
+    #[inline]
+    fn to_tag(&self) -> Tag {
+        T::slice_to_tag(self)
+    }
+}
+
+impl<T: FromTag, const SIZE: usize> FromTag for [T; SIZE] {
+    fn from_tag(tag: &Tag) -> Result<Self> {
+        let values = Vec::<T>::from_tag(tag)?;
+        let found = values.len();
+        values.try_into().map_err(|_| FromTagError::wrong_length(SIZE, found))
+    }
+}
+
+fn map_to_tag<'a, T: IntoTag + 'a, It: IntoIterator<Item = (&'a String, &'a T)>>(entries: It) -> Tag {
+    Tag::Map(Box::new(entries.into_iter().map(|(key, value)| (key.clone(), value.to_tag())).collect()))
+}
+
+fn map_from_tag<T: FromTag, M: FromIterator<(String, T)>>(tag: &Tag) -> Result<M> {
+    let Tag::Map(map) = tag else {
+        return Err(FromTagError::wrong_type("Map", tag));
+    };
+    map.iter()
+        .map(|(key, value)| Ok((key.clone(), T::from_tag(value).map_err(|err| err.at_key(key))?)))
+        .collect()
+}
+
+impl<T: IntoTag> IntoTag for HashMap<String, T> {
+    #[inline]
+    fn to_tag(&self) -> Tag {
+        map_to_tag(self)
+    }
+}
+
+impl<T: FromTag> FromTag for HashMap<String, T> {
+    #[inline]
+    fn from_tag(tag: &Tag) -> Result<Self> {
+        map_from_tag(tag)
+    }
+}
+
+impl<T: IntoTag> IntoTag for std::collections::HashMap<String, T> {
+    #[inline]
+    fn to_tag(&self) -> Tag {
+        map_to_tag(self)
+    }
+}
+
+impl<T: FromTag> FromTag for std::collections::HashMap<String, T> {
+    #[inline]
+    fn from_tag(tag: &Tag) -> Result<Self> {
+        map_from_tag(tag)
+    }
+}
+
+impl<T: IntoTag> IntoTag for BTreeMap<String, T> {
+    #[inline]
+    fn to_tag(&self) -> Tag {
+        map_to_tag(self)
+    }
+}
+
+impl<T: FromTag> FromTag for BTreeMap<String, T> {
+    #[inline]
+    fn from_tag(tag: &Tag) -> Result<Self> {
+        map_from_tag(tag)
+    }
+}
+
+impl Tag {
+    /// Converts the tag into `T`. See [FromTag].
+    #[inline]
+    pub fn convert<T: FromTag>(&self) -> Result<T> {
+        T::from_tag(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexmacros::{FromTag, IntoTag};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, IntoTag, FromTag)]
+    struct Item {
+        id: String,
+        #[tag(default = "one")]
+        count: u8,
+    }
+
+    fn one() -> u8 {
+        1
+    }
+
+    #[derive(Debug, Clone, PartialEq, IntoTag, FromTag)]
+    enum Lock {
+        Open,
+        #[tag(rename = "key")]
+        Key { id: u32 },
+        Code(String, u16),
+    }
+
+    #[derive(Debug, Clone, PartialEq, IntoTag, FromTag)]
+    struct Chest<T> {
+        #[tag(rename = "type")]
+        kind: String,
+        #[tag(default)]
+        open: bool,
+        items: Vec<Item>,
+        levels: Vec<u8>,
+        lock: Lock,
+        owner: Option<String>,
+        extra: T,
+        #[tag(skip)]
+        viewers: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, IntoTag, FromTag)]
+    struct Meters(f32);
+
+    #[test]
+    fn derive_round_trip_test() {
+        let chest = Chest {
+            kind: "chest".to_owned(),
+            open: true,
+            items: vec![Item { id: "apple".to_owned(), count: 3 }],
+            levels: vec![1, 2, 3],
+            lock: Lock::Key { id: 7 },
+            owner: None,
+            extra: Meters(1.5),
+            viewers: 4,
+        };
+        let tag = chest.to_tag();
+        debug_assert_eq!(tag["type"], Tag::from("chest"));
+        debug_assert_eq!(tag["levels"], Tag::from(vec![1u8, 2, 3]));
+        debug_assert_eq!(tag["extra"], Tag::F32(1.5));
+        debug_assert!(tag.get("owner").is_err());
+        debug_assert!(tag.get("viewers").is_err());
+        debug_assert_eq!(tag.get("lock.key.id").unwrap(), &Tag::U32(7));
+        let read = Chest::<Meters>::from_tag(&tag).unwrap();
+        debug_assert_eq!(read, Chest { viewers: 0, ..chest });
+
+        for lock in [Lock::Open, Lock::Key { id: 1 }, Lock::Code("1234".to_owned(), 4)] {
+            debug_assert_eq!(lock.to_tag().convert::<Lock>().unwrap(), lock);
+        }
+        debug_assert_eq!(Lock::Open.to_tag(), Tag::from("Open"));
+    }
+
+    #[test]
+    fn from_tag_error_test() {
+        let tag = Tag::from_text(r#"{items: [{id: "apple"}], levels: [U8;], lock: "Open", extra: 1i32}"#).unwrap();
+        let err = Chest::<i32>::from_tag(&tag).unwrap_err();
+        debug_assert_eq!(err, FromTagError::missing("type"));
+        debug_assert_eq!(err.to_string(), "Failed to convert tag at \"type\": the field is missing.");
+
+        let tag = Tag::from_text(r#"{type: "chest", items: [{id: "apple"}, {id: 5i32}], levels: [], lock: "Open", extra: 1i32}"#).unwrap();
+        let err = Chest::<i32>::from_tag(&tag).unwrap_err();
+        debug_assert_eq!(err.path.to_string(), "items[1].id");
+        debug_assert_eq!(err.kind, FromTagErrorKind::WrongType { expected: "String", found: "I32" });
+
+        let tag = Tag::from_text(r#"{type: "chest", items: [], levels: [], lock: {key: {id: 1i32}}, extra: 1i32}"#).unwrap();
+        let err = Chest::<i32>::from_tag(&tag).unwrap_err();
+        debug_assert_eq!(err.path.to_string(), "lock.key.id");
+
+        let err = Lock::from_tag(&Tag::from("Closed")).unwrap_err();
+        debug_assert_eq!(err.kind, FromTagErrorKind::UnknownVariant("Closed".to_owned()));
+        let err = Lock::from_tag(&Tag::from_text(r#"{Code: ["1234"]}"#).unwrap()).unwrap_err();
+        debug_assert_eq!(err.path.to_string(), "Code");
+        debug_assert_eq!(err.kind, FromTagErrorKind::WrongLength { expected: 2, found: 1 });
+    }
+}
@@ -0,0 +1,274 @@
+//! Structural diffs between [Tag]s, which can be applied to a tag as a patch.
+//!
+//! Sending the [TagDiff] of a tag instead of the whole tag only sends what changed.
+
+use crate::io::{read_vec, write_slice, Readable, Writeable};
+
+use super::path::{self, PathSegment, TagPath, TagPathError};
+use super::{Array, Tag};
+
+/// A single change in a [TagDiff].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagChange {
+    /// A map entry or an array element at the end of an array was added.
+    Added(TagPath, Tag),
+    /// A map entry or an array element was removed.
+    Removed(TagPath),
+    /// The value at the path was replaced.
+    Changed(TagPath, Tag),
+}
+
+impl TagChange {
+    #[inline]
+    pub fn path(&self) -> &TagPath {
+        match self {
+            TagChange::Added(path, _) | TagChange::Removed(path) | TagChange::Changed(path, _) => path,
+        }
+    }
+
+    /// Applies the change to `tag`.
+    pub fn apply(&self, tag: &mut Tag) -> path::Result<()> {
+        match self {
+            TagChange::Added(path, value) => {
+                let Some((&PathSegment::Index(index), parent)) = path.segments().split_last() else {
+                    tag.set(path, value.clone())?;
+                    return Ok(());
+                };
+                // Elements are added to the end of the array.
+                let Tag::Array(array) = tag.get_mut(&TagPath::new(parent.iter().cloned()))? else {
+                    return Err(TagPathError::NotAnArray(path.to_string()));
+                };
+                match array.as_mut() {
+                    Array::Tag(tags) if index == tags.len() => tags.push(value.clone()),
+                    Array::Tag(tags) => return Err(TagPathError::IndexOutOfBounds(path.to_string(), tags.len())),
+                    Array::Empty if index == 0 => **array = Array::Tag(vec![value.clone()]),
+                    Array::Empty => return Err(TagPathError::IndexOutOfBounds(path.to_string(), 0)),
+                    _ => return Err(TagPathError::TypedArray(path.to_string())),
+                }
+            }
+            TagChange::Removed(path) => {
+                tag.remove(path)?;
+            }
+            TagChange::Changed(path, value) => {
+                *tag.get_mut(path)? = value.clone();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The changes that turn one [Tag] into another, created with [Tag::diff].
+///
+/// Changes are in the order that they need to be applied. Elements removed from an array are removed from the end first
+/// so that the indices of the other removed elements don't shift.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TagDiff {
+    changes: Vec<TagChange>,
+}
+
+impl TagDiff {
+    #[inline]
+    pub fn changes(&self) -> &[TagChange] {
+        &self.changes
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Applies every change to `tag` in order.
+    /// If a change fails, the changes before it stay applied.
+    pub fn apply(&self, tag: &mut Tag) -> path::Result<()> {
+        for change in &self.changes {
+            change.apply(tag)?;
+        }
+        Ok(())
+    }
+}
+
+fn diff_into(old: &Tag, new: &Tag, path: &TagPath, changes: &mut Vec<TagChange>) {
+    match (old, new) {
+        (Tag::Map(old_map), Tag::Map(new_map)) => {
+            // Sorted so that the diff is the same every time.
+            let mut removed = old_map.keys().filter(|key| !new_map.contains_key(*key)).collect::<Vec<_>>();
+            removed.sort_unstable();
+            for key in removed {
+                changes.push(TagChange::Removed(path.join(PathSegment::Key(key.clone()))));
+            }
+            let mut keys = new_map.keys().collect::<Vec<_>>();
+            keys.sort_unstable();
+            for key in keys {
+                let child_path = path.join(PathSegment::Key(key.clone()));
+                match old_map.get(key) {
+                    Some(old_value) => diff_into(old_value, &new_map[key], &child_path, changes),
+                    None => changes.push(TagChange::Added(child_path, new_map[key].clone())),
+                }
+            }
+        }
+        (Tag::Array(old_array), Tag::Array(new_array)) => match (old_array.as_ref(), new_array.as_ref()) {
+            (Array::Tag(old_tags), Array::Tag(new_tags)) => {
+                for (index, (old_value, new_value)) in old_tags.iter().zip(new_tags).enumerate() {
+                    diff_into(old_value, new_value, &path.join(PathSegment::Index(index)), changes);
+                }
+                for index in (new_tags.len()..old_tags.len()).rev() {
+                    changes.push(TagChange::Removed(path.join(PathSegment::Index(index))));
+                }
+                for (index, value) in new_tags.iter().enumerate().skip(old_tags.len()) {
+                    changes.push(TagChange::Added(path.join(PathSegment::Index(index)), value.clone()));
+                }
+            }
+            // Typed arrays are replaced as a whole.
+            _ if old_array != new_array => changes.push(TagChange::Changed(path.clone(), new.clone())),
+            _ => (),
+        },
+        _ if old != new => changes.push(TagChange::Changed(path.clone(), new.clone())),
+        _ => (),
+    }
+}
+
+impl Tag {
+    /// Finds the changes that turn `self` into `other`.
+    ///
+    /// Maps and arrays of tags are compared entry by entry. Any other value that isn't equal is changed as a whole.
+    pub fn diff(&self, other: &Tag) -> TagDiff {
+        let mut changes = Vec::new();
+        diff_into(self, other, &TagPath::ROOT, &mut changes);
+        TagDiff { changes }
+    }
+
+    /// Applies a diff created with [Tag::diff]. See [TagDiff::apply].
+    #[inline]
+    pub fn patch(&mut self, diff: &TagDiff) -> path::Result<()> {
+        diff.apply(self)
+    }
+}
+
+/// Written as `~ inventory[3].count: 5i32`, with values written as [tag text](super::text).
+impl std::fmt::Display for TagChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (sign, value) = match self {
+            TagChange::Added(_, value) => ('+', Some(value)),
+            TagChange::Removed(_) => ('-', None),
+            TagChange::Changed(_, value) => ('~', Some(value)),
+        };
+        let path = self.path();
+        if path.is_empty() {
+            write!(f, "{sign} (root)")?;
+        } else {
+            write!(f, "{sign} {path}")?;
+        }
+        match value {
+            Some(value) => write!(f, ": {value}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Display for TagDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Readable for TagChange {
+    fn read_from<R: std::io::Read>(reader: &mut R) -> crate::prelude::VoxelResult<Self> {
+        Ok(match u8::read_from(reader)? {
+            0 => TagChange::Added(TagPath::read_from(reader)?, Tag::read_from(reader)?),
+            1 => TagChange::Removed(TagPath::read_from(reader)?),
+            2 => TagChange::Changed(TagPath::read_from(reader)?, Tag::read_from(reader)?),
+            _ => return Err(crate::error::Error::InvalidBinaryFormat),
+        })
+    }
+}
+
+impl Writeable for TagChange {
+    fn write_to<W: std::io::Write>(&self, writer: &mut W) -> crate::prelude::VoxelResult<u64> {
+        Ok(match self {
+            TagChange::Added(path, value) => 0u8.write_to(writer)? + path.write_to(writer)? + value.write_to(writer)?,
+            TagChange::Removed(path) => 1u8.write_to(writer)? + path.write_to(writer)?,
+            TagChange::Changed(path, value) => 2u8.write_to(writer)? + path.write_to(writer)? + value.write_to(writer)?,
+        })
+    }
+}
+
+impl Readable for TagDiff {
+    fn read_from<R: std::io::Read>(reader: &mut R) -> crate::prelude::VoxelResult<Self> {
+        Ok(TagDiff { changes: read_vec(reader)? })
+    }
+}
+
+impl Writeable for TagDiff {
+    fn write_to<W: std::io::Write>(&self, writer: &mut W) -> crate::prelude::VoxelResult<u64> {
+        write_slice(writer, &self.changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn diff_patch_test() {
+        let old = Tag::from_text(r#"{
+            name: "chest",
+            open: false,
+            items: [{id: "apple", count: 3u8}, {id: "sword"}, {id: "stick"}],
+            pos: IVec3(1, 2, 3),
+            levels: [U8; 1, 2],
+            gone: 1i32,
+        }"#).unwrap();
+        let new = Tag::from_text(r#"{
+            name: "chest",
+            open: true,
+            items: [{id: "apple", count: 4u8}],
+            pos: IVec3(1, 2, 3),
+            levels: [U8; 1, 2, 3],
+            owner: "steve",
+        }"#).unwrap();
+        let diff = old.diff(&new);
+        debug_assert_eq!(diff.to_string(), [
+            "- gone",
+            "~ items[0].count: 4u8",
+            "- items[2]",
+            "- items[1]",
+            "~ levels: [U8; 1, 2, 3]",
+            "~ open: true",
+            "+ owner: \"steve\"",
+            "",
+        ].join("\n"));
+        let mut patched = old.clone();
+        patched.patch(&diff).unwrap();
+        debug_assert_eq!(patched, new);
+        debug_assert!(new.diff(&new).is_empty());
+
+        // Growing an array.
+        let mut patched = new.clone();
+        patched.patch(&new.diff(&old)).unwrap();
+        debug_assert_eq!(patched, old);
+
+        // Values that aren't maps or arrays of tags are replaced.
+        let diff = Tag::from(1i32).diff(&Tag::from(1u8));
+        debug_assert_eq!(diff.to_string(), "~ (root): 1u8\n");
+
+        let mut buffer = Cursor::new(Vec::new());
+        let diff = old.diff(&new);
+        diff.write_to(&mut buffer).unwrap();
+        buffer.set_position(0);
+        debug_assert_eq!(TagDiff::read_from(&mut buffer).unwrap(), diff);
+
+        // Patching a tag that doesn't match.
+        let mut other = Tag::from_text("{items: []}").unwrap();
+        debug_assert!(other.patch(&diff).is_err());
+    }
+}
@@ -0,0 +1,436 @@
+//! Limits for reading [Tag]s from untrusted sources, such as chunk files and network packets.
+//!
+//! Without limits, a corrupt or malicious stream could use length prefixes to make the reader allocate huge amounts of
+//! memory, or nest arrays and maps deeply enough to overflow the stack. Every [Readable] impl for [Tag], [Array], and
+//! maps of tags reads within the limits of the current thread, which are [TagReadLimits::DEFAULT] unless changed with
+//! [TagReadLimits::scoped].
+//!
+//! The limits apply to each top-level tag on its own. A value that contains several tags, such as a `Vec<Tag>` or a
+//! [TagDiff](super::diff::TagDiff), starts a new budget for every tag in it, so it can read up to `max_bytes` for each
+//! of them. Wrap the reader with [Read::take] to limit the size of the whole value.
+//! ```rust
+//! use std::io::{Cursor, Read};
+//!
+//! use hexahedron::io::{Readable, Writeable};
+//! use hexahedron::tag::{diff::TagDiff, limits::TagReadLimits, Tag};
+//!
+//! # fn main() -> hexahedron::error::Result<()> {
+//! # let mut bytes = Vec::new();
+//! # Tag::from("hello").write_to(&mut bytes)?;
+//! # Tag::Null.diff(&Tag::from("world")).write_to(&mut bytes)?;
+//! # let mut packet = Cursor::new(bytes);
+//! let limits = TagReadLimits { max_bytes: 2 * 1024 * 1024, ..TagReadLimits::DEFAULT };
+//! let tag = Tag::read_with_limits(&mut packet, limits)?;
+//! // Or for values that contain tags, with at most 8 MiB for the whole diff:
+//! let diff = limits.scoped(|| TagDiff::read_from(&mut (&mut packet).take(8 * 1024 * 1024)))?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::cell::Cell;
+use std::io::Read;
+
+use crate::error::{Error, Result};
+use crate::io::{read_bytes, read_u24, Readable, MAX_PREALLOCATE};
+
+use super::{tag_table, Array, Tag, TAG_ARRAY_ID};
+
+/// The limits for reading a single top-level [Tag].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TagReadLimits {
+    /// The most arrays and maps that can be nested inside each other.
+    pub max_depth: usize,
+    /// The most bytes that can be read, including the tag ID.
+    pub max_bytes: u64,
+    /// The most array elements and map entries in total.
+    pub max_elements: usize,
+    /// The most bytes in a single string or map key.
+    pub max_string_length: usize,
+}
+
+impl TagReadLimits {
+    pub const DEFAULT: Self = Self {
+        max_depth: 128,
+        max_bytes: 64 * 1024 * 1024,
+        max_elements: 4 * 1024 * 1024,
+        max_string_length: 1024 * 1024,
+    };
+
+    /// No limits other than those of the format. Only use this for trusted data.
+    pub const UNLIMITED: Self = Self {
+        max_depth: usize::MAX,
+        max_bytes: u64::MAX,
+        max_elements: usize::MAX,
+        max_string_length: usize::MAX,
+    };
+
+    /// The limits that [Readable] impls use on this thread.
+    #[inline]
+    pub fn current() -> Self {
+        CURRENT_LIMITS.with(Cell::get)
+    }
+
+    /// Uses these limits for every tag read with [Readable] on this thread until `f` returns.
+    pub fn scoped<T, F: FnOnce() -> T>(self, f: F) -> T {
+        struct Restore(TagReadLimits);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                CURRENT_LIMITS.with(|limits| limits.set(self.0));
+            }
+        }
+        let _restore = Restore(CURRENT_LIMITS.with(|limits| limits.replace(self)));
+        f()
+    }
+
+    fn reader<R: Read>(self, reader: &mut R) -> LimitedReader<'_, R> {
+        LimitedReader {
+            reader,
+            limits: self,
+            bytes: 0,
+            elements: 0,
+            depth: 0,
+            too_large: false,
+        }
+    }
+
+    pub(crate) fn read_array<R: Read>(self, reader: &mut R) -> Result<Array> {
+        self.reader(reader).finish(|reader| reader.nested(LimitedReader::read_array))
+    }
+
+    pub(crate) fn read_map<R: Read, M: Default + Extend<(String, Tag)>>(self, reader: &mut R) -> Result<M> {
+        self.reader(reader).finish(|reader| reader.nested(LimitedReader::read_map))
+    }
+}
+
+impl Default for TagReadLimits {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+thread_local! {
+    static CURRENT_LIMITS: Cell<TagReadLimits> = const { Cell::new(TagReadLimits::DEFAULT) };
+}
+
+/// Counts what has been read so far against the limits.
+struct LimitedReader<'a, R: Read> {
+    reader: &'a mut R,
+    limits: TagReadLimits,
+    bytes: u64,
+    elements: usize,
+    depth: usize,
+    /// Set when a read would go past `max_bytes`, so that the resulting IO error can be replaced.
+    too_large: bool,
+}
+
+impl<R: Read> Read for LimitedReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.limits.max_bytes - self.bytes;
+        if remaining == 0 && !buf.is_empty() {
+            self.too_large = true;
+            return Err(std::io::Error::other("tag byte limit reached"));
+        }
+        let length = buf.len().min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        let count = self.reader.read(&mut buf[..length])?;
+        self.bytes += count as u64;
+        Ok(count)
+    }
+}
+
+impl<R: Read> LimitedReader<'_, R> {
+    fn finish<T, F: FnOnce(&mut Self) -> Result<T>>(mut self, f: F) -> Result<T> {
+        f(&mut self).map_err(|err| if self.too_large {
+            Error::TagTooLarge(self.limits.max_bytes)
+        } else {
+            err
+        })
+    }
+
+    fn nested<T, F: FnOnce(&mut Self) -> Result<T>>(&mut self, f: F) -> Result<T> {
+        if self.depth >= self.limits.max_depth {
+            return Err(Error::TagTooDeep(self.limits.max_depth));
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn add_elements(&mut self, count: usize) -> Result<()> {
+        if count > self.limits.max_elements - self.elements {
+            return Err(Error::TooManyTagElements(self.limits.max_elements));
+        }
+        self.elements += count;
+        Ok(())
+    }
+
+    fn read_string(&mut self) -> Result<String> {
+        let length = read_u24(self)? as usize;
+        if length > self.limits.max_string_length {
+            return Err(Error::TagStringTooLong(self.limits.max_string_length));
+        }
+        Ok(String::from_utf8(read_bytes(self, length)?)?)
+    }
+
+    /// Reads a length prefixed array one element at a time.
+    fn read_elements<T, F: FnMut(&mut Self) -> Result<T>>(&mut self, mut read: F) -> Result<Vec<T>> {
+        let length = read_u24(self)? as usize;
+        self.add_elements(length)?;
+        let mut result = Vec::with_capacity(length.min(MAX_PREALLOCATE));
+        for _ in 0..length {
+            result.push(read(self)?);
+        }
+        Ok(result)
+    }
+
+    /// Reads a typed array with its own [Readable] impl, after checking its length prefix.
+    fn read_typed<T>(&mut self) -> Result<Vec<T>>
+    where Vec<T>: Readable {
+        let length = read_u24(self)?;
+        self.add_elements(length as usize)?;
+        let prefix = length.to_be_bytes();
+        Vec::<T>::read_from(&mut (&prefix[1..]).chain(&mut *self))
+    }
+
+    fn read_map<M: Default + Extend<(String, Tag)>>(&mut self) -> Result<M> {
+        let mut map = M::default();
+        loop {
+            let id = u8::read_from(self)?;
+            if id == 255 {
+                break;
+            }
+            self.add_elements(1)?;
+            let name = self.read_string()?;
+            let tag = self.read_tag(id)?;
+            map.extend(Some((name, tag)));
+        }
+        Ok(map)
+    }
+
+    fn read_tags(&mut self) -> Result<Vec<Tag>> {
+        self.read_elements(|reader| {
+            let id = u8::read_from(reader)?;
+            reader.read_tag(id)
+        })
+    }
+}
+
+// Arrays and maps are read recursively, so the functions that they recurse through only match the containers. Every
+// other value is read by a function that isn't inlined, which keeps the stack frames of the recursion small.
+macro_rules! limited_read_impls {
+    ($({$id:literal $name:ident $impl:ident $box:ident <$type:ty> $($end:tt)*})*) => {
+        $(
+            limited_read_impls!(@id $name $id);
+        )*
+
+        impl<R: Read> LimitedReader<'_, R> {
+            fn read_tag(&mut self, id: u8) -> Result<Tag> {
+                Ok(match id {
+                    STRING_ID => Tag::String(Box::new(self.read_string()?)),
+                    ARRAY_ID => Tag::Array(Box::new(self.nested(Self::read_array)?)),
+                    MAP_ID => Tag::Map(Box::new(self.nested(Self::read_map)?)),
+                    id => self.read_value(id)?,
+                })
+            }
+
+            #[inline(never)]
+            fn read_value(&mut self, id: u8) -> Result<Tag> {
+                Ok(match id {
+                    0 => Tag::Null,
+                    $(
+                        $id => limited_read_impls!(@value self $name $box $type),
+                    )*
+                    _ => return Err(Error::InvalidBinaryFormat),
+                })
+            }
+
+            fn read_array(&mut self) -> Result<Array> {
+                Ok(match u8::read_from(self)? {
+                    TAG_ARRAY_ID => Array::Tag(self.read_tags()?),
+                    STRING_ID => Array::String(self.read_elements(Self::read_string)?),
+                    ARRAY_ID => Array::Array(self.read_elements(|reader| reader.nested(Self::read_array))?),
+                    MAP_ID => Array::Map(self.read_elements(|reader| reader.nested(Self::read_map))?),
+                    id => self.read_typed_array(id)?,
+                })
+            }
+
+            #[inline(never)]
+            fn read_typed_array(&mut self, id: u8) -> Result<Array> {
+                Ok(match id {
+                    0 => Array::Empty,
+                    $(
+                        $id => limited_read_impls!(@typed_array self $name $type),
+                    )*
+                    _ => return Err(Error::InvalidBinaryFormat),
+                })
+            }
+        }
+    };
+    (@id String $id:literal) => {
+        const STRING_ID: u8 = $id;
+    };
+    (@id Array $id:literal) => {
+        const ARRAY_ID: u8 = $id;
+    };
+    (@id Map $id:literal) => {
+        const MAP_ID: u8 = $id;
+    };
+    (@id $name:ident $id:literal) => {};
+    (@value $self:ident String $box:ident $type:ty) => {
+        return Err(Error::InvalidBinaryFormat)
+    };
+    (@value $self:ident Array $box:ident $type:ty) => {
+        return Err(Error::InvalidBinaryFormat)
+    };
+    (@value $self:ident Map $box:ident $type:ty) => {
+        return Err(Error::InvalidBinaryFormat)
+    };
+    (@value $self:ident $name:ident box $type:ty) => {
+        Tag::$name(Box::new(<$type>::read_from($self)?))
+    };
+    (@value $self:ident $name:ident unbox $type:ty) => {
+        Tag::$name(<$type>::read_from($self)?)
+    };
+    (@typed_array $self:ident String $type:ty) => {
+        return Err(Error::InvalidBinaryFormat)
+    };
+    (@typed_array $self:ident Array $type:ty) => {
+        return Err(Error::InvalidBinaryFormat)
+    };
+    (@typed_array $self:ident Map $type:ty) => {
+        return Err(Error::InvalidBinaryFormat)
+    };
+    (@typed_array $self:ident $name:ident $type:ty) => {
+        Array::$name($self.read_typed::<$type>()?)
+    };
+}
+
+tag_table!(limited_read_impls);
+
+impl Tag {
+    /// Reads a tag within `limits` instead of the limits of the current thread.
+    pub fn read_with_limits<R: Read>(reader: &mut R, limits: TagReadLimits) -> Result<Tag> {
+        limits.reader(reader).finish(|reader| {
+            let id = u8::read_from(reader)?;
+            reader.read_tag(id)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::io::Writeable;
+
+    use super::*;
+
+    fn to_bytes(tag: &Tag) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        tag.write_to(&mut buffer).unwrap();
+        buffer
+    }
+
+    fn sample() -> Tag {
+        Tag::from_text(r#"{
+            name: "chest",
+            items: [{id: "apple", count: 3u8}, {id: "sword", tags: ["sharp", "heavy"]}],
+            levels: [U16; 1, 2, 3],
+            flags: [Bool; true, false],
+            nested: {a: {b: [[Tag; 1i32]]}},
+            pos: IVec3(1, 2, 3),
+        }"#).unwrap()
+    }
+
+    #[test]
+    fn read_limits_test() {
+        let tag = sample();
+        let bytes = to_bytes(&tag);
+        debug_assert_eq!(Tag::read_from(&mut Cursor::new(&bytes)).unwrap(), tag);
+
+        let limits = TagReadLimits { max_depth: 2, ..TagReadLimits::DEFAULT };
+        debug_assert!(matches!(Tag::read_with_limits(&mut Cursor::new(&bytes), limits), Err(Error::TagTooDeep(2))));
+        let limits = TagReadLimits { max_bytes: bytes.len() as u64 - 1, ..TagReadLimits::DEFAULT };
+        debug_assert!(matches!(Tag::read_with_limits(&mut Cursor::new(&bytes), limits), Err(Error::TagTooLarge(_))));
+        let limits = TagReadLimits { max_bytes: bytes.len() as u64, ..TagReadLimits::DEFAULT };
+        debug_assert_eq!(Tag::read_with_limits(&mut Cursor::new(&bytes), limits).unwrap(), tag);
+        let limits = TagReadLimits { max_elements: 8, ..TagReadLimits::DEFAULT };
+        debug_assert!(matches!(Tag::read_with_limits(&mut Cursor::new(&bytes), limits), Err(Error::TooManyTagElements(8))));
+        let limits = TagReadLimits { max_string_length: 4, ..TagReadLimits::DEFAULT };
+        debug_assert!(matches!(Tag::read_with_limits(&mut Cursor::new(&bytes), limits), Err(Error::TagStringTooLong(4))));
+
+        // Readable impls use the limits of the current thread.
+        let limits = TagReadLimits { max_depth: 1, ..TagReadLimits::DEFAULT };
+        let result = limits.scoped(|| Tag::read_from(&mut Cursor::new(&bytes)));
+        debug_assert!(matches!(result, Err(Error::TagTooDeep(1))));
+        debug_assert_eq!(TagReadLimits::current(), TagReadLimits::DEFAULT);
+    }
+
+    #[test]
+    fn hostile_input_test() {
+        // Deep nesting fails before the stack overflows.
+        let mut bytes = Vec::new();
+        for _ in 0..100_000 {
+            // Array tag, array of tags, one element.
+            bytes.extend([43, TAG_ARRAY_ID, 0, 0, 1]);
+        }
+        debug_assert!(matches!(Tag::read_from(&mut Cursor::new(&bytes)), Err(Error::TagTooDeep(_))));
+        // Map tag, then entries named "a" that are maps.
+        let mut bytes = vec![44];
+        for _ in 0..100_000 {
+            bytes.extend([44, 0, 0, 1, b'a']);
+        }
+        debug_assert!(matches!(Tag::read_from(&mut Cursor::new(&bytes)), Err(Error::TagTooDeep(_))));
+
+        // Length prefixes larger than the stream don't allocate their full length.
+        let limits = TagReadLimits::UNLIMITED;
+        for bytes in [
+            vec![42, 0xff, 0xff, 0xff, b'a'],
+            vec![43, 36, 0xff, 0xff, 0xff, 0, 0],
+            vec![43, TAG_ARRAY_ID, 0xff, 0xff, 0xff, 0],
+            vec![43, 42, 0xff, 0xff, 0xff],
+        ] {
+            debug_assert!(Tag::read_with_limits(&mut Cursor::new(&bytes), limits).is_err());
+        }
+        let limits = TagReadLimits { max_elements: 1000, ..TagReadLimits::DEFAULT };
+        let bytes = [43, 36, 0xff, 0xff, 0xff];
+        debug_assert!(matches!(Tag::read_with_limits(&mut Cursor::new(&bytes), limits), Err(Error::TooManyTagElements(1000))));
+    }
+
+    #[test]
+    fn fuzz_read_test() {
+        let mut rng = StdRng::seed_from_u64(0x7a6);
+        let valid = to_bytes(&sample());
+        let limits = TagReadLimits {
+            max_depth: 16,
+            max_bytes: 4096,
+            max_elements: 1024,
+            max_string_length: 256,
+        };
+        for _ in 0..5000 {
+            // Random streams.
+            let length = rng.gen_range(0..64);
+            let mut bytes = (0..length).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+            if let Some(first) = bytes.first_mut() {
+                // Mostly containers, which are the interesting case.
+                *first = [42, 43, 44, rng.gen_range(0..=Tag::MAX_ID)][rng.gen_range(0..4)];
+            }
+            _ = Tag::read_with_limits(&mut Cursor::new(&bytes), limits);
+
+            // Mutations of a valid tag.
+            let mut bytes = valid.clone();
+            for _ in 0..rng.gen_range(1..4) {
+                let index = rng.gen_range(0..bytes.len());
+                bytes[index] = rng.gen();
+            }
+            bytes.truncate(rng.gen_range(bytes.len() / 2..=bytes.len()));
+            let mut cursor = Cursor::new(&bytes);
+            if Tag::read_with_limits(&mut cursor, limits).is_ok() {
+                debug_assert!(cursor.position() <= limits.max_bytes);
+            }
+        }
+    }
+}
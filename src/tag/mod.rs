@@ -1,5 +1,11 @@
+pub mod convert;
+pub mod diff;
+pub mod limits;
+pub mod path;
+pub mod text;
+
 use serde::{Serialize, Deserialize};
-use crate::error::{Error, Result};
+use crate::error::Result;
 use crate::io::{
     Readable,
     Writeable,
@@ -67,6 +73,8 @@ table!(macro tag_table {
     */
 });
 
+pub(crate) use tag_table;
+
 const TAG_ARRAY_ID: u8 = 63;
 
 macro_rules! table_impls {
@@ -106,16 +114,6 @@ macro_rules! table_impls {
                 }
             }
 
-            pub(crate) fn read_with_id<R: Read>(id: u8, reader: &mut R) -> Result<Self> {
-                Ok(match id {
-                    0 => Tag::Null,
-                    $(
-                        $id => Tag::$name(<table_impls!(@box_unbox: $box $type)>::read_from(reader)?),
-                    )*
-                    _ => return Err(Error::InvalidBinaryFormat),
-                })
-            }
-
             pub(crate) fn write_without_id<W: Write>(&self, writer: &mut W) -> Result<u64> {
                 Ok(match self {
                     Tag::Null => 0,
@@ -128,8 +126,7 @@ macro_rules! table_impls {
 
         impl Readable for Tag {
             fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-                let id: u8 = u8::read_from(reader)?;
-                Tag::read_with_id(id, reader)
+                Tag::read_with_limits(reader, limits::TagReadLimits::current())
             }
         }
 
@@ -201,15 +198,7 @@ macro_rules! table_impls {
 
         impl Readable for Array {
             fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
-                let id: u8 = u8::read_from(reader)?;
-                Ok(match id {
-                    0 => Array::Empty,
-                    $(
-                        $id => Array::$name(Vec::<$type>::read_from(reader)?),
-                    )*
-                    TAG_ARRAY_ID => Array::Tag(Vec::<Tag>::read_from(reader)?),
-                    _ => return Err(Error::InvalidBinaryFormat),
-                })
+                limits::TagReadLimits::current().read_array(reader)
             }
         }
 
@@ -0,0 +1,602 @@
+//! Paths into nested [Tag]s, such as `inventory[3].enchantments.sharpness`.
+//!
+//! A path is a list of segments separated by `.`:
+//! - `name` gets a key of a [Tag::Map]. Keys with other characters can be quoted: `"my key"`.
+//!   Inside quotes, `\"` and `\\` are the only escapes, and every other character is taken as is.
+//! - `[3]` gets an element of a [Tag::Array] of tags ([Array::Tag]).
+//! - `*` and `[*]` match every value of a map or every element of an array, and can only be used with [Tag::query].
+//!
+//! The empty path `""` is the tag itself.
+
+use std::borrow::Cow;
+use std::fmt::Write;
+
+use thiserror::Error;
+
+use crate::io::{read_vec, write_slice, Readable, Writeable};
+
+use super::{Array, Tag};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TagPathError {
+    #[error("Invalid tag path at character {0}: {1}.")]
+    Syntax(usize, &'static str),
+    #[error("Failed at \"{0}\": the value before it is not a map.")]
+    NotAMap(String),
+    #[error("Failed at \"{0}\": the value before it is not an array.")]
+    NotAnArray(String),
+    #[error("Failed at \"{0}\": the value before it is a typed array, only arrays of tags can be indexed.")]
+    TypedArray(String),
+    #[error("Failed at \"{0}\": the key was not found.")]
+    KeyNotFound(String),
+    #[error("Failed at \"{0}\": the index is out of bounds for an array of length {1}.")]
+    IndexOutOfBounds(String, usize),
+    #[error("Failed at \"{0}\": wildcards can only be used with Tag::query.")]
+    Wildcard(String),
+    #[error("The empty path can't be removed.")]
+    EmptyPath,
+}
+
+pub type Result<T> = std::result::Result<T, TagPathError>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// `name` or `"name"`
+    Key(String),
+    /// `[index]`
+    Index(usize),
+    /// `*`
+    AnyKey,
+    /// `[*]`
+    AnyIndex,
+}
+
+impl PathSegment {
+    #[inline]
+    pub fn is_wildcard(&self) -> bool {
+        matches!(self, PathSegment::AnyKey | PathSegment::AnyIndex)
+    }
+}
+
+/// A parsed path into a [Tag]. Paths that are used often can be parsed once and reused.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct TagPath {
+    segments: Vec<PathSegment>,
+}
+
+impl TagPath {
+    /// The empty path, which is the tag itself.
+    pub const ROOT: TagPath = TagPath { segments: Vec::new() };
+
+    pub fn new<It: IntoIterator<Item = PathSegment>>(segments: It) -> Self {
+        Self {
+            segments: segments.into_iter().collect(),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        Parser { text, pos: 0 }.parse()
+    }
+
+    #[inline]
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    #[inline]
+    pub fn has_wildcard(&self) -> bool {
+        self.segments.iter().any(PathSegment::is_wildcard)
+    }
+
+    /// Returns a new path with `segment` added to the end.
+    pub fn join(&self, segment: PathSegment) -> TagPath {
+        let mut segments = self.segments.clone();
+        segments.push(segment);
+        TagPath { segments }
+    }
+
+    pub fn push(&mut self, segment: PathSegment) {
+        self.segments.push(segment);
+    }
+
+    /// The text of the first `count` segments, used to say which segment failed.
+    fn prefix(&self, count: usize) -> String {
+        TagPath::new(self.segments[..count].iter().cloned()).to_string()
+    }
+
+    fn check_wildcards(&self) -> Result<()> {
+        match self.segments.iter().position(PathSegment::is_wildcard) {
+            Some(index) => Err(TagPathError::Wildcard(self.prefix(index + 1))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn is_bare_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | ':' | '/')
+}
+
+impl std::fmt::Display for TagPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) => {
+                    if i > 0 {
+                        f.write_str(".")?;
+                    }
+                    if !key.is_empty() && key.chars().all(is_bare_key_char) {
+                        f.write_str(key)?;
+                    } else {
+                        // Only the escapes that the parser knows, every other character is written as is.
+                        f.write_char('"')?;
+                        for c in key.chars() {
+                            if matches!(c, '"' | '\\') {
+                                f.write_char('\\')?;
+                            }
+                            f.write_char(c)?;
+                        }
+                        f.write_char('"')?;
+                    }
+                }
+                PathSegment::AnyKey => {
+                    if i > 0 {
+                        f.write_str(".")?;
+                    }
+                    f.write_str("*")?;
+                }
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+                PathSegment::AnyIndex => f.write_str("[*]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for TagPath {
+    type Err = TagPathError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl Readable for PathSegment {
+    fn read_from<R: std::io::Read>(reader: &mut R) -> crate::prelude::VoxelResult<Self> {
+        Ok(match u8::read_from(reader)? {
+            0 => PathSegment::Key(String::read_from(reader)?),
+            1 => PathSegment::Index(u64::read_from(reader)? as usize),
+            2 => PathSegment::AnyKey,
+            3 => PathSegment::AnyIndex,
+            _ => return Err(crate::error::Error::InvalidBinaryFormat),
+        })
+    }
+}
+
+impl Writeable for PathSegment {
+    fn write_to<W: std::io::Write>(&self, writer: &mut W) -> crate::prelude::VoxelResult<u64> {
+        Ok(match self {
+            PathSegment::Key(key) => 0u8.write_to(writer)? + key.write_to(writer)?,
+            PathSegment::Index(index) => 1u8.write_to(writer)? + (*index as u64).write_to(writer)?,
+            PathSegment::AnyKey => 2u8.write_to(writer)?,
+            PathSegment::AnyIndex => 3u8.write_to(writer)?,
+        })
+    }
+}
+
+impl Readable for TagPath {
+    fn read_from<R: std::io::Read>(reader: &mut R) -> crate::prelude::VoxelResult<Self> {
+        Ok(TagPath { segments: read_vec(reader)? })
+    }
+}
+
+impl Writeable for TagPath {
+    fn write_to<W: std::io::Write>(&self, writer: &mut W) -> crate::prelude::VoxelResult<u64> {
+        write_slice(writer, &self.segments)
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn parse(mut self) -> Result<TagPath> {
+        let mut segments = Vec::new();
+        if self.text.is_empty() {
+            return Ok(TagPath { segments });
+        }
+        if self.peek() != Some('[') {
+            segments.push(self.key()?);
+        }
+        while let Some(c) = self.next() {
+            match c {
+                '.' => segments.push(self.key()?),
+                '[' => segments.push(self.index()?),
+                _ => return Err(TagPathError::Syntax(self.pos - 1, "expected '.' or '['")),
+            }
+        }
+        Ok(TagPath { segments })
+    }
+
+    fn key(&mut self) -> Result<PathSegment> {
+        match self.peek() {
+            Some('*') => {
+                self.next();
+                Ok(PathSegment::AnyKey)
+            }
+            Some('"') => {
+                self.next();
+                let mut key = String::new();
+                loop {
+                    match self.next() {
+                        Some('"') => return Ok(PathSegment::Key(key)),
+                        Some('\\') => match self.next() {
+                            Some(c @ ('"' | '\\')) => key.push(c),
+                            _ => return Err(TagPathError::Syntax(self.pos.saturating_sub(1), "only '\\\"' and '\\\\' can be escaped")),
+                        },
+                        Some(c) => key.push(c),
+                        None => return Err(TagPathError::Syntax(self.pos, "unclosed quote")),
+                    }
+                }
+            }
+            _ => {
+                let start = self.pos;
+                while self.peek().is_some_and(is_bare_key_char) {
+                    self.next();
+                }
+                if start == self.pos {
+                    return Err(TagPathError::Syntax(start, "expected a key"));
+                }
+                Ok(PathSegment::Key(self.text[start..self.pos].to_owned()))
+            }
+        }
+    }
+
+    fn index(&mut self) -> Result<PathSegment> {
+        let start = self.pos;
+        let segment = if self.peek() == Some('*') {
+            self.next();
+            PathSegment::AnyIndex
+        } else {
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.next();
+            }
+            let index = self.text[start..self.pos].parse().map_err(|_| TagPathError::Syntax(start, "expected an index"))?;
+            PathSegment::Index(index)
+        };
+        if self.peek() != Some(']') {
+            return Err(TagPathError::Syntax(self.pos, "expected ']'"));
+        }
+        self.next();
+        Ok(segment)
+    }
+}
+
+/// Types that can be used as a [TagPath]: the path text, or an already parsed [TagPath].
+pub trait AsTagPath {
+    fn as_tag_path(&self) -> Result<Cow<'_, TagPath>>;
+}
+
+impl AsTagPath for TagPath {
+    #[inline]
+    fn as_tag_path(&self) -> Result<Cow<'_, TagPath>> {
+        Ok(Cow::Borrowed(self))
+    }
+}
+
+impl AsTagPath for str {
+    #[inline]
+    fn as_tag_path(&self) -> Result<Cow<'_, TagPath>> {
+        TagPath::parse(self).map(Cow::Owned)
+    }
+}
+
+impl AsTagPath for String {
+    #[inline]
+    fn as_tag_path(&self) -> Result<Cow<'_, TagPath>> {
+        self.as_str().as_tag_path()
+    }
+}
+
+fn tag_array<'a>(tag: &'a Tag, path: &TagPath, depth: usize) -> Result<&'a [Tag]> {
+    match tag {
+        Tag::Array(array) => match array.as_ref() {
+            Array::Tag(tags) => Ok(tags),
+            Array::Empty => Ok(&[]),
+            _ => Err(TagPathError::TypedArray(path.prefix(depth + 1))),
+        },
+        _ => Err(TagPathError::NotAnArray(path.prefix(depth + 1))),
+    }
+}
+
+fn tag_array_mut<'a>(tag: &'a mut Tag, path: &TagPath, depth: usize) -> Result<&'a mut Vec<Tag>> {
+    match tag {
+        Tag::Array(array) => match array.as_mut() {
+            Array::Tag(tags) => Ok(tags),
+            // Every index is out of bounds.
+            Array::Empty => Err(TagPathError::IndexOutOfBounds(path.prefix(depth + 1), 0)),
+            _ => Err(TagPathError::TypedArray(path.prefix(depth + 1))),
+        },
+        _ => Err(TagPathError::NotAnArray(path.prefix(depth + 1))),
+    }
+}
+
+/// Follows one segment (that isn't a wildcard) of `path`.
+fn step<'a>(tag: &'a Tag, path: &TagPath, depth: usize) -> Result<&'a Tag> {
+    match &path.segments[depth] {
+        PathSegment::Key(key) => {
+            let Tag::Map(map) = tag else {
+                return Err(TagPathError::NotAMap(path.prefix(depth + 1)));
+            };
+            map.get(key).ok_or_else(|| TagPathError::KeyNotFound(path.prefix(depth + 1)))
+        }
+        &PathSegment::Index(index) => {
+            let tags = tag_array(tag, path, depth)?;
+            tags.get(index).ok_or_else(|| TagPathError::IndexOutOfBounds(path.prefix(depth + 1), tags.len()))
+        }
+        PathSegment::AnyKey | PathSegment::AnyIndex => Err(TagPathError::Wildcard(path.prefix(depth + 1))),
+    }
+}
+
+fn step_mut<'a>(tag: &'a mut Tag, path: &TagPath, depth: usize) -> Result<&'a mut Tag> {
+    match &path.segments[depth] {
+        PathSegment::Key(key) => {
+            let Tag::Map(map) = tag else {
+                return Err(TagPathError::NotAMap(path.prefix(depth + 1)));
+            };
+            map.get_mut(key).ok_or_else(|| TagPathError::KeyNotFound(path.prefix(depth + 1)))
+        }
+        &PathSegment::Index(index) => {
+            let tags = tag_array_mut(tag, path, depth)?;
+            let len = tags.len();
+            tags.get_mut(index).ok_or_else(|| TagPathError::IndexOutOfBounds(path.prefix(depth + 1), len))
+        }
+        PathSegment::AnyKey | PathSegment::AnyIndex => Err(TagPathError::Wildcard(path.prefix(depth + 1))),
+    }
+}
+
+fn query_into<'a>(tag: &'a Tag, segments: &[PathSegment], results: &mut Vec<&'a Tag>) {
+    let Some((segment, rest)) = segments.split_first() else {
+        results.push(tag);
+        return;
+    };
+    match (segment, tag) {
+        (PathSegment::Key(key), Tag::Map(map)) => {
+            if let Some(child) = map.get(key) {
+                query_into(child, rest, results);
+            }
+        }
+        (PathSegment::AnyKey, Tag::Map(map)) => {
+            for child in map.values() {
+                query_into(child, rest, results);
+            }
+        }
+        (&PathSegment::Index(index), Tag::Array(array)) => {
+            if let Array::Tag(tags) = array.as_ref() {
+                if let Some(child) = tags.get(index) {
+                    query_into(child, rest, results);
+                }
+            }
+        }
+        (PathSegment::AnyIndex, Tag::Array(array)) => {
+            if let Array::Tag(tags) = array.as_ref() {
+                for child in tags {
+                    query_into(child, rest, results);
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+impl Tag {
+    /// Gets the tag at `path`, such as `inventory[3].enchantments.sharpness`.
+    pub fn get<P: AsTagPath + ?Sized>(&self, path: &P) -> Result<&Tag> {
+        let path = path.as_tag_path()?;
+        let mut tag = self;
+        for depth in 0..path.len() {
+            tag = step(tag, &path, depth)?;
+        }
+        Ok(tag)
+    }
+
+    pub fn get_mut<P: AsTagPath + ?Sized>(&mut self, path: &P) -> Result<&mut Tag> {
+        let path = path.as_tag_path()?;
+        let mut tag = self;
+        for depth in 0..path.len() {
+            tag = step_mut(tag, &path, depth)?;
+        }
+        Ok(tag)
+    }
+
+    /// Sets the tag at `path` to `value` and returns the previous value, or [Tag::Null] if there wasn't one.
+    ///
+    /// Missing keys (and [Tag::Null] values) before the last segment are created as empty maps
+    /// when the segment after them is a key. Array indices must already exist.
+    /// The tag is left unchanged if this fails.
+    pub fn set<P: AsTagPath + ?Sized, T: Into<Tag>>(&mut self, path: &P, value: T) -> Result<Tag> {
+        let path = path.as_tag_path()?;
+        path.check_wildcards()?;
+        // Check the whole path before creating anything, so that a failure doesn't leave empty maps behind.
+        // `existing` is `None` once the path reaches a key that will be created.
+        let mut existing = Some(&*self);
+        for (depth, segment) in path.segments.iter().enumerate() {
+            existing = match (segment, existing) {
+                (PathSegment::Key(key), Some(Tag::Map(map))) => map.get(key).filter(|tag| !tag.is_null()),
+                (PathSegment::Key(_), Some(Tag::Null) | None) => None,
+                (PathSegment::Key(_), Some(_)) => return Err(TagPathError::NotAMap(path.prefix(depth + 1))),
+                (_, Some(tag)) => Some(step(tag, &path, depth)?),
+                // Only maps are created, so the key before the index has to exist.
+                (_, None) => return Err(TagPathError::KeyNotFound(path.prefix(depth))),
+            };
+        }
+        let mut tag = self;
+        for (depth, segment) in path.segments.iter().enumerate() {
+            if let PathSegment::Key(key) = segment {
+                if tag.is_null() {
+                    *tag = Tag::Map(Box::default());
+                }
+                let Tag::Map(map) = tag else {
+                    return Err(TagPathError::NotAMap(path.prefix(depth + 1)));
+                };
+                tag = map.entry(key.clone()).or_insert(Tag::Null);
+            } else {
+                tag = step_mut(tag, &path, depth)?;
+            }
+        }
+        Ok(std::mem::replace(tag, value.into()))
+    }
+
+    /// Removes the tag at `path` from its map or array and returns it.
+    pub fn remove<P: AsTagPath + ?Sized>(&mut self, path: &P) -> Result<Tag> {
+        let path = path.as_tag_path()?;
+        path.check_wildcards()?;
+        let Some((last, parent_segments)) = path.segments.split_last() else {
+            return Err(TagPathError::EmptyPath);
+        };
+        let depth = parent_segments.len();
+        let mut parent = &mut *self;
+        for depth in 0..depth {
+            parent = step_mut(parent, &path, depth)?;
+        }
+        match last {
+            PathSegment::Key(key) => {
+                let Tag::Map(map) = parent else {
+                    return Err(TagPathError::NotAMap(path.prefix(depth + 1)));
+                };
+                map.remove(key).ok_or_else(|| TagPathError::KeyNotFound(path.prefix(depth + 1)))
+            }
+            &PathSegment::Index(index) => {
+                let tags = tag_array_mut(parent, &path, depth)?;
+                if index >= tags.len() {
+                    return Err(TagPathError::IndexOutOfBounds(path.prefix(depth + 1), tags.len()));
+                }
+                Ok(tags.remove(index))
+            }
+            // Wildcards were checked above.
+            PathSegment::AnyKey | PathSegment::AnyIndex => unreachable!(),
+        }
+    }
+
+    /// Gets every tag that matches `path`, which may contain wildcards such as `items[*].id`.
+    /// Paths that don't exist are skipped. Map values are visited in no particular order.
+    pub fn query<P: AsTagPath + ?Sized>(&self, path: &P) -> Result<Vec<&Tag>> {
+        let path = path.as_tag_path()?;
+        let mut results = Vec::new();
+        query_into(self, &path.segments, &mut results);
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player() -> Tag {
+        let mut sword = Tag::Map(Box::default());
+        sword["id"] = "sword".into();
+        sword["enchantments"] = Tag::Map(Box::default());
+        sword["enchantments"]["sharpness"] = 3i32.into();
+        let mut apple = Tag::Map(Box::default());
+        apple["id"] = "apple".into();
+        let mut player = Tag::Map(Box::default());
+        player["inventory"] = vec![apple, sword].into();
+        player["scores"] = vec![1i32, 2, 3].into();
+        player
+    }
+
+    #[test]
+    fn path_parse_test() {
+        let path = TagPath::parse("inventory[3].enchantments.\"odd key\"[*].*").unwrap();
+        debug_assert_eq!(path.segments(), &[
+            PathSegment::Key("inventory".into()),
+            PathSegment::Index(3),
+            PathSegment::Key("enchantments".into()),
+            PathSegment::Key("odd key".into()),
+            PathSegment::AnyIndex,
+            PathSegment::AnyKey,
+        ]);
+        debug_assert_eq!(path.to_string(), "inventory[3].enchantments.\"odd key\"[*].*");
+        debug_assert_eq!(TagPath::parse("[0][1]").unwrap().segments(), &[PathSegment::Index(0), PathSegment::Index(1)]);
+        debug_assert!(TagPath::parse("").unwrap().is_empty());
+        debug_assert_eq!(TagPath::parse("a..b"), Err(TagPathError::Syntax(2, "expected a key")));
+        debug_assert_eq!(TagPath::parse("a[x]"), Err(TagPathError::Syntax(2, "expected an index")));
+        debug_assert_eq!(TagPath::parse("a[1"), Err(TagPathError::Syntax(3, "expected ']'")));
+        debug_assert!(TagPath::parse("a.\"b").is_err());
+        debug_assert!(TagPath::parse("a b").is_err());
+        // Keys with quotes, backslashes and control characters are written so that they parse back.
+        for key in ["say \"hi\"", "back\\slash", "line\nbreak\ttab", "\u{7}bell", ""] {
+            let path = TagPath::new([PathSegment::Key(key.into()), PathSegment::Index(1)]);
+            debug_assert_eq!(TagPath::parse(&path.to_string()).unwrap(), path);
+        }
+        debug_assert_eq!(TagPath::new([PathSegment::Key("a\"b\n".into())]).to_string(), "\"a\\\"b\n\"");
+    }
+
+    #[test]
+    fn path_get_set_test() {
+        let mut tag = player();
+        debug_assert_eq!(tag.get("inventory[1].enchantments.sharpness"), Ok(&Tag::I32(3)));
+        debug_assert_eq!(tag.get(""), Ok(&tag));
+        debug_assert_eq!(tag.get("inventory[5].id"), Err(TagPathError::IndexOutOfBounds("inventory[5]".into(), 2)));
+        debug_assert_eq!(tag.get("inventory[0].name"), Err(TagPathError::KeyNotFound("inventory[0].name".into())));
+        debug_assert_eq!(tag.get("inventory.id"), Err(TagPathError::NotAMap("inventory.id".into())));
+        debug_assert_eq!(tag.get("scores[0]"), Err(TagPathError::TypedArray("scores[0]".into())));
+        debug_assert_eq!(tag.get("inventory[*].id"), Err(TagPathError::Wildcard("inventory[*]".into())));
+
+        *tag.get_mut("inventory[1].enchantments.sharpness").unwrap() = 5i32.into();
+        debug_assert_eq!(tag.set("inventory[1].enchantments.sharpness", 4i32), Ok(Tag::I32(5)));
+        // Intermediate maps are created.
+        debug_assert_eq!(tag.set("stats.health.max", 20i32), Ok(Tag::Null));
+        debug_assert_eq!(tag["stats"]["health"]["max"], Tag::I32(20));
+        // Arrays aren't, and nothing is left behind when it fails.
+        debug_assert_eq!(tag.set("pets[0].name", "rex"), Err(TagPathError::KeyNotFound("pets".into())));
+        debug_assert!(tag.get("pets").is_err());
+        let before = tag.clone();
+        debug_assert_eq!(tag.set("extra.pets[0].name", "rex"), Err(TagPathError::KeyNotFound("extra.pets".into())));
+        debug_assert_eq!(tag.set("stats.health.max.value", 1i32), Err(TagPathError::NotAMap("stats.health.max.value".into())));
+        debug_assert_eq!(tag.set("stats.pets[0]", "rex"), Err(TagPathError::KeyNotFound("stats.pets".into())));
+        debug_assert_eq!(tag.set("inventory[0].extra.tags[1]", 1i32), Err(TagPathError::KeyNotFound("inventory[0].extra.tags".into())));
+        debug_assert_eq!(tag, before);
+        let mut empty = Tag::Map(Box::default());
+        debug_assert_eq!(empty.set("stats.pets[0].name", "rex"), Err(TagPathError::KeyNotFound("stats.pets".into())));
+        debug_assert_eq!(empty, Tag::Map(Box::default()));
+
+        debug_assert_eq!(tag.remove("inventory[0].id"), Ok(Tag::from("apple")));
+        let removed = tag.remove("inventory[0]").unwrap();
+        debug_assert!(removed["id"].is_null());
+        debug_assert_eq!(tag.get("inventory[0].id"), Ok(&Tag::from("sword")));
+        debug_assert_eq!(tag.remove(""), Err(TagPathError::EmptyPath));
+        debug_assert_eq!(tag.remove("stats.mana"), Err(TagPathError::KeyNotFound("stats.mana".into())));
+    }
+
+    #[test]
+    fn path_query_test() {
+        let tag = player();
+        let path = TagPath::parse("inventory[*].id").unwrap();
+        let ids = tag.query(&path).unwrap();
+        debug_assert_eq!(ids, vec![&Tag::from("apple"), &Tag::from("sword")]);
+        debug_assert_eq!(tag.query("inventory[*].enchantments.*").unwrap(), vec![&Tag::I32(3)]);
+        debug_assert!(tag.query("missing[*]").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,980 @@
+//! A human-readable text format for [Tag]s, similar to SNBT.
+//!
+//! Unlike JSON, every variant keeps its type so the text can be read back into the exact same tag:
+//! ```text
+//! {
+//!     name: "Lamp",
+//!     lit: true,
+//!     power: 15u8,
+//!     brightness: 0.75f32,
+//!     facing: Direction(PosX),
+//!     color: Rgb(255, 200, 120),
+//!     offset: Vec3(0.0, 0.5, 0.0),
+//!     levels: [U8; 1, 2, 3],
+//!     parts: [{id: "base"}, {id: "bulb"}],
+//! }
+//! ```
+//! - Numbers have a suffix with their type (`5i32`, `1.5f64`). Numbers without a suffix are `i32`, or `f64` if they have a `.` or an exponent.
+//! - Other values are written as `Type(...)`, using the name of their [Tag] variant.
+//! - Typed arrays start with their element type: `[Vec3; (0.0, 1.0, 0.0), (1.0, 0.0, 0.0)]`. `[a, b]` is an array of tags,
+//!   `[Tag;]` is an empty array of tags and `[]` is [Array::Empty].
+//! - Map keys can be quoted (`"odd key"`) when they contain characters other than `a-z`, `A-Z`, `0-9`, `_` and `-`.
+//! - `//` starts a comment, and lists and maps may have a trailing comma.
+
+use glam::{IVec2, IVec3, IVec4, Mat2, Mat3, Mat4, Quat, Vec2, Vec3, Vec4};
+use hashbrown::HashMap;
+use rollgrid::{bounds2d::Bounds2D, bounds3d::Bounds3D};
+use thiserror::Error;
+
+use crate::math::axis::Axis;
+use crate::math::axis_flags::AxisFlags;
+use crate::math::bit::{BitFlags8, BitFlags16, BitFlags32, BitFlags64, BitFlags128};
+use crate::rendering::color::{Color, Rgb, Rgba};
+use crate::voxel::cardinal::Cardinal;
+use crate::voxel::direction::Direction;
+use crate::voxel::face_flags::FaceFlags;
+use crate::voxel::orientation::{Flip, Orientation, Rotation};
+
+use super::{tag_table, Array, Tag};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid tag text at line {line}, column {column}: {reason}.")]
+pub struct TagTextError {
+    pub line: usize,
+    pub column: usize,
+    pub reason: String,
+}
+
+pub type Result<T> = std::result::Result<T, TagTextError>;
+
+/// Writes text with or without line breaks and indentation.
+pub(crate) struct TextWriter {
+    out: String,
+    pretty: bool,
+    depth: usize,
+}
+
+impl TextWriter {
+    fn new(pretty: bool) -> Self {
+        Self {
+            out: String::new(),
+            pretty,
+            depth: 0,
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+
+    /// Starts a new line when pretty printing, otherwise writes `compact`.
+    fn line_break(&mut self, compact: &str) {
+        if self.pretty {
+            self.out.push('\n');
+            for _ in 0..self.depth {
+                self.out.push_str("    ");
+            }
+        } else {
+            self.out.push_str(compact);
+        }
+    }
+
+    fn string(&mut self, text: &str) {
+        self.out.push('"');
+        for c in text.chars() {
+            match c {
+                '"' => self.out.push_str("\\\""),
+                '\\' => self.out.push_str("\\\\"),
+                '\n' => self.out.push_str("\\n"),
+                '\r' => self.out.push_str("\\r"),
+                '\t' => self.out.push_str("\\t"),
+                c if c.is_control() => self.out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+                c => self.out.push(c),
+            }
+        }
+        self.out.push('"');
+    }
+
+    fn key(&mut self, key: &str) {
+        if !key.is_empty() && key.chars().all(is_key_char) {
+            self.push(key);
+        } else {
+            self.string(key);
+        }
+    }
+
+    /// Writes `(a, b, c)`.
+    fn args<T: TagText>(&mut self, args: &[T]) {
+        self.push("(");
+        for (i, arg) in args.iter().enumerate() {
+            if i > 0 {
+                self.push(", ");
+            }
+            arg.write_text(self);
+        }
+        self.push(")");
+    }
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-')
+}
+
+pub(crate) struct TextParser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> TextParser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn error_at<S: Into<String>>(&self, pos: usize, reason: S) -> TagTextError {
+        let before = &self.text[..pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().map_or(0, |line| line.chars().count()) + 1;
+        TagTextError { line, column, reason: reason.into() }
+    }
+
+    fn error<S: Into<String>>(&self, reason: S) -> TagTextError {
+        self.error_at(self.pos, reason)
+    }
+
+    #[inline]
+    fn peek_char(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek_char()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    /// Skips whitespace and comments.
+    fn skip_whitespace(&mut self) {
+        loop {
+            let rest = &self.text[self.pos..];
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Skips whitespace and peeks the next character.
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.peek_char()
+    }
+
+    /// Skips `c` if it's next.
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{c}'")))
+        }
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> &'a str {
+        let start = self.pos;
+        while self.peek_char().is_some_and(&predicate) {
+            self.bump();
+        }
+        &self.text[start..self.pos]
+    }
+
+    fn ident(&mut self) -> Result<&'a str> {
+        self.skip_whitespace();
+        let ident = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        if ident.is_empty() {
+            return Err(self.error("expected a name"));
+        }
+        Ok(ident)
+    }
+
+    /// A number such as `-12`, `1.5e-3` or `inf`, including any type suffix.
+    fn number_token(&mut self) -> Result<(usize, &'a str)> {
+        self.skip_whitespace();
+        let start = self.pos;
+        if matches!(self.peek_char(), Some('+' | '-')) {
+            self.bump();
+        }
+        loop {
+            match self.peek_char() {
+                Some(c) if c.is_ascii_alphanumeric() || matches!(c, '.' | '_') => {
+                    self.bump();
+                }
+                // The sign of an exponent.
+                Some('+' | '-') if self.text[..self.pos].ends_with(['e', 'E']) => {
+                    self.bump();
+                }
+                _ => break,
+            }
+        }
+        if self.pos == start {
+            return Err(self.error("expected a number"));
+        }
+        Ok((start, &self.text[start..self.pos]))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.expect('"')?;
+        let mut text = String::new();
+        loop {
+            let start = self.pos;
+            match self.bump() {
+                Some('"') => return Ok(text),
+                Some('\\') => match self.bump() {
+                    Some('"') => text.push('"'),
+                    Some('\\') => text.push('\\'),
+                    Some('n') => text.push('\n'),
+                    Some('r') => text.push('\r'),
+                    Some('t') => text.push('\t'),
+                    Some('u') => {
+                        self.expect('{')?;
+                        let hex = self.take_while(|c| c.is_ascii_hexdigit());
+                        let c = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+                            .ok_or_else(|| self.error_at(start, "invalid unicode escape"))?;
+                        self.expect('}')?;
+                        text.push(c);
+                    }
+                    _ => return Err(self.error_at(start, "invalid escape")),
+                },
+                Some(c) => text.push(c),
+                None => return Err(self.error("unclosed string")),
+            }
+        }
+    }
+
+    fn key(&mut self) -> Result<String> {
+        if self.peek() == Some('"') {
+            return self.string();
+        }
+        let key = self.take_while(is_key_char);
+        if key.is_empty() {
+            return Err(self.error("expected a key"));
+        }
+        Ok(key.to_owned())
+    }
+
+    /// Reads `(a, b, c)`.
+    fn args<T: TagText + Copy + Default, const N: usize>(&mut self) -> Result<[T; N]> {
+        self.expect('(')?;
+        let mut args = [T::default(); N];
+        for (i, arg) in args.iter_mut().enumerate() {
+            if i > 0 {
+                self.expect(',')?;
+            }
+            *arg = T::read_text(self)?;
+        }
+        self.expect(')')?;
+        Ok(args)
+    }
+
+    /// Reads the elements of a list until `close`, allowing a trailing comma.
+    fn list<T, F: FnMut(&mut Self) -> Result<T>>(&mut self, close: char, mut read: F) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        while !self.eat(close) {
+            items.push(read(self)?);
+            if !self.eat(',') {
+                self.expect(close)?;
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    fn named<T: Copy + std::fmt::Debug, It: IntoIterator<Item = T>>(&mut self, kind: &str, values: It) -> Result<T> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let name = self.ident()?;
+        values.into_iter().find(|value| format!("{value:?}") == name)
+            .ok_or_else(|| self.error_at(start, format!("unknown {kind} \"{name}\"")))
+    }
+}
+
+/// A type that can be a value in tag text.
+pub(crate) trait TagText: Sized {
+    /// Whether the value is written as `Name(...)` with its [Tag] variant name.
+    const NAMED: bool = true;
+
+    /// Writes the value without the variant name, which is how it's written in typed arrays.
+    fn write_text(&self, writer: &mut TextWriter);
+
+    fn read_text(parser: &mut TextParser) -> Result<Self>;
+
+    /// Writes the value as a [Tag] of the variant `name`.
+    fn write_tag(&self, name: &str, writer: &mut TextWriter) {
+        if Self::NAMED {
+            writer.push(name);
+        }
+        self.write_text(writer);
+    }
+}
+
+macro_rules! number_text {
+    ($($type:ty),*) => {
+        $(
+            impl TagText for $type {
+                const NAMED: bool = false;
+
+                fn write_text(&self, writer: &mut TextWriter) {
+                    // Debug prints floats with a decimal point and with enough digits to read back the same value.
+                    writer.push(&format!("{self:?}"));
+                }
+
+                fn read_text(parser: &mut TextParser) -> Result<Self> {
+                    let (start, token) = parser.number_token()?;
+                    token.parse().map_err(|_| parser.error_at(start, format!("invalid {} \"{token}\"", stringify!($type))))
+                }
+
+                fn write_tag(&self, name: &str, writer: &mut TextWriter) {
+                    self.write_text(writer);
+                    writer.push(&name.to_ascii_lowercase());
+                }
+            }
+        )*
+    };
+}
+
+number_text!(u8, i8, u16, i16, u32, i32, u64, i64, f32, f64);
+
+macro_rules! bitflags_text {
+    ($($type:ident($inner:ty)),*) => {
+        $(
+            impl TagText for $type {
+                fn write_text(&self, writer: &mut TextWriter) {
+                    writer.push(&format!("({:#0width$b})", self.0, width = <$inner>::BITS as usize + 2));
+                }
+
+                fn read_text(parser: &mut TextParser) -> Result<Self> {
+                    parser.expect('(')?;
+                    let (start, token) = parser.number_token()?;
+                    let bits = if let Some(binary) = token.strip_prefix("0b") {
+                        <$inner>::from_str_radix(binary, 2)
+                    } else if let Some(hex) = token.strip_prefix("0x") {
+                        <$inner>::from_str_radix(hex, 16)
+                    } else {
+                        token.parse()
+                    }.map_err(|_| parser.error_at(start, format!("invalid bits \"{token}\"")))?;
+                    parser.expect(')')?;
+                    Ok($type(bits))
+                }
+            }
+        )*
+    };
+}
+
+bitflags_text!(BitFlags8(u8), BitFlags16(u16), BitFlags32(u32), BitFlags64(u64), BitFlags128(u128));
+
+impl TagText for bool {
+    const NAMED: bool = false;
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.push(if *self { "true" } else { "false" });
+    }
+
+    fn read_text(parser: &mut TextParser) -> Result<Self> {
+        parser.named("bool", [true, false])
+    }
+}
+
+macro_rules! enum_text {
+    ($($type:ty => $kind:literal $values:expr;)*) => {
+        $(
+            impl TagText for $type {
+                fn write_text(&self, writer: &mut TextWriter) {
+                    writer.push(&format!("({self:?})"));
+                }
+
+                fn read_text(parser: &mut TextParser) -> Result<Self> {
+                    parser.expect('(')?;
+                    let value = parser.named($kind, $values)?;
+                    parser.expect(')')?;
+                    Ok(value)
+                }
+            }
+        )*
+    };
+}
+
+enum_text!(
+    Direction => "direction" Direction::ALL;
+    Cardinal => "cardinal" Cardinal::ALL;
+    Axis => "axis" [Axis::X, Axis::Y, Axis::Z];
+);
+
+/// Writes the axes that are set, such as `XZ`, or `None`.
+fn axes_text(writer: &mut TextWriter, [x, y, z]: [bool; 3]) {
+    if !(x || y || z) {
+        writer.push("None");
+        return;
+    }
+    for (set, name) in [(x, "X"), (y, "Y"), (z, "Z")] {
+        if set {
+            writer.push(name);
+        }
+    }
+}
+
+fn read_axes(parser: &mut TextParser) -> Result<[bool; 3]> {
+    parser.skip_whitespace();
+    let start = parser.pos;
+    let name = parser.ident()?;
+    if name == "None" {
+        return Ok([false; 3]);
+    }
+    let mut axes = [false; 3];
+    for c in name.chars() {
+        let index = match c {
+            'X' => 0,
+            'Y' => 1,
+            'Z' => 2,
+            _ => return Err(parser.error_at(start, format!("invalid axes \"{name}\""))),
+        };
+        axes[index] = true;
+    }
+    Ok(axes)
+}
+
+impl TagText for Flip {
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.push("(");
+        axes_text(writer, [self.x(), self.y(), self.z()]);
+        writer.push(")");
+    }
+
+    fn read_text(parser: &mut TextParser) -> Result<Self> {
+        parser.expect('(')?;
+        let [x, y, z] = read_axes(parser)?;
+        parser.expect(')')?;
+        Ok(Flip::new(x, y, z))
+    }
+}
+
+impl TagText for AxisFlags {
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.push("(");
+        axes_text(writer, self.array());
+        writer.push(")");
+    }
+
+    fn read_text(parser: &mut TextParser) -> Result<Self> {
+        parser.expect('(')?;
+        let [x, y, z] = read_axes(parser)?;
+        parser.expect(')')?;
+        Ok(AxisFlags::from_u8((x as u8) | ((y as u8) << 1) | ((z as u8) << 2)))
+    }
+}
+
+impl TagText for FaceFlags {
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.push("(");
+        let faces = Direction::ALL.into_iter().filter(|&face| self.get(face)).map(|face| format!("{face:?}")).collect::<Vec<_>>();
+        writer.push(&faces.join(", "));
+        writer.push(")");
+    }
+
+    fn read_text(parser: &mut TextParser) -> Result<Self> {
+        parser.expect('(')?;
+        let mut flags = FaceFlags::from_bits(0);
+        for face in parser.list(')', |parser| parser.named("direction", Direction::ALL))? {
+            flags.set(face, true);
+        }
+        Ok(flags)
+    }
+}
+
+/// Rotations are written as their up direction and angle, such as `(PosX, 1)`.
+/// Invalid rotations that can only come from binary data are written as their raw number so they still round-trip.
+fn rotation_is_valid(rotation: Rotation) -> bool {
+    rotation.0 >> 2 < 6
+}
+
+fn write_rotation(writer: &mut TextWriter, rotation: Rotation) {
+    writer.push(&format!("{:?}, {}", rotation.up(), rotation.angle()));
+}
+
+fn read_rotation(parser: &mut TextParser) -> Result<Rotation> {
+    let up = parser.named("direction", Direction::ALL)?;
+    parser.expect(',')?;
+    let angle = i32::read_text(parser)?;
+    Ok(Rotation::new(up, angle))
+}
+
+impl TagText for Rotation {
+    fn write_text(&self, writer: &mut TextWriter) {
+        if rotation_is_valid(*self) {
+            writer.push("(");
+            write_rotation(writer, *self);
+            writer.push(")");
+        } else {
+            writer.args(&[self.0]);
+        }
+    }
+
+    fn read_text(parser: &mut TextParser) -> Result<Self> {
+        parser.expect('(')?;
+        let rotation = if parser.peek().is_some_and(|c| c.is_ascii_digit()) {
+            Rotation(u8::read_text(parser)?)
+        } else {
+            read_rotation(parser)?
+        };
+        parser.expect(')')?;
+        Ok(rotation)
+    }
+}
+
+impl TagText for Orientation {
+    fn write_text(&self, writer: &mut TextWriter) {
+        if rotation_is_valid(self.rotation()) {
+            writer.push("(");
+            write_rotation(writer, self.rotation());
+            writer.push(", ");
+            let flip = self.flip();
+            axes_text(writer, [flip.x(), flip.y(), flip.z()]);
+            writer.push(")");
+        } else {
+            writer.args(&[self.0]);
+        }
+    }
+
+    fn read_text(parser: &mut TextParser) -> Result<Self> {
+        parser.expect('(')?;
+        let orientation = if parser.peek().is_some_and(|c| c.is_ascii_digit()) {
+            Orientation(u8::read_text(parser)?)
+        } else {
+            let rotation = read_rotation(parser)?;
+            parser.expect(',')?;
+            let [x, y, z] = read_axes(parser)?;
+            Orientation::new(rotation, Flip::new(x, y, z))
+        };
+        parser.expect(')')?;
+        Ok(orientation)
+    }
+}
+
+impl TagText for Color {
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.push(&format!("({})", self.pascal_name()));
+    }
+
+    fn read_text(parser: &mut TextParser) -> Result<Self> {
+        parser.expect('(')?;
+        parser.skip_whitespace();
+        let start = parser.pos;
+        let name = parser.ident()?;
+        let color = Color::iter().find(|color| color.pascal_name() == name)
+            .ok_or_else(|| parser.error_at(start, format!("unknown color \"{name}\"")))?;
+        parser.expect(')')?;
+        Ok(color)
+    }
+}
+
+/// Implements [TagText] for a type that's written as a fixed number of arguments.
+macro_rules! args_text {
+    ($($type:ty: [$arg:ty; $count:literal] |$value:ident| $to_args:expr, |$args:ident| $from_args:expr;)*) => {
+        $(
+            impl TagText for $type {
+                fn write_text(&self, writer: &mut TextWriter) {
+                    let $value = self;
+                    let args: [$arg; $count] = $to_args;
+                    writer.args(&args);
+                }
+
+                fn read_text(parser: &mut TextParser) -> Result<Self> {
+                    let $args = parser.args::<$arg, $count>()?;
+                    Ok($from_args)
+                }
+            }
+        )*
+    };
+}
+
+args_text!(
+    Rgb: [u8; 3] |rgb| [rgb.r, rgb.g, rgb.b], |args| Rgb::from_bytes(args);
+    Rgba: [u8; 4] |rgba| [rgba.r, rgba.g, rgba.b, rgba.a], |args| Rgba::new(args[0], args[1], args[2], args[3]);
+    IVec2: [i32; 2] |v| v.to_array(), |args| IVec2::from_array(args);
+    IVec3: [i32; 3] |v| v.to_array(), |args| IVec3::from_array(args);
+    IVec4: [i32; 4] |v| v.to_array(), |args| IVec4::from_array(args);
+    Vec2: [f32; 2] |v| v.to_array(), |args| Vec2::from_array(args);
+    Vec3: [f32; 3] |v| v.to_array(), |args| Vec3::from_array(args);
+    Vec4: [f32; 4] |v| v.to_array(), |args| Vec4::from_array(args);
+    Mat2: [f32; 4] |m| m.to_cols_array(), |args| Mat2::from_cols_array(&args);
+    Mat3: [f32; 9] |m| m.to_cols_array(), |args| Mat3::from_cols_array(&args);
+    Mat4: [f32; 16] |m| m.to_cols_array(), |args| Mat4::from_cols_array(&args);
+    Quat: [f32; 4] |q| q.to_array(), |args| Quat::from_array(args);
+    Bounds2D: [i32; 4] |b| [b.min.0, b.min.1, b.max.0, b.max.1], |args| Bounds2D { min: (args[0], args[1]), max: (args[2], args[3]) };
+    Bounds3D: [i32; 6] |b| [b.min.0, b.min.1, b.min.2, b.max.0, b.max.1, b.max.2], |args| Bounds3D { min: (args[0], args[1], args[2]), max: (args[3], args[4], args[5]) };
+    std::ops::Range<i64>: [i64; 2] |r| [r.start, r.end], |args| args[0]..args[1];
+    std::ops::RangeInclusive<i64>: [i64; 2] |r| [*r.start(), *r.end()], |args| args[0]..=args[1];
+);
+
+impl TagText for String {
+    const NAMED: bool = false;
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        writer.string(self);
+    }
+
+    fn read_text(parser: &mut TextParser) -> Result<Self> {
+        parser.string()
+    }
+}
+
+impl TagText for HashMap<String, Tag> {
+    const NAMED: bool = false;
+
+    fn write_text(&self, writer: &mut TextWriter) {
+        if self.is_empty() {
+            writer.push("{}");
+            return;
+        }
+        // Sorted so that the text doesn't change between runs.
+        let mut entries = self.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+        writer.push("{");
+        writer.depth += 1;
+        for (i, (key, value)) in entries.into_iter().enumerate() {
+            if i > 0 {
+                writer.push(",");
+                writer.line_break(" ");
+            } else {
+                writer.line_break("");
+            }
+            writer.key(key);
+            writer.push(": ");
+            value.write_text(writer);
+        }
+        writer.depth -= 1;
+        if writer.pretty {
+            writer.push(",");
+        }
+        writer.line_break("");
+        writer.push("}");
+    }
+
+    fn read_text(parser: &mut TextParser) -> Result<Self> {
+        parser.expect('{')?;
+        let mut map = HashMap::new();
+        for (start, key, value) in parser.list('}', |parser| {
+            parser.skip_whitespace();
+            let start = parser.pos;
+            let key = parser.key()?;
+            parser.expect(':')?;
+            Ok((start, key, Tag::read_text(parser)?))
+        })? {
+            if map.insert(key, value).is_some() {
+                return Err(parser.error_at(start, "duplicate key"));
+            }
+        }
+        Ok(map)
+    }
+}
+
+fn write_items<T: TagText>(writer: &mut TextWriter, name: &str, items: &[T]) {
+    writer.push("[");
+    writer.push(name);
+    writer.push(";");
+    for (i, item) in items.iter().enumerate() {
+        writer.push(if i > 0 { ", " } else { " " });
+        item.write_text(writer);
+    }
+    writer.push("]");
+}
+
+macro_rules! text_impls {
+    ($({$id:literal $name:ident $impl:ident $box:ident <$type:ty> $($end:tt)*})*) => {
+        impl TagText for Tag {
+            const NAMED: bool = false;
+
+            fn write_text(&self, writer: &mut TextWriter) {
+                match self {
+                    Tag::Null => writer.push("null"),
+                    $(
+                        Tag::$name(value) => value.write_tag(stringify!($name), writer),
+                    )*
+                }
+            }
+
+            fn read_text(parser: &mut TextParser) -> Result<Self> {
+                let Some(c) = parser.peek() else {
+                    return Err(parser.error("expected a value"));
+                };
+                match c {
+                    '"' => Ok(Tag::String(Box::new(parser.string()?))),
+                    '{' => Ok(Tag::Map(Box::new(HashMap::read_text(parser)?))),
+                    '[' => Ok(Tag::Array(Box::new(Array::read_text(parser)?))),
+                    '0'..='9' | '+' | '-' | '.' => read_number(parser),
+                    _ => {
+                        let start = parser.pos;
+                        match parser.ident()? {
+                            "null" => Ok(Tag::Null),
+                            "true" => Ok(Tag::Bool(true)),
+                            "false" => Ok(Tag::Bool(false)),
+                            "NaNf32" | "inff32" | "NaNf64" | "inff64" | "NaN" | "inf" => {
+                                parser.pos = start;
+                                read_number(parser)
+                            }
+                            $(
+                                stringify!($name) if <$type as TagText>::NAMED => Ok(Tag::from(<$type as TagText>::read_text(parser)?)),
+                            )*
+                            name => Err(parser.error_at(start, format!("unknown type \"{name}\""))),
+                        }
+                    }
+                }
+            }
+        }
+
+        impl TagText for Array {
+            const NAMED: bool = false;
+
+            fn write_text(&self, writer: &mut TextWriter) {
+                match self {
+                    Array::Empty => writer.push("[]"),
+                    $(
+                        Array::$name(items) => write_items(writer, stringify!($name), items),
+                    )*
+                    Array::Tag(items) if items.is_empty() => writer.push("[Tag;]"),
+                    Array::Tag(items) => {
+                        writer.push("[");
+                        writer.depth += 1;
+                        for (i, item) in items.iter().enumerate() {
+                            if i > 0 {
+                                writer.push(",");
+                                writer.line_break(" ");
+                            } else {
+                                writer.line_break("");
+                            }
+                            item.write_text(writer);
+                        }
+                        writer.depth -= 1;
+                        if writer.pretty {
+                            writer.push(",");
+                        }
+                        writer.line_break("");
+                        writer.push("]");
+                    }
+                }
+            }
+
+            fn read_text(parser: &mut TextParser) -> Result<Self> {
+                parser.expect('[')?;
+                if parser.eat(']') {
+                    return Ok(Array::Empty);
+                }
+                // A typed array starts with `Name;`.
+                let start = parser.pos;
+                let header = parser.ident().ok().filter(|_| parser.eat(';'));
+                let Some(name) = header else {
+                    parser.pos = start;
+                    return Ok(Array::Tag(parser.list(']', Tag::read_text)?));
+                };
+                match name {
+                    $(
+                        stringify!($name) => Ok(Array::$name(parser.list(']', <$type as TagText>::read_text)?)),
+                    )*
+                    "Tag" => Ok(Array::Tag(parser.list(']', Tag::read_text)?)),
+                    _ => Err(parser.error_at(start, format!("unknown array type \"{name}\""))),
+                }
+            }
+        }
+    };
+}
+
+tag_table!(text_impls);
+
+/// Reads a number and its suffix. Numbers without a suffix are `i32`, or `f64` if they look like a float.
+fn read_number(parser: &mut TextParser) -> Result<Tag> {
+    let (start, token) = parser.number_token()?;
+    let suffixes = ["u16", "i16", "u32", "i32", "u64", "i64", "f32", "f64", "u8", "i8"];
+    let (number, suffix) = match suffixes.into_iter().find(|suffix| token.ends_with(suffix)) {
+        Some(suffix) => (&token[..token.len() - suffix.len()], suffix),
+        None if token.contains(['.', 'e', 'E', 'n', 'N']) => (token, "f64"),
+        None => (token, "i32"),
+    };
+    fn parse<T: std::str::FromStr>(parser: &TextParser, start: usize, number: &str, suffix: &str) -> Result<T> {
+        number.parse().map_err(|_| parser.error_at(start, format!("invalid {suffix} \"{number}\"")))
+    }
+    Ok(match suffix {
+        "u8" => Tag::U8(parse(parser, start, number, suffix)?),
+        "i8" => Tag::I8(parse(parser, start, number, suffix)?),
+        "u16" => Tag::U16(parse(parser, start, number, suffix)?),
+        "i16" => Tag::I16(parse(parser, start, number, suffix)?),
+        "u32" => Tag::U32(parse(parser, start, number, suffix)?),
+        "i32" => Tag::I32(parse(parser, start, number, suffix)?),
+        "u64" => Tag::U64(parse(parser, start, number, suffix)?),
+        "i64" => Tag::I64(parse(parser, start, number, suffix)?),
+        "f32" => Tag::F32(parse(parser, start, number, suffix)?),
+        _ => Tag::F64(parse(parser, start, number, suffix)?),
+    })
+}
+
+impl Tag {
+    /// Writes the tag as text on a single line. See the [module docs](self) for the syntax.
+    pub fn to_text(&self) -> String {
+        let mut writer = TextWriter::new(false);
+        self.write_text(&mut writer);
+        writer.out
+    }
+
+    /// Writes the tag as text with one map entry or array element per line.
+    pub fn to_text_pretty(&self) -> String {
+        let mut writer = TextWriter::new(true);
+        self.write_text(&mut writer);
+        writer.out
+    }
+
+    /// Reads a tag from text written by [Tag::to_text] or by hand.
+    pub fn from_text(text: &str) -> Result<Tag> {
+        let mut parser = TextParser::new(text);
+        let tag = Tag::read_text(&mut parser)?;
+        if parser.peek().is_some() {
+            return Err(parser.error("expected the end of the text"));
+        }
+        Ok(tag)
+    }
+}
+
+impl std::fmt::Display for Tag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_text())
+    }
+}
+
+impl std::str::FromStr for Tag {
+    type Err = TagTextError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Tag::from_text(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn every_variant() -> Tag {
+        let mut tag = Tag::Map(Box::default());
+        tag["null"] = Tag::Null;
+        tag["bool"] = true.into();
+        tag["bits8"] = BitFlags8(0b1010).into();
+        tag["bits16"] = BitFlags16(0xf0f0).into();
+        tag["bits32"] = BitFlags32(7).into();
+        tag["bits64"] = BitFlags64(u64::MAX).into();
+        tag["bits128"] = BitFlags128(1 << 100).into();
+        tag["u8"] = 255u8.into();
+        tag["i8"] = (-128i8).into();
+        tag["u16"] = 65535u16.into();
+        tag["i16"] = (-5i16).into();
+        tag["u32"] = 7u32.into();
+        tag["i32"] = (-7i32).into();
+        tag["u64"] = u64::MAX.into();
+        tag["i64"] = i64::MIN.into();
+        tag["f32"] = 0.1f32.into();
+        tag["f64"] = (-1.5e-300f64).into();
+        tag["inf"] = f32::NEG_INFINITY.into();
+        tag["whole"] = 3.0f64.into();
+        tag["direction"] = Direction::NegZ.into();
+        tag["cardinal"] = Cardinal::East.into();
+        tag["rotation"] = Rotation::new(Direction::PosX, 3).into();
+        tag["flip"] = Flip::XZ.into();
+        tag["orientation"] = Orientation::new(Rotation::new(Direction::NegY, 1), Flip::Y).into();
+        tag["axis"] = Axis::Z.into();
+        tag["axis_flags"] = AxisFlags::XY.into();
+        tag["faces"] = FaceFlags::from_bits(0b100101).into();
+        tag["color"] = Color::iter().nth(7).unwrap().into();
+        tag["rgb"] = Rgb::new(1, 2, 3).into();
+        tag["rgba"] = Rgba::new(1, 2, 3, 4).into();
+        tag["ivec2"] = IVec2::new(1, -2).into();
+        tag["ivec3"] = IVec3::new(1, 2, 3).into();
+        tag["ivec4"] = IVec4::new(1, 2, 3, 4).into();
+        tag["vec2"] = Vec2::new(0.5, 1.0).into();
+        tag["vec3"] = Vec3::new(1.0, 2.0, 3.0).into();
+        tag["vec4"] = Vec4::new(1.0, 2.0, 3.0, 4.0).into();
+        tag["mat2"] = Mat2::from_cols_array(&[1.0, 2.0, 3.0, 4.0]).into();
+        tag["mat3"] = Mat3::IDENTITY.into();
+        tag["mat4"] = Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0)).into();
+        tag["quat"] = Quat::from_rotation_y(1.0).into();
+        tag["bounds2"] = Bounds2D { min: (0, 1), max: (2, 3) }.into();
+        tag["bounds3"] = Bounds3D { min: (0, 1, 2), max: (3, 4, 5) }.into();
+        tag["range"] = (-4i64..8).into();
+        tag["range_inclusive"] = (-4i64..=8).into();
+        tag["string"] = "quote \" slash \\ line\n tab\t bell\u{7} é".into();
+        tag["empty_array"] = Tag::Array(Box::new(Array::Empty));
+        tag["empty_tags"] = Vec::<Tag>::new().into();
+        tag["empty_ints"] = Vec::<i32>::new().into();
+        tag["ints"] = vec![1i32, -2, 3].into();
+        tag["vecs"] = vec![Vec3::X, Vec3::NEG_Y].into();
+        tag["strings"] = vec!["a", "b"].into();
+        tag["tags"] = vec![Tag::from(1u8), Tag::from("two"), vec![3i64].into()].into();
+        tag["empty_map"] = Tag::Map(Box::default());
+        tag["odd key!"] = 1i32.into();
+        tag["nested"] = Tag::Map(Box::default());
+        tag["nested"]["deeper"] = vec![Tag::Map(Box::default())].into();
+        tag
+    }
+
+    #[test]
+    fn text_round_trip_test() {
+        let tag = every_variant();
+        let text = tag.to_text();
+        debug_assert_eq!(Tag::from_text(&text).unwrap(), tag, "{text}");
+        let pretty = tag.to_text_pretty();
+        debug_assert_eq!(Tag::from_text(&pretty).unwrap(), tag, "{pretty}");
+        debug_assert_eq!(text.parse::<Tag>().unwrap().to_text(), text);
+        debug_assert_eq!(Tag::from(Vec3::new(0.0, 0.5, 0.0)).to_text(), "Vec3(0.0, 0.5, 0.0)");
+        debug_assert_eq!(Tag::from(Direction::PosX).to_text(), "Direction(PosX)");
+        debug_assert_eq!(Tag::from(5u8).to_text(), "5u8");
+        debug_assert_eq!(Tag::from(vec![1u8, 2]).to_text(), "[U8; 1, 2]");
+    }
+
+    #[test]
+    fn text_parse_test() {
+        let tag = Tag::from_text(r#"
+            // A lamp.
+            {
+                name: "Lamp",
+                "power level": 15,
+                scale: 2.5,
+                color: Rgb(255, 200, 120),
+                offset: Vec3(0, 0.5e0, -1),
+                faces: FaceFlags(PosX, NegY),
+                levels: [U8; 1, 2, 3,],
+                parts: [{id: "base"}, {id: "bulb"}],
+            }
+        "#).unwrap();
+        debug_assert_eq!(tag["name"], Tag::from("Lamp"));
+        debug_assert_eq!(tag["power level"], Tag::I32(15));
+        debug_assert_eq!(tag["scale"], Tag::F64(2.5));
+        debug_assert_eq!(tag["color"], Tag::Rgb(Rgb::new(255, 200, 120)));
+        debug_assert_eq!(tag["offset"], Tag::Vec3(Vec3::new(0.0, 0.5, -1.0)));
+        debug_assert_eq!(tag["levels"], Tag::from(vec![1u8, 2, 3]));
+        debug_assert_eq!(tag["parts"], Tag::from_text("[{id: \"base\"}, {id: \"bulb\"}]").unwrap());
+
+        let error = Tag::from_text("{\n    a: 1,\n    b: Vec3(1.0, 2.0)\n}").unwrap_err();
+        debug_assert_eq!((error.line, error.column), (3, 21));
+        debug_assert!(Tag::from_text("{a: 1, a: 2}").is_err());
+        debug_assert!(Tag::from_text("300u8").is_err());
+        debug_assert!(Tag::from_text("Direction(Up)").is_err());
+        debug_assert!(Tag::from_text("[Nope; 1]").is_err());
+        debug_assert!(Tag::from_text("1 2").is_err());
+        debug_assert!(Tag::from_text("\"open").is_err());
+    }
+}
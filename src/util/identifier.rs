@@ -0,0 +1,272 @@
+//! Namespaced identifiers (`namespace:path`) used as registry keys.
+
+use std::borrow::{Borrow, Cow};
+
+use hashbrown::HashMap;
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+
+/// The namespace that bare names (without a `namespace:` prefix) belong to unless a registry is configured otherwise.
+pub const DEFAULT_NAMESPACE: &str = "hexahedron";
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum IdentifierError {
+    #[error("Invalid identifier namespace \"{0}\", namespaces may only contain a-z, 0-9, '_', '-' and '.'.")]
+    InvalidNamespace(String),
+    #[error("Invalid identifier path \"{0}\", paths may only contain a-z, 0-9, '_', '-', '.' and '/'.")]
+    InvalidPath(String),
+}
+
+/// A `namespace:path` name, such as `hexahedron:stone` or `my_pack:lamps/red_lamp`.
+///
+/// Content from different sources goes in different namespaces so that their names can't collide.
+/// The text is stored in its `namespace:path` form, which is also how it's hashed and compared,
+/// so maps keyed by [Identifier] can be looked up with a `&str`.
+#[derive(Debug, Clone)]
+pub struct Identifier {
+    text: String,
+    /// The byte index of the `:`.
+    colon: usize,
+}
+
+impl Identifier {
+    pub fn new(namespace: &str, path: &str) -> Result<Self, IdentifierError> {
+        validate_namespace(namespace)?;
+        validate_path(path)?;
+        Ok(Self {
+            text: format!("{namespace}:{path}"),
+            colon: namespace.len(),
+        })
+    }
+
+    /// Parses `namespace:path`, or a bare `path` which goes in [DEFAULT_NAMESPACE].
+    #[inline]
+    pub fn parse(text: &str) -> Result<Self, IdentifierError> {
+        Self::parse_or(text, DEFAULT_NAMESPACE)
+    }
+
+    /// Parses `namespace:path`, or a bare `path` which goes in `default_namespace`.
+    pub fn parse_or(text: &str, default_namespace: &str) -> Result<Self, IdentifierError> {
+        match text.split_once(':') {
+            Some((namespace, path)) => Self::new(namespace, path),
+            None => Self::new(default_namespace, text),
+        }
+    }
+
+    #[inline]
+    pub fn namespace(&self) -> &str {
+        &self.text[..self.colon]
+    }
+
+    #[inline]
+    pub fn path(&self) -> &str {
+        &self.text[self.colon + 1..]
+    }
+
+    /// The `namespace:path` text.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+fn validate_namespace(namespace: &str) -> Result<(), IdentifierError> {
+    let valid = !namespace.is_empty() && namespace.chars().all(|c| matches!(c, 'a'..='z' | '0'..='9' | '_' | '-' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(IdentifierError::InvalidNamespace(namespace.to_owned()))
+    }
+}
+
+fn validate_path(path: &str) -> Result<(), IdentifierError> {
+    let valid = !path.is_empty() && path.chars().all(|c| matches!(c, 'a'..='z' | '0'..='9' | '_' | '-' | '.' | '/'));
+    if valid {
+        Ok(())
+    } else {
+        Err(IdentifierError::InvalidPath(path.to_owned()))
+    }
+}
+
+/// Adds `default_namespace` to `name` if it's a bare name, without validating it.
+/// Used for looking up names in an [IdentifierMap].
+#[inline]
+pub fn qualify<'a>(name: &'a str, default_namespace: &str) -> Cow<'a, str> {
+    if name.contains(':') {
+        Cow::Borrowed(name)
+    } else {
+        Cow::Owned(format!("{default_namespace}:{name}"))
+    }
+}
+
+// Compared and hashed by the text alone, since `Borrow<str>` requires the hash to match the `str`'s.
+impl PartialEq for Identifier {
+    fn eq(&self, other: &Self) -> bool {
+        self.text == other.text
+    }
+}
+
+impl Eq for Identifier {}
+
+impl PartialOrd for Identifier {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Identifier {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.text.cmp(&other.text)
+    }
+}
+
+impl std::hash::Hash for Identifier {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.text.hash(state)
+    }
+}
+
+impl std::fmt::Display for Identifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl std::str::FromStr for Identifier {
+    type Err = IdentifierError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Borrow<str> for Identifier {
+    fn borrow(&self) -> &str {
+        &self.text
+    }
+}
+
+impl AsRef<str> for Identifier {
+    fn as_ref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Serialize for Identifier {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+impl<'de> Deserialize<'de> for Identifier {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = Cow::<'de, str>::deserialize(deserializer)?;
+        Identifier::parse(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A map keyed by [Identifier] that can be looked up by name, where bare names are in the map's default namespace.
+/// This is the lookup table of registries.
+#[derive(Debug, Clone)]
+pub struct IdentifierMap<V> {
+    default_namespace: String,
+    map: HashMap<Identifier, V>,
+}
+
+impl<V> Default for IdentifierMap<V> {
+    fn default() -> Self {
+        Self {
+            default_namespace: DEFAULT_NAMESPACE.to_owned(),
+            map: HashMap::new(),
+        }
+    }
+}
+
+impl<V> IdentifierMap<V> {
+    pub fn new(default_namespace: &str) -> Result<Self, IdentifierError> {
+        validate_namespace(default_namespace)?;
+        Ok(Self {
+            default_namespace: default_namespace.to_owned(),
+            map: HashMap::new(),
+        })
+    }
+
+    #[inline]
+    pub fn default_namespace(&self) -> &str {
+        &self.default_namespace
+    }
+
+    /// Parses `name` into an [Identifier], using the default namespace for bare names.
+    #[inline]
+    pub fn resolve(&self, name: &str) -> Result<Identifier, IdentifierError> {
+        Identifier::parse_or(name, &self.default_namespace)
+    }
+
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&V> {
+        self.map.get(qualify(name, &self.default_namespace).as_ref())
+    }
+
+    #[inline]
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    #[inline]
+    pub fn insert(&mut self, id: Identifier, value: V) -> Option<V> {
+        self.map.insert(id, value)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&Identifier, &V)> {
+        self.map.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifier_test() {
+        let id = Identifier::parse("my_pack:lamps/red_lamp").unwrap();
+        debug_assert_eq!(id.namespace(), "my_pack");
+        debug_assert_eq!(id.path(), "lamps/red_lamp");
+        debug_assert_eq!(id.to_string(), "my_pack:lamps/red_lamp");
+        debug_assert_eq!("stone".parse::<Identifier>().unwrap(), Identifier::new(DEFAULT_NAMESPACE, "stone").unwrap());
+        debug_assert_eq!(Identifier::parse_or("stone", "other").unwrap().as_str(), "other:stone");
+        debug_assert!(matches!(Identifier::parse("My:stone"), Err(IdentifierError::InvalidNamespace(_))));
+        debug_assert!(matches!(Identifier::parse("a:b:c"), Err(IdentifierError::InvalidPath(_))));
+        debug_assert!(matches!(Identifier::parse(":stone"), Err(IdentifierError::InvalidNamespace(_))));
+        debug_assert!(matches!(Identifier::parse("stone:"), Err(IdentifierError::InvalidPath(_))));
+        debug_assert!(Identifier::parse("white space").is_err());
+
+        let json = serde_json::to_string(&id).unwrap();
+        debug_assert_eq!(json, "\"my_pack:lamps/red_lamp\"");
+        debug_assert_eq!(serde_json::from_str::<Identifier>(&json).unwrap(), id);
+        debug_assert!(serde_json::from_str::<Identifier>("\"Bad\"").is_err());
+    }
+
+    #[test]
+    fn identifier_map_test() {
+        let mut map = IdentifierMap::new("game").unwrap();
+        map.insert(map.resolve("lamp").unwrap(), 1);
+        map.insert(map.resolve("pack:lamp").unwrap(), 2);
+        debug_assert_eq!(map.get("lamp"), Some(&1));
+        debug_assert_eq!(map.get("game:lamp"), Some(&1));
+        debug_assert_eq!(map.get("pack:lamp"), Some(&2));
+        debug_assert_eq!(map.get("other:lamp"), None);
+        debug_assert_eq!(map.len(), 2);
+        debug_assert!(IdentifierMap::<()>::new("Bad Namespace").is_err());
+    }
+}
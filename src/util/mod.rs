@@ -1,8 +1,10 @@
-// pub mod extensions;
-// pub mod functional;
+pub mod extensions;
+pub mod functional;
 pub mod traits;
-// pub mod change;
-// pub mod iter;
+pub mod scanner;
+pub mod identifier;
+pub mod change;
+pub mod iter;
 // pub mod hashing;
 // pub mod rng;
 // pub mod crypt;
@@ -0,0 +1,370 @@
+//! A small character scanner used by the text formats in this crate.
+
+use std::fmt::Write;
+
+/// An error produced while parsing text.
+/// `position` is the byte offset into the source where the error occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub position: usize,
+    pub reason: String,
+}
+
+impl SyntaxError {
+    #[inline]
+    pub fn new<S: Into<String>>(position: usize, reason: S) -> Self {
+        Self {
+            position,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at position {}.", self.reason, self.position)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+pub type SyntaxResult<T> = std::result::Result<T, SyntaxError>;
+
+/// Reads through a `&str` one character at a time while keeping track of the byte position.
+#[derive(Debug, Clone)]
+pub struct Scanner<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Scanner<'a> {
+    #[inline]
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            position: 0,
+        }
+    }
+
+    #[inline]
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// The byte offset of the next character.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The text that hasn't been consumed yet.
+    #[inline]
+    pub fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+
+    #[inline]
+    pub fn is_eof(&self) -> bool {
+        self.position >= self.source.len()
+    }
+
+    #[inline]
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    pub fn next_char(&mut self) -> Option<char> {
+        let next = self.peek()?;
+        self.position += next.len_utf8();
+        Some(next)
+    }
+
+    /// Consumes `c` if it is the next character.
+    pub fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes `text` if the remaining text starts with it.
+    pub fn eat_str(&mut self, text: &str) -> bool {
+        if self.rest().starts_with(text) {
+            self.position += text.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn expect(&mut self, c: char) -> SyntaxResult<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{c}`")))
+        }
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    /// Consumes characters while `predicate` returns `true` and returns the consumed text.
+    pub fn take_while<F: FnMut(char) -> bool>(&mut self, mut predicate: F) -> &'a str {
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            if !predicate(c) {
+                break;
+            }
+            self.position += c.len_utf8();
+        }
+        &self.source[start..self.position]
+    }
+
+    /// Returns the word (made of [is_word_char] characters) at the current position without consuming it.
+    pub fn peek_word(&self) -> &'a str {
+        let rest = self.rest();
+        let end = rest.find(|c: char| !is_word_char(c)).unwrap_or(rest.len());
+        &rest[..end]
+    }
+
+    /// Consumes a word made of [is_word_char] characters.
+    pub fn word(&mut self) -> SyntaxResult<&'a str> {
+        let word = self.take_while(is_word_char);
+        if word.is_empty() {
+            Err(self.unexpected("a word"))
+        } else {
+            Ok(word)
+        }
+    }
+
+    /// Skips trailing whitespace and fails if there is anything left.
+    pub fn expect_eof(&mut self) -> SyntaxResult<()> {
+        self.skip_whitespace();
+        if self.is_eof() {
+            Ok(())
+        } else {
+            Err(self.unexpected("end of input"))
+        }
+    }
+
+    #[inline]
+    pub fn error<S: Into<String>>(&self, reason: S) -> SyntaxError {
+        SyntaxError::new(self.position, reason)
+    }
+
+    #[inline]
+    pub fn error_at<S: Into<String>>(&self, position: usize, reason: S) -> SyntaxError {
+        SyntaxError::new(position, reason)
+    }
+
+    /// An error saying that `expected` was expected, but something else was found.
+    pub fn unexpected(&self, expected: &str) -> SyntaxError {
+        match self.peek() {
+            Some(found) => self.error(format!("Expected {expected}, found `{}`", found.escape_debug())),
+            None => self.error(format!("Expected {expected}, found end of input")),
+        }
+    }
+
+    /// Parses an integer literal. Supports an optional sign, `0x`/`0o`/`0b` prefixes, and `_` separators.
+    pub fn integer<T: TryFrom<i128> + TryFrom<u128>>(&mut self) -> SyntaxResult<T> {
+        let start = self.position;
+        let negative = if self.eat('-') {
+            true
+        } else {
+            self.eat('+');
+            false
+        };
+        let radix = if self.eat_str("0x") {
+            16
+        } else if self.eat_str("0o") {
+            8
+        } else if self.eat_str("0b") {
+            2
+        } else {
+            10
+        };
+        let digits_start = self.position;
+        let digits = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        if digits.is_empty() {
+            self.position = digits_start;
+            return Err(self.unexpected("digits"));
+        }
+        let digits = digits.replace('_', "");
+        let Ok(magnitude) = u128::from_str_radix(&digits, radix) else {
+            return Err(self.error_at(start, format!("Invalid integer `{}`", &self.source[start..self.position])));
+        };
+        let out_of_range = || self.error_at(start, format!("Integer out of range for {}", std::any::type_name::<T>()));
+        if negative {
+            let value = 0i128.checked_sub_unsigned(magnitude).ok_or_else(out_of_range)?;
+            T::try_from(value).map_err(|_| out_of_range())
+        } else {
+            T::try_from(magnitude).map_err(|_| out_of_range())
+        }
+    }
+
+    /// Parses a double quoted string with the escapes written by [write_quoted].
+    pub fn quoted(&mut self) -> SyntaxResult<String> {
+        self.expect('"')?;
+        let mut text = String::new();
+        loop {
+            let position = self.position;
+            match self.next_char() {
+                Some('"') => return Ok(text),
+                Some('\\') => text.push(self.escape(position)?),
+                Some(c) => text.push(c),
+                None => return Err(self.error("Unterminated string")),
+            }
+        }
+    }
+
+    /// Parses a double quoted byte string (the `b` prefix must already be consumed).
+    /// Bytes that aren't printable ASCII are written as `\xNN`.
+    pub fn quoted_bytes(&mut self) -> SyntaxResult<Vec<u8>> {
+        self.expect('"')?;
+        let mut bytes = Vec::new();
+        loop {
+            let position = self.position;
+            match self.next_char() {
+                Some('"') => return Ok(bytes),
+                Some('\\') => {
+                    if self.eat('x') {
+                        let hex_start = self.position;
+                        let hex = self.rest().get(..2).unwrap_or("");
+                        let Ok(byte) = u8::from_str_radix(hex, 16) else {
+                            return Err(self.error_at(hex_start, "Expected two hex digits"));
+                        };
+                        self.position += 2;
+                        bytes.push(byte);
+                    } else {
+                        let c = self.escape(position)?;
+                        if !c.is_ascii() {
+                            return Err(self.error_at(position, "Byte strings can only contain ASCII"));
+                        }
+                        bytes.push(c as u8);
+                    }
+                }
+                Some(c) if c.is_ascii() => bytes.push(c as u8),
+                Some(_) => return Err(self.error_at(position, "Byte strings can only contain ASCII")),
+                None => return Err(self.error("Unterminated byte string")),
+            }
+        }
+    }
+
+    /// Parses the character after a `\`. `position` is the position of the `\`.
+    fn escape(&mut self, position: usize) -> SyntaxResult<char> {
+        Ok(match self.next_char() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some('u') => {
+                self.expect('{')?;
+                let hex = self.take_while(|c| c.is_ascii_hexdigit());
+                let c = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32);
+                self.expect('}')?;
+                c.ok_or_else(|| self.error_at(position, "Invalid unicode escape"))?
+            }
+            _ => return Err(self.error_at(position, "Invalid escape")),
+        })
+    }
+}
+
+/// Characters that can be used in unquoted names.
+#[inline]
+pub fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':' | '/')
+}
+
+/// Whether `text` can be written without quotes.
+#[inline]
+pub fn is_word(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(is_word_char)
+}
+
+/// Writes `text` in double quotes, escaping it so that [Scanner::quoted] can read it back.
+pub fn write_quoted<W: Write>(f: &mut W, text: &str) -> std::fmt::Result {
+    f.write_char('"')?;
+    for c in text.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            '\0' => f.write_str("\\0")?,
+            c if c.is_control() => write!(f, "\\u{{{:x}}}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Writes `bytes` as a `b"..."` byte string that [Scanner::quoted_bytes] can read back.
+pub fn write_quoted_bytes<W: Write>(f: &mut W, bytes: &[u8]) -> std::fmt::Result {
+    f.write_str("b\"")?;
+    for &byte in bytes {
+        match byte {
+            b'"' => f.write_str("\\\"")?,
+            b'\\' => f.write_str("\\\\")?,
+            0x20..=0x7e => f.write_char(byte as char)?,
+            _ => write!(f, "\\x{byte:02x}")?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Writes `text` as is if it is a word, otherwise writes it quoted.
+pub fn write_name<W: Write>(f: &mut W, text: &str) -> std::fmt::Result {
+    if is_word(text) {
+        f.write_str(text)
+    } else {
+        write_quoted(f, text)
+    }
+}
+
+/// Parses either a word or a quoted string.
+pub fn parse_name(scanner: &mut Scanner) -> SyntaxResult<String> {
+    if scanner.peek() == Some('"') {
+        scanner.quoted()
+    } else {
+        scanner.word().map(str::to_owned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn scanner_test() {
+        let mut scanner = Scanner::new("  -0x7f, 255 1_000 \"a\\n\\u{1f600}\\\"\"");
+        scanner.skip_whitespace();
+        debug_assert_eq!(scanner.integer::<i8>(), Ok(-127));
+        scanner.expect(',').unwrap();
+        scanner.skip_whitespace();
+        debug_assert_eq!(scanner.clone().integer::<i8>().unwrap_err().position, 9);
+        debug_assert_eq!(scanner.integer::<u8>(), Ok(255));
+        scanner.skip_whitespace();
+        debug_assert_eq!(scanner.integer::<i64>(), Ok(1000));
+        scanner.skip_whitespace();
+        debug_assert_eq!(scanner.quoted().unwrap(), "a\n\u{1f600}\"");
+        scanner.expect_eof().unwrap();
+
+        let mut text = String::new();
+        write_quoted(&mut text, "tab\t\u{7}\"quote\"").unwrap();
+        debug_assert_eq!(Scanner::new(&text).quoted().unwrap(), "tab\t\u{7}\"quote\"");
+        let mut text = String::new();
+        write_quoted_bytes(&mut text, &[0, 1, b'a', 0xff, b'"']).unwrap();
+        let mut scanner = Scanner::new(&text);
+        scanner.expect('b').unwrap();
+        debug_assert_eq!(scanner.quoted_bytes().unwrap(), vec![0, 1, b'a', 0xff, b'"']);
+
+        let error = Scanner::new("\"unterminated").quoted().unwrap_err();
+        debug_assert_eq!(error.position, 13);
+    }
+}
@@ -1,15 +1,166 @@
 use std::any::Any;
 
+use crate::voxel::direction::Direction;
+use crate::voxel::orientation::Orientation;
+
+use super::block_context::{BlockContext, UseResult};
 use super::block_registry::BlockRegistry;
+use super::block_material::BlockMaterial;
+use super::block_schema::PropertyDef;
+use super::block_shape::{self, BlockShape};
+use super::block_state::BlockState;
+use super::id::StateId;
 
-pub trait BlockBehavior: Any {
+pub trait BlockBehavior: Any + Send + Sync {
     // Details
     fn name(&self) -> &str;
     fn display_name(&self) -> Option<&str> { None }
     fn description(&self) -> Option<&str> { None }
+    /// The properties that states of this block can have.
+    /// Every combination of property values is registered as a state when the block is registered.
+    fn properties(&self) -> Vec<PropertyDef> { Vec::new() }
+    /// Whether this block should receive [BlockBehavior::on_random_tick] calls.
+    fn receives_random_ticks(&self) -> bool { false }
+    /// Transforms `state` (a state of this block) when the block is rotated or mirrored by `orientation`,
+    /// such as when a structure is placed. The default transforms every property with [Property::reorient](super::block_property::Property::reorient).
+    fn transform(&self, state: &BlockState, orientation: Orientation) -> BlockState { state.reorient(orientation) }
+
+    // Shapes
+    // Shapes are queried once per state when the block is registered, and cached by the registry.
+    /// The shape that entities collide with, before it is rotated by [BlockBehavior::orientation].
+    #[allow(unused)]
+    fn collision_shape(&self, state: &BlockState) -> BlockShape { BlockShape::full() }
+    /// The shape that is outlined and hit by raycasts, before it is rotated by [BlockBehavior::orientation].
+    fn outline_shape(&self, state: &BlockState) -> BlockShape { self.collision_shape(state) }
+    /// The orientation that the shapes of `state` are rotated by.
+    /// The default is [block_shape::state_orientation], which uses an [Orientation] or `facing` property.
+    fn orientation(&self, state: &BlockState) -> Orientation { block_shape::state_orientation(state) }
+
+    /// The light levels, transparency and physical properties of `state`.
+    /// Queried once per state when the block is registered, and can be overridden with [BlockRegistry::set_material].
+    #[allow(unused)]
+    fn material(&self, state: &BlockState) -> BlockMaterial { BlockMaterial::DEFAULT }
 
     // Callbacks
     #[allow(unused)]
     fn on_register(&self, registry: &BlockRegistry) {}
-    
-}
\ No newline at end of file
+
+    /// Called after the block has been placed in the world.
+    #[allow(unused)]
+    fn on_place(&self, context: &mut BlockContext) {}
+
+    /// Called after the block has been removed from the world.
+    /// [BlockContext::state_id] is the state that was removed.
+    #[allow(unused)]
+    fn on_break(&self, context: &mut BlockContext) {}
+
+    /// Called when the neighbor in `direction` has changed to `neighbor`.
+    #[allow(unused)]
+    fn on_neighbor_changed(&self, context: &mut BlockContext, direction: Direction, neighbor: StateId) {}
+
+    /// Called when a tick that was scheduled with [BlockContext::schedule_tick] is due.
+    #[allow(unused)]
+    fn on_scheduled_tick(&self, context: &mut BlockContext) {}
+
+    /// Called at random intervals if [BlockBehavior::receives_random_ticks] returns `true`.
+    #[allow(unused)]
+    fn on_random_tick(&self, context: &mut BlockContext) {}
+
+    /// Called when a player interacts with the block.
+    #[allow(unused)]
+    fn on_use(&self, context: &mut BlockContext) -> UseResult { UseResult::Pass }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::IVec3;
+    use hashbrown::HashMap;
+
+    use crate::blockstate;
+    use crate::util::change::Change;
+    use crate::voxel::block::block_context::BlockWorld;
+    use super::*;
+
+    struct TestWorld {
+        registry: BlockRegistry,
+        blocks: HashMap<IVec3, StateId>,
+        scheduled: Vec<(IVec3, u32)>,
+    }
+
+    impl BlockWorld for TestWorld {
+        fn registry(&self) -> &BlockRegistry {
+            &self.registry
+        }
+
+        fn get_state(&self, coord: IVec3) -> StateId {
+            self.blocks.get(&coord).copied().unwrap_or(StateId::AIR)
+        }
+
+        fn set_state(&mut self, coord: IVec3, state: StateId) -> Change<StateId> {
+            let old = self.blocks.insert(coord, state).unwrap_or(StateId::AIR);
+            Change::cmp_new(&state, old)
+        }
+
+        fn schedule_tick(&mut self, coord: IVec3, delay: u32) {
+            self.scheduled.push((coord, delay));
+        }
+    }
+
+    #[test]
+    fn callback_test() {
+        // Turns into a lit lamp when used, then turns itself off with a scheduled tick.
+        struct LampBlock;
+        impl BlockBehavior for LampBlock {
+            fn name(&self) -> &str {
+                "lamp"
+            }
+
+            fn properties(&self) -> Vec<PropertyDef> {
+                vec![PropertyDef::bool("lit", false)]
+            }
+
+            fn on_use(&self, context: &mut BlockContext) -> UseResult {
+                context.set_block_state(context.coord(), blockstate!(lamp[lit=true])).unwrap();
+                context.schedule_tick(4);
+                UseResult::Consume
+            }
+
+            fn on_scheduled_tick(&self, context: &mut BlockContext) {
+                context.set_block_state(context.coord(), blockstate!(lamp[lit=false])).unwrap();
+            }
+
+            fn on_neighbor_changed(&self, context: &mut BlockContext, _direction: Direction, neighbor: StateId) {
+                if neighbor.is_air() {
+                    context.remove();
+                }
+            }
+        }
+        let registry = BlockRegistry::new();
+        registry.register_block(LampBlock).unwrap();
+        let off = registry.find_state(blockstate!(lamp[lit=false])).unwrap();
+        let mut world = TestWorld {
+            registry: registry.clone(),
+            blocks: HashMap::new(),
+            scheduled: Vec::new(),
+        };
+        let coord = IVec3::new(1, 2, 3);
+        world.set_state(coord, off);
+        let lamp = registry.get_block(off).unwrap();
+
+        let mut context = BlockContext::new(&mut world, coord, off);
+        let result = lamp.on_use(&mut context);
+        debug_assert!(result.consumed());
+        let on = context.state_id();
+        debug_assert_ne!(on, off);
+        debug_assert_eq!(world.get_state(coord), on);
+        debug_assert_eq!(world.scheduled, vec![(coord, 4)]);
+
+        let mut context = BlockContext::new(&mut world, coord, on);
+        lamp.on_scheduled_tick(&mut context);
+        debug_assert_eq!(world.get_state(coord), off);
+
+        let mut context = BlockContext::new(&mut world, coord, off);
+        lamp.on_neighbor_changed(&mut context, Direction::NegY, StateId::AIR);
+        debug_assert_eq!(world.get_state(coord), StateId::AIR);
+    }
+}
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use glam::IVec3;
+
+use crate::util::change::Change;
+use crate::voxel::direction::Direction;
+
+use super::block::BlockBehavior;
+use super::block_registry::{BlockRegistry, RefOrOwned};
+use super::block_state::BlockState;
+use super::id::{BlockId, StateId};
+use super::error::Result;
+
+/// The world access that [BlockBehavior] callbacks are given through [BlockContext].
+///
+/// Any world that wants to run block callbacks needs to implement this trait.
+pub trait BlockWorld {
+    /// The [BlockRegistry] that the [StateId]s in this world belong to.
+    fn registry(&self) -> &BlockRegistry;
+    /// Gets the [StateId] at `coord`. Unloaded or empty coordinates are [StateId::AIR].
+    fn get_state(&self, coord: IVec3) -> StateId;
+    /// Sets the [StateId] at `coord`, returning the previous [StateId] if it changed.
+    fn set_state(&mut self, coord: IVec3, state: StateId) -> Change<StateId>;
+    /// Schedules a call to [BlockBehavior::on_scheduled_tick] for the block at `coord` in `delay` ticks.
+    fn schedule_tick(&mut self, coord: IVec3, delay: u32);
+}
+
+/// The result of [BlockBehavior::on_use].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UseResult {
+    /// The block did not handle the interaction, so it should be passed on (for example, to the held item).
+    #[default]
+    Pass,
+    /// The block handled the interaction.
+    Consume,
+}
+
+impl UseResult {
+    #[inline]
+    pub const fn consumed(self) -> bool {
+        matches!(self, UseResult::Consume)
+    }
+}
+
+/// The context passed to [BlockBehavior] callbacks.
+///
+/// Holds the coordinate and [StateId] of the block that the callback is for
+/// as well as mutable access to the [BlockWorld] that the block is in.
+pub struct BlockContext<'a> {
+    world: &'a mut dyn BlockWorld,
+    coord: IVec3,
+    state: StateId,
+}
+
+impl<'a> BlockContext<'a> {
+    #[inline]
+    pub fn new(world: &'a mut dyn BlockWorld, coord: IVec3, state: StateId) -> Self {
+        Self {
+            world,
+            coord,
+            state,
+        }
+    }
+
+    /// The coordinate of the block that the callback is for.
+    #[inline]
+    pub fn coord(&self) -> IVec3 {
+        self.coord
+    }
+
+    /// The [StateId] of the block that the callback is for.
+    #[inline]
+    pub fn state_id(&self) -> StateId {
+        self.state
+    }
+
+    /// The [BlockState] of the block that the callback is for.
+    #[inline]
+    pub fn state(&self) -> Result<Arc<BlockState>> {
+        self.registry().get_state(self.state)
+    }
+
+    #[inline]
+    pub fn block_id(&self) -> Result<BlockId> {
+        self.registry().block_id(self.state)
+    }
+
+    #[inline]
+    pub fn registry(&self) -> &BlockRegistry {
+        self.world.registry()
+    }
+
+    #[inline]
+    pub fn world(&self) -> &dyn BlockWorld {
+        self.world
+    }
+
+    #[inline]
+    pub fn world_mut(&mut self) -> &mut dyn BlockWorld {
+        self.world
+    }
+
+    #[inline]
+    pub fn get_state(&self, coord: IVec3) -> StateId {
+        self.world.get_state(coord)
+    }
+
+    /// Gets the [StateId] of the block adjacent to this block in `direction`.
+    #[inline]
+    pub fn neighbor(&self, direction: Direction) -> StateId {
+        self.world.get_state(self.coord + direction.to_ivec3())
+    }
+
+    #[inline]
+    pub fn get_block(&self, coord: IVec3) -> Result<Arc<dyn BlockBehavior>> {
+        let state = self.world.get_state(coord);
+        self.registry().get_block(state)
+    }
+
+    /// Sets the [StateId] at `coord`. If `coord` is the coordinate of this block,
+    /// [BlockContext::state_id] is updated as well.
+    pub fn set_state(&mut self, coord: IVec3, state: StateId) -> Change<StateId> {
+        if coord == self.coord {
+            self.state = state;
+        }
+        self.world.set_state(coord, state)
+    }
+
+    /// Registers `state` (if it isn't already registered) and sets it at `coord`.
+    pub fn set_block_state<S: RefOrOwned<BlockState>>(&mut self, coord: IVec3, state: S) -> Result<Change<StateId>> {
+        let id = self.registry().find_state(state)?;
+        Ok(self.set_state(coord, id))
+    }
+
+    /// Replaces the state of this block.
+    #[inline]
+    pub fn replace_state(&mut self, state: StateId) -> Change<StateId> {
+        self.set_state(self.coord, state)
+    }
+
+    /// Removes this block from the world by replacing it with air.
+    #[inline]
+    pub fn remove(&mut self) -> Change<StateId> {
+        self.set_state(self.coord, StateId::AIR)
+    }
+
+    /// Schedules a tick for this block in `delay` ticks.
+    #[inline]
+    pub fn schedule_tick(&mut self, delay: u32) {
+        self.world.schedule_tick(self.coord, delay);
+    }
+
+    #[inline]
+    pub fn schedule_tick_at(&mut self, coord: IVec3, delay: u32) {
+        self.world.schedule_tick(coord, delay);
+    }
+}
@@ -0,0 +1,250 @@
+use serde::{Serialize, Deserialize};
+
+use super::error::{Error, Result};
+use super::id::StateId;
+
+/// The highest light level, used for [BlockMaterial::light_emission] and [BlockMaterial::light_opacity].
+pub const MAX_LIGHT: u8 = 15;
+
+/// How a block lets light and the faces behind it through.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transparency {
+    /// Fully solid, hides the faces behind it.
+    #[default]
+    Opaque,
+    /// Either fully solid or fully see-through per pixel, such as leaves.
+    Cutout,
+    /// Partially see-through, such as glass or water. Needs to be sorted when rendered.
+    Transparent,
+}
+
+/// The physical properties of a block state that are used by lighting, meshing and physics.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BlockMaterial {
+    /// The light level that the block emits, from 0 to 15.
+    pub light_emission: u8,
+    /// How much light is absorbed by the block, from 0 (transparent) to 15 (opaque).
+    pub light_opacity: u8,
+    pub transparency: Transparency,
+    /// How slippery the block is to walk on. Lower values are more slippery.
+    pub friction: f32,
+    /// How long the block takes to break. Negative values can't be broken.
+    pub hardness: f32,
+    pub blast_resistance: f32,
+}
+
+impl Default for BlockMaterial {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl BlockMaterial {
+    /// A solid block like stone.
+    pub const DEFAULT: BlockMaterial = BlockMaterial {
+        light_emission: 0,
+        light_opacity: MAX_LIGHT,
+        transparency: Transparency::Opaque,
+        friction: 0.6,
+        hardness: 1.0,
+        blast_resistance: 1.0,
+    };
+
+    pub const AIR: BlockMaterial = BlockMaterial {
+        light_emission: 0,
+        light_opacity: 0,
+        transparency: Transparency::Transparent,
+        friction: 0.6,
+        hardness: 0.0,
+        blast_resistance: 0.0,
+    };
+
+    /// Checks that the light levels are at most [MAX_LIGHT] and that the numbers are finite.
+    /// Returns the name of the invalid field and the reason.
+    pub fn validate(&self) -> std::result::Result<(), (&'static str, String)> {
+        for (field, level) in [("light_emission", self.light_emission), ("light_opacity", self.light_opacity)] {
+            if level > MAX_LIGHT {
+                return Err((field, format!("must be at most {MAX_LIGHT}")));
+            }
+        }
+        for (field, value) in [("friction", self.friction), ("hardness", self.hardness), ("blast_resistance", self.blast_resistance)] {
+            if !value.is_finite() {
+                return Err((field, "must be finite".to_owned()));
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn is_opaque(&self) -> bool {
+        self.transparency == Transparency::Opaque
+    }
+}
+
+/// The [BlockMaterial] of every state, split into one flat array per field and indexed by [StateId].
+/// Lighting only reads the light levels, so they're kept packed together rather than spread out among the other fields.
+#[derive(Debug, Default, Clone)]
+pub(super) struct MaterialTable {
+    light_emission: Vec<u8>,
+    light_opacity: Vec<u8>,
+    transparency: Vec<Transparency>,
+    friction: Vec<f32>,
+    hardness: Vec<f32>,
+    blast_resistance: Vec<f32>,
+}
+
+impl MaterialTable {
+    pub(super) fn push(&mut self, material: BlockMaterial) {
+        self.light_emission.push(material.light_emission);
+        self.light_opacity.push(material.light_opacity);
+        self.transparency.push(material.transparency);
+        self.friction.push(material.friction);
+        self.hardness.push(material.hardness);
+        self.blast_resistance.push(material.blast_resistance);
+    }
+
+    pub(super) fn set(&mut self, id: StateId, material: BlockMaterial) {
+        let index = id.index();
+        self.light_emission[index] = material.light_emission;
+        self.light_opacity[index] = material.light_opacity;
+        self.transparency[index] = material.transparency;
+        self.friction[index] = material.friction;
+        self.hardness[index] = material.hardness;
+        self.blast_resistance[index] = material.blast_resistance;
+    }
+
+    pub(super) fn get(&self, id: StateId) -> BlockMaterial {
+        let index = id.index();
+        BlockMaterial {
+            light_emission: self.light_emission[index],
+            light_opacity: self.light_opacity[index],
+            transparency: self.transparency[index],
+            friction: self.friction[index],
+            hardness: self.hardness[index],
+            blast_resistance: self.blast_resistance[index],
+        }
+    }
+
+    #[inline]
+    pub(super) fn light_emission(&self) -> &[u8] {
+        &self.light_emission
+    }
+
+    #[inline]
+    pub(super) fn light_opacity(&self) -> &[u8] {
+        &self.light_opacity
+    }
+
+    #[inline]
+    pub(super) fn transparency(&self) -> &[Transparency] {
+        &self.transparency
+    }
+
+    #[inline]
+    pub(super) fn friction(&self) -> &[f32] {
+        &self.friction
+    }
+
+    #[inline]
+    pub(super) fn hardness(&self) -> &[f32] {
+        &self.hardness
+    }
+
+    #[inline]
+    pub(super) fn blast_resistance(&self) -> &[f32] {
+        &self.blast_resistance
+    }
+}
+
+impl FromIterator<BlockMaterial> for MaterialTable {
+    fn from_iter<It: IntoIterator<Item = BlockMaterial>>(materials: It) -> Self {
+        let mut table = Self::default();
+        for material in materials {
+            table.push(material);
+        }
+        table
+    }
+}
+
+/// Validates `material` for the block `block_name`.
+pub(super) fn validate(block_name: &str, material: &BlockMaterial) -> Result<()> {
+    material.validate().map_err(|(field, reason)| Error::InvalidMaterial(block_name.to_owned(), format!("{field} {reason}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockstate;
+    use crate::voxel::block::block::BlockBehavior;
+    use crate::voxel::block::block_registry::BlockRegistry;
+    use crate::voxel::block::block_schema::PropertyDef;
+    use crate::voxel::block::block_state::BlockState;
+
+    struct Lamp;
+    impl BlockBehavior for Lamp {
+        fn name(&self) -> &str {
+            "lamp"
+        }
+
+        fn properties(&self) -> Vec<PropertyDef> {
+            vec![PropertyDef::bool("lit", false)]
+        }
+
+        fn material(&self, state: &BlockState) -> BlockMaterial {
+            BlockMaterial {
+                light_emission: if state["lit"] == true.into() { MAX_LIGHT } else { 0 },
+                hardness: 0.3,
+                ..BlockMaterial::DEFAULT
+            }
+        }
+    }
+
+    struct Glow;
+    impl BlockBehavior for Glow {
+        fn name(&self) -> &str {
+            "glow"
+        }
+
+        fn material(&self, _state: &BlockState) -> BlockMaterial {
+            BlockMaterial {
+                light_emission: 20,
+                ..BlockMaterial::DEFAULT
+            }
+        }
+    }
+
+    #[test]
+    fn material_test() {
+        let registry = BlockRegistry::new();
+        registry.register_block(Lamp).unwrap();
+        debug_assert!(matches!(registry.register_block(Glow), Err(Error::InvalidMaterial(..))));
+        let lit = registry.find_state(blockstate!(lamp[lit=true])).unwrap();
+        let unlit = registry.find_state(blockstate!(lamp[lit=false])).unwrap();
+        debug_assert_eq!(registry.material(lit).unwrap().light_emission, MAX_LIGHT);
+        debug_assert_eq!(registry.material(unlit).unwrap().light_emission, 0);
+        debug_assert_eq!(registry.material(StateId::AIR).unwrap(), BlockMaterial::AIR);
+
+        // Overriding a single state.
+        let glass = BlockMaterial {
+            light_opacity: 0,
+            transparency: Transparency::Transparent,
+            ..registry.material(unlit).unwrap()
+        };
+        registry.set_material(unlit, glass).unwrap();
+        debug_assert_eq!(registry.material(unlit).unwrap(), glass);
+        debug_assert!(registry.set_material(unlit, BlockMaterial { light_opacity: 16, ..glass }).is_err());
+
+        let frozen = registry.freeze().unwrap();
+        debug_assert!(matches!(registry.set_material(lit, glass), Err(Error::RegistryFrozen)));
+        debug_assert_eq!(frozen.light_emission(lit), MAX_LIGHT);
+        debug_assert_eq!(frozen.light_opacity(lit), MAX_LIGHT);
+        debug_assert_eq!(frozen.light_opacity(unlit), 0);
+        debug_assert_eq!(frozen.transparency(unlit), Transparency::Transparent);
+        debug_assert_eq!(frozen.hardness(lit), 0.3);
+        debug_assert_eq!(frozen.friction(lit), BlockMaterial::DEFAULT.friction);
+        debug_assert_eq!(frozen.blast_resistance(StateId::AIR), 0.0);
+        debug_assert_eq!(frozen.material(unlit), glass);
+        debug_assert_eq!(frozen.light_emission_table().len(), frozen.state_count());
+    }
+}
@@ -0,0 +1,222 @@
+use serde::{Serialize, Deserialize};
+
+use crate::io::*;
+
+use super::block_property::BlockProperty;
+use super::block_state::BlockState;
+use super::error::{Error, Result};
+
+/// The version of the block data in a save, which decides which [MigrationRule]s are run when it's loaded.
+///
+/// Saves should store the [BlockRegistry::data_version](super::block_registry::BlockRegistry::data_version)
+/// they were written with next to their [BlockPalette](super::block_palette::BlockPalette).
+/// Version 0 is data from before any migration was added.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct DataVersion(pub u32);
+
+impl DataVersion {
+    pub const INITIAL: DataVersion = DataVersion(0);
+}
+
+impl Readable for DataVersion {
+    fn read_from<R: std::io::Read>(reader: &mut R) -> crate::prelude::VoxelResult<Self> {
+        Ok(DataVersion(u32::read_from(reader)?))
+    }
+}
+
+impl Writeable for DataVersion {
+    fn write_to<W: std::io::Write>(&self, writer: &mut W) -> crate::prelude::VoxelResult<u64> {
+        self.0.write_to(writer)
+    }
+}
+
+/// A change to saved [BlockState]s, such as a block being renamed or split into several blocks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MigrationRule {
+    /// Renames the block `from` to `to`, keeping its properties.
+    Rename {
+        from: String,
+        to: String,
+    },
+    /// Replaces states of the block `when.name()` that have every property in `when` with `to`.
+    /// The state's other properties are kept unless `to` sets them, so `when` only needs the properties that decide the replacement.
+    ///
+    /// For example, `log[wood="oak"]` to `oak_log` splits a `log` block into one block per wood type, keeping `axis`.
+    Replace {
+        when: BlockState,
+        to: BlockState,
+    },
+}
+
+impl MigrationRule {
+    /// Applies the rule to `state`, returning `None` if the rule doesn't match the state.
+    pub fn apply(&self, state: &BlockState) -> Option<BlockState> {
+        match self {
+            MigrationRule::Rename { from, to } => {
+                (state.name() == from).then(|| BlockState::new(to.clone(), state.properties().iter().cloned()))
+            }
+            MigrationRule::Replace { when, to } => {
+                if state.name() != when.name() {
+                    return None;
+                }
+                if !when.properties().iter().all(|prop| state.get_property(prop.name()) == prop.value()) {
+                    return None;
+                }
+                let kept = state.properties().iter().filter(|prop| {
+                    when.get_property(prop.name()).is_null() && to.get_property(prop.name()).is_null()
+                }).cloned();
+                Some(BlockState::new(to.name(), to.properties().iter().cloned().chain(kept).collect::<Vec<BlockProperty>>()))
+            }
+        }
+    }
+}
+
+/// The [MigrationRule]s of a registry, ordered by the [DataVersion] that they were added in.
+#[derive(Debug, Default, Clone)]
+pub struct MigrationTable {
+    rules: Vec<(DataVersion, MigrationRule)>,
+}
+
+impl MigrationTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule that is run on data saved before `version`.
+    /// Rules of the same version run in the order they were added.
+    pub fn add(&mut self, version: DataVersion, rule: MigrationRule) -> Result<()> {
+        if version == DataVersion::INITIAL {
+            return Err(Error::InvalidMigration("migrations must have a version greater than 0".to_owned()));
+        }
+        if let MigrationRule::Rename { from, to } = &rule {
+            if from == to {
+                return Err(Error::InvalidMigration(format!("\"{from}\" is renamed to itself")));
+            }
+        }
+        // Insert after every rule with the same or an older version.
+        let index = self.rules.partition_point(|(rule_version, _)| *rule_version <= version);
+        self.rules.insert(index, (version, rule));
+        Ok(())
+    }
+
+    /// The version of the newest rule, which is the version that data is saved with.
+    pub fn current_version(&self) -> DataVersion {
+        self.rules.last().map(|(version, _)| *version).unwrap_or(DataVersion::INITIAL)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Runs every rule newer than `version` on `state` in order.
+    /// Returns `None` if no rule changed the state.
+    pub fn migrate(&self, state: &BlockState, version: DataVersion) -> Option<BlockState> {
+        let start = self.rules.partition_point(|(rule_version, _)| *rule_version <= version);
+        let mut migrated: Option<BlockState> = None;
+        for (_, rule) in &self.rules[start..] {
+            if let Some(next) = rule.apply(migrated.as_ref().unwrap_or(state)) {
+                migrated = Some(next);
+            }
+        }
+        migrated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::blockstate;
+    use crate::math::axis::Axis;
+    use crate::voxel::block::block::BlockBehavior;
+    use crate::voxel::block::block_palette::{BlockPalette, MissingBlockPolicy};
+    use crate::voxel::block::block_registry::BlockRegistry;
+    use crate::voxel::block::block_schema::PropertyDef;
+    use crate::voxel::block::id::StateId;
+
+    struct StoneBrick;
+    impl BlockBehavior for StoneBrick {
+        fn name(&self) -> &str {
+            "stone_brick"
+        }
+    }
+
+    struct Log(&'static str);
+    impl BlockBehavior for Log {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn properties(&self) -> Vec<PropertyDef> {
+            vec![PropertyDef::axis("axis", Axis::Y)]
+        }
+    }
+
+    #[test]
+    fn migration_table_test() {
+        let mut table = MigrationTable::new();
+        debug_assert_eq!(table.current_version(), DataVersion::INITIAL);
+        table.add(DataVersion(2), MigrationRule::Rename { from: "bricks".into(), to: "stone_bricks".into() }).unwrap();
+        table.add(DataVersion(1), MigrationRule::Rename { from: "brick".into(), to: "bricks".into() }).unwrap();
+        table.add(DataVersion(3), MigrationRule::Rename { from: "stone_bricks".into(), to: "stone_brick".into() }).unwrap();
+        debug_assert!(table.add(DataVersion(0), MigrationRule::Rename { from: "a".into(), to: "b".into() }).is_err());
+        debug_assert_eq!(table.current_version(), DataVersion(3));
+
+        // Rules are chained in version order.
+        debug_assert_eq!(table.migrate(&blockstate!(brick), DataVersion(0)), Some(blockstate!(stone_brick)));
+        debug_assert_eq!(table.migrate(&blockstate!(bricks), DataVersion(1)), Some(blockstate!(stone_brick)));
+        // Data saved at version 2 already renamed `bricks`, so only the last rename runs.
+        debug_assert_eq!(table.migrate(&blockstate!(bricks), DataVersion(2)), None);
+        debug_assert_eq!(table.migrate(&blockstate!(stone_bricks), DataVersion(2)), Some(blockstate!(stone_brick)));
+        debug_assert_eq!(table.migrate(&blockstate!(stone_bricks), DataVersion(3)), None);
+
+        let split = MigrationRule::Replace {
+            when: blockstate!(log[wood="oak"]),
+            to: blockstate!(oak_log),
+        };
+        debug_assert_eq!(split.apply(&blockstate!(log[wood="oak", axis=Axis::X])), Some(blockstate!(oak_log[axis=Axis::X])));
+        debug_assert_eq!(split.apply(&blockstate!(log[wood="birch", axis=Axis::X])), None);
+    }
+
+    #[test]
+    fn migrate_on_load_test() {
+        // Saved before `stone_bricks` was renamed and `log` was split.
+        let saved = BlockPalette::new([
+            blockstate!(air),
+            blockstate!(stone_bricks),
+            blockstate!(log[wood="oak", axis=Axis::X]),
+            blockstate!(log[wood="birch", axis=Axis::Z]),
+        ]);
+        let mut buffer = Cursor::new(Vec::new());
+        DataVersion(1).write_to(&mut buffer).unwrap();
+        saved.write_to(&mut buffer).unwrap();
+
+        let registry = BlockRegistry::new();
+        registry.register_block(StoneBrick).unwrap();
+        registry.register_block(Log("oak_log")).unwrap();
+        registry.register_block(Log("birch_log")).unwrap();
+        registry.add_migration(DataVersion(2), MigrationRule::Rename { from: "stone_bricks".into(), to: "stone_brick".into() }).unwrap();
+        for wood in ["oak", "birch"] {
+            registry.add_migration(DataVersion(3), MigrationRule::Replace {
+                when: blockstate!(log[wood=wood]),
+                to: BlockState::new(format!("{wood}_log"), []),
+            }).unwrap();
+        }
+        debug_assert_eq!(registry.data_version().unwrap(), DataVersion(3));
+
+        buffer.set_position(0);
+        let version = DataVersion::read_from(&mut buffer).unwrap();
+        let palette = BlockPalette::read_from(&mut buffer).unwrap();
+        let remap = registry.remap(&palette, version, MissingBlockPolicy::Fail).unwrap();
+        let mut ids = [StateId(0), StateId(1), StateId(2), StateId(3)];
+        remap.apply(&mut ids).unwrap();
+        debug_assert_eq!(*registry.get_state(ids[1]).unwrap(), blockstate!(stone_brick));
+        debug_assert_eq!(*registry.get_state(ids[2]).unwrap(), blockstate!(oak_log[axis=Axis::X]));
+        debug_assert_eq!(*registry.get_state(ids[3]).unwrap(), blockstate!(birch_log[axis=Axis::Z]));
+
+        // Data that is already at the current version isn't migrated.
+        debug_assert!(registry.remap(&palette, DataVersion(3), MissingBlockPolicy::Fail).is_err());
+    }
+}
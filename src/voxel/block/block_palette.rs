@@ -0,0 +1,261 @@
+use crate::io::*;
+
+use super::block_state::BlockState;
+use super::error::{Error, Result};
+use super::id::StateId;
+
+/// The [BlockState] of every [StateId] in a [BlockRegistry](super::block_registry::BlockRegistry), indexed by [StateId].
+///
+/// [StateId]s are assigned in registration order, so they change whenever blocks are added or registered
+/// in a different order. The palette is saved along with the ids so that they can be translated with
+/// [BlockRegistry::remap](super::block_registry::BlockRegistry::remap) when they are loaded.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BlockPalette {
+    states: Vec<BlockState>,
+}
+
+impl BlockPalette {
+    #[inline]
+    pub fn new<It: IntoIterator<Item = BlockState>>(states: It) -> Self {
+        Self {
+            states: states.into_iter().collect(),
+        }
+    }
+
+    #[inline]
+    pub fn states(&self) -> &[BlockState] {
+        &self.states
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    #[inline]
+    pub fn get(&self, id: StateId) -> Option<&BlockState> {
+        self.states.get(id.index())
+    }
+}
+
+impl Readable for BlockPalette {
+    fn read_from<R: std::io::Read>(reader: &mut R) -> crate::prelude::VoxelResult<Self> {
+        let length = u32::read_from(reader)?;
+        let mut states = Vec::with_capacity((length as usize).min(MAX_PREALLOCATED_STATES));
+        for _ in 0..length {
+            states.push(BlockState::read_from(reader)?);
+        }
+        Ok(Self {
+            states,
+        })
+    }
+}
+
+impl Writeable for BlockPalette {
+    fn write_to<W: std::io::Write>(&self, writer: &mut W) -> crate::prelude::VoxelResult<u64> {
+        let mut length = (self.states.len() as u32).write_to(writer)?;
+        for state in self.states.iter() {
+            length += state.write_to(writer)?;
+        }
+        Ok(length)
+    }
+}
+
+/// The length prefix of a palette comes from the file, so don't trust it for large allocations.
+const MAX_PREALLOCATED_STATES: usize = 4096;
+
+/// What [BlockRegistry::remap](super::block_registry::BlockRegistry::remap) does with saved states of blocks that aren't registered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MissingBlockPolicy {
+    /// Replace the state with the given state.
+    Placeholder(StateId),
+    /// Fail with [Error::BlockNotFound].
+    Fail,
+    /// Add the saved [BlockState] to the registry as the only state of an [UnknownBlock](super::blocks::UnknownBlock).
+    /// The state keeps its [StateId] for every palette it is in, and is written back unchanged with the registry's palette.
+    /// 
+    /// The registry must not be frozen yet.
+    KeepRaw,
+}
+
+/// Translates [StateId]s saved with a [BlockPalette] into the [StateId]s of the current registry.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StateRemap {
+    /// Indexed by the saved [StateId].
+    table: Vec<StateId>,
+    /// Saved [StateId]s whose block isn't registered.
+    missing: Vec<StateId>,
+}
+
+impl StateRemap {
+    #[inline]
+    pub(crate) fn new(table: Vec<StateId>, missing: Vec<StateId>) -> Self {
+        Self {
+            table,
+            missing,
+        }
+    }
+
+    /// Gets the current [StateId] for the saved `id`, or `None` if `id` isn't in the palette.
+    #[inline]
+    pub fn get(&self, id: StateId) -> Option<StateId> {
+        self.table.get(id.index()).copied()
+    }
+
+    /// Saved [StateId]s whose block isn't registered, which were handled by the [MissingBlockPolicy].
+    #[inline]
+    pub fn missing(&self) -> &[StateId] {
+        &self.missing
+    }
+
+    /// Returns `true` if every saved [StateId] maps to itself, in which case nothing needs to be remapped.
+    pub fn is_identity(&self) -> bool {
+        self.table.iter().enumerate().all(|(index, id)| id.index() == index)
+    }
+
+    /// Remaps every id in `ids` in place.
+    /// Fails with [Error::StateNotInPalette] (leaving `ids` partially remapped) if an id isn't in the palette.
+    pub fn apply(&self, ids: &mut [StateId]) -> Result<()> {
+        for id in ids.iter_mut() {
+            *id = self.get(*id).ok_or(Error::StateNotInPalette(id.0))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::blockstate;
+    use crate::voxel::block::block::BlockBehavior;
+    use crate::voxel::block::block_migration::DataVersion;
+    use crate::voxel::block::block_registry::BlockRegistry;
+    use crate::voxel::block::block_schema::PropertyDef;
+    use crate::voxel::cardinal::Cardinal;
+
+    struct Door;
+    impl BlockBehavior for Door {
+        fn name(&self) -> &str {
+            "door"
+        }
+
+        fn properties(&self) -> Vec<PropertyDef> {
+            vec![PropertyDef::cardinal("facing", Cardinal::North), PropertyDef::bool("open", false)]
+        }
+    }
+
+    struct Lamp;
+    impl BlockBehavior for Lamp {
+        fn name(&self) -> &str {
+            "lamp"
+        }
+
+        fn properties(&self) -> Vec<PropertyDef> {
+            vec![PropertyDef::bool("lit", false)]
+        }
+    }
+
+    struct Stone;
+    impl BlockBehavior for Stone {
+        fn name(&self) -> &str {
+            "stone"
+        }
+    }
+
+    #[test]
+    fn palette_remap_test() {
+        let saved = BlockRegistry::new();
+        saved.register_block(Door).unwrap();
+        saved.register_block(Lamp).unwrap();
+        saved.register_block(Stone).unwrap();
+        let door = saved.find_state(blockstate!(door[facing=Cardinal::East, open=true])).unwrap();
+        let lamp = saved.find_state(blockstate!(lamp[lit=true])).unwrap();
+        let stone = saved.find_state(blockstate!(stone)).unwrap();
+
+        let mut buffer = Cursor::new(Vec::new());
+        saved.palette().unwrap().write_to(&mut buffer).unwrap();
+        buffer.set_position(0);
+        let palette = BlockPalette::read_from(&mut buffer).unwrap();
+        debug_assert_eq!(palette, saved.palette().unwrap());
+
+        // Registered in a different order and without stone.
+        let current = BlockRegistry::new();
+        current.register_block(Lamp).unwrap();
+        current.register_block(Door).unwrap();
+        let remap = current.remap(&palette, DataVersion::INITIAL, MissingBlockPolicy::Placeholder(StateId::AIR)).unwrap();
+        debug_assert!(!remap.is_identity());
+        debug_assert_eq!(remap.missing(), &[stone]);
+        let mut ids = [StateId::AIR, door, lamp, stone];
+        remap.apply(&mut ids).unwrap();
+        debug_assert_eq!(ids[0], StateId::AIR);
+        debug_assert_eq!(*current.get_state(ids[1]).unwrap(), blockstate!(door[facing=Cardinal::East, open=true]));
+        debug_assert_eq!(*current.get_state(ids[2]).unwrap(), blockstate!(lamp[lit=true]));
+        debug_assert_eq!(ids[3], StateId::AIR);
+        debug_assert!(matches!(remap.apply(&mut [StateId(u32::MAX)]), Err(Error::StateNotInPalette(..))));
+
+        debug_assert!(matches!(
+            current.remap(&palette, DataVersion::INITIAL, MissingBlockPolicy::Fail),
+            Err(Error::BlockNotFound(name)) if name == "stone"
+        ));
+
+        let same = saved.remap(&palette, DataVersion::INITIAL, MissingBlockPolicy::Fail).unwrap();
+        debug_assert!(same.is_identity());
+
+        // Properties that were removed or whose value is no longer allowed fall back to the default.
+        let old = BlockPalette::new([blockstate!(air), blockstate!(lamp[lit=true, color="red"]), blockstate!(lamp[lit=3i64])]);
+        let remap = current.remap(&old, DataVersion::INITIAL, MissingBlockPolicy::Fail).unwrap();
+        debug_assert_eq!(*current.get_state(remap.get(StateId(1)).unwrap()).unwrap(), blockstate!(lamp[lit=true]));
+        debug_assert_eq!(*current.get_state(remap.get(StateId(2)).unwrap()).unwrap(), blockstate!(lamp[lit=false]));
+    }
+
+    #[test]
+    fn palette_keep_raw_test() {
+        let saved = BlockRegistry::new();
+        saved.register_block(Stone).unwrap();
+        saved.register_block(Lamp).unwrap();
+        let lamp = saved.find_state(blockstate!(lamp[lit=true])).unwrap();
+        let palette = saved.palette().unwrap();
+
+        // Without stone, the saved id of stone is the id of unlit lamp in the current registry.
+        let current = BlockRegistry::new();
+        current.register_block(Lamp).unwrap();
+        let remap = current.remap(&palette, DataVersion::INITIAL, MissingBlockPolicy::KeepRaw).unwrap();
+        let unlit = current.find_state(blockstate!(lamp[lit=false])).unwrap();
+        let stone = remap.get(StateId(1)).unwrap();
+        debug_assert_eq!(unlit, StateId(1));
+        debug_assert_ne!(stone, unlit);
+        debug_assert_eq!(remap.missing(), &[StateId(1)]);
+        debug_assert_eq!(*current.get_state(remap.get(lamp).unwrap()).unwrap(), blockstate!(lamp[lit=true]));
+        // The kept state is a real state of the registry, so it is in range of every lookup.
+        debug_assert_eq!(*current.get_state(stone).unwrap(), blockstate!(stone));
+        debug_assert_eq!(current.get_block(stone).unwrap().name(), "stone");
+        debug_assert!(current.collision_shape(stone).is_ok());
+        debug_assert_eq!(current.palette().unwrap().get(stone), Some(&blockstate!(stone)));
+
+        // Another palette with stone at a different id gets the same id for it.
+        let other = BlockPalette::new([blockstate!(air), blockstate!(lamp[lit=false]), blockstate!(glass), blockstate!(stone)]);
+        let other_remap = current.remap(&other, DataVersion::INITIAL, MissingBlockPolicy::KeepRaw).unwrap();
+        debug_assert_eq!(other_remap.get(StateId(3)), Some(stone));
+        let glass = other_remap.get(StateId(2)).unwrap();
+        debug_assert_ne!(glass, stone);
+        debug_assert_eq!(other_remap.missing(), &[StateId(2), StateId(3)]);
+        // Kept states are still missing blocks for the other policies.
+        let placeholder = current.remap(&other, DataVersion::INITIAL, MissingBlockPolicy::Placeholder(StateId::AIR)).unwrap();
+        debug_assert_eq!(placeholder.get(StateId(3)), Some(StateId::AIR));
+
+        // Saving with the registry's palette and loading it again keeps the stone.
+        let reloaded = current.remap(&current.palette().unwrap(), DataVersion::INITIAL, MissingBlockPolicy::KeepRaw).unwrap();
+        debug_assert!(reloaded.is_identity());
+        let frozen = current.freeze().unwrap();
+        debug_assert_eq!(*frozen.get_state(glass), blockstate!(glass));
+        debug_assert!(matches!(current.remap(&palette, DataVersion::INITIAL, MissingBlockPolicy::KeepRaw), Err(Error::RegistryFrozen)));
+        debug_assert!(matches!(current.get_state(StateId(u32::MAX)), Err(Error::StateNotFound(u32::MAX))));
+    }
+}
@@ -38,6 +38,8 @@ table!(macro property_table {
     */
 });
 
+pub(crate) use property_table;
+
 const PROPERTY_ARRAY_ID: u8 = 28;
 
 macro_rules! build_property_enum {
@@ -192,6 +194,11 @@ property_table!(build_property_enum);
 impl Property {
     pub const NULL: Property = Property::Null;
 
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        matches!(self, Property::Null)
+    }
+
     pub fn get_property(&self, name: &str) -> &Property {
         if let Self::Map(map) = self {
             map.get(name).unwrap_or_else(|| &Property::NULL)
@@ -348,7 +355,7 @@ impl Writeable for BlockProperty {
 impl PartialOrd for BlockProperty {
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        other.name().partial_cmp(self.name())
+        Some(self.cmp(other))
     }
 }
 
@@ -1,9 +1,22 @@
 use hashbrown::HashMap;
+use std::ops::Range;
+use std::path::Path;
 use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
-use super::blocks::AirBlock;
+use super::blocks::{AirBlock, UnknownBlock};
+use super::block_property::Property;
+use super::block_migration::{DataVersion, MigrationRule, MigrationTable};
+use super::block_palette::{BlockPalette, MissingBlockPolicy, StateRemap};
+use super::block_material::{self, BlockMaterial, MaterialTable};
+use super::block_schema::BlockSchema;
+use super::block_shape::{BlockShape, ShapeCache, StateShapes};
+use super::block_transform;
+use super::block_tag::{self, BlockSet, TagEntry, TagFile, TagResolver};
+use super::frozen_registry::{FrozenBlockRegistry, FrozenInner};
 use super::block_state::{BlockState, blockstate};
 use super::{block::BlockBehavior, id::{BlockId, StateId}};
 use super::error::{Error, Result};
+use crate::util::identifier::{Identifier, IdentifierMap};
+use crate::voxel::orientation::Orientation;
 
 // main
 
@@ -11,36 +24,136 @@ use super::error::{Error, Result};
 /// `blocks` represents the list of [BlockBehavior]s (the trait that has functions for blocks).
 /// 
 /// `block_lookup` allows for looking up a [BlockBehavior]'s [BlockId], which points
-/// back to the [BlockBehavior] in `blocks`.
+/// back to the [BlockBehavior] in `blocks`. Blocks are keyed by [Identifier], so `lamp` and
+/// `hexahedron:lamp` are the same block when the registry's default namespace is `hexahedron`.
+/// 
+/// `schemas` contains the [BlockSchema] of each block, indexed by [BlockId].
+/// 
+/// `block_states` contains the range of [StateId]s that belong to each block, indexed by [BlockId].
+/// Every valid state of a block is registered when the block is registered, so the range is contiguous.
 /// 
 /// `states` contains each [BlockState] contained in this registry.
 /// 
 /// `block_ids` contains the [BlockId]s associated with each state.
 /// 
 /// `state_lookup` is a lookup table of [BlockState]s that will return its [StateId].
+/// 
+/// `shapes` contains the oriented collision and outline shapes of each state, indexed by [StateId].
+/// 
+/// `materials` contains the [BlockMaterial] of each state in flat arrays, indexed by [StateId].
+/// 
+/// `tags` contains the entries of each block tag. Tags are resolved into [BlockSet]s when they are used.
+/// 
+/// `migrations` contains the rules that update [BlockState]s saved with an older [DataVersion] when they are loaded.
+/// 
+/// `frozen` is set by [BlockRegistry::freeze], after which no more blocks can be registered.
 struct InnerBlockRegistry {
     blocks: Vec<Arc<dyn BlockBehavior>>,
-    block_lookup: HashMap<String, BlockId>,
+    block_lookup: IdentifierMap<BlockId>,
+    schemas: Vec<Arc<BlockSchema>>,
+    block_states: Vec<Range<u32>>,
     states: Vec<Arc<BlockState>>,
     block_ids: Vec<BlockId>,
     state_lookup: HashMap<Arc<BlockState>, StateId>,
+    shapes: Vec<StateShapes>,
+    materials: MaterialTable,
+    tags: HashMap<String, Vec<TagEntry>>,
+    migrations: MigrationTable,
+    frozen: bool,
 }
 
 impl Default for InnerBlockRegistry {
     fn default() -> Self {
-        Self::new()
+        Self::new(IdentifierMap::default())
     }
 }
 
 impl InnerBlockRegistry {
-    fn new() -> Self {
+    fn new(mut block_lookup: IdentifierMap<BlockId>) -> Self {
         let air_state = Arc::new(blockstate!(air));
+        // `air` is always a valid path, so this can't fail.
+        if let Ok(air) = block_lookup.resolve("air") {
+            block_lookup.insert(air, BlockId(0));
+        }
         Self {
             blocks: vec![Arc::new(AirBlock)],
-            block_lookup: HashMap::from([("air".to_owned(), BlockId(0))]),
+            block_lookup,
+            schemas: vec![Arc::new(BlockSchema::empty())],
+            block_states: vec![0..1],
             states: vec![air_state.clone()],
             block_ids: vec![BlockId(0)],
             state_lookup: HashMap::from([(air_state, StateId(0))]),
+            shapes: vec![StateShapes::empty()],
+            materials: MaterialTable::from_iter([BlockMaterial::AIR]),
+            tags: HashMap::new(),
+            migrations: MigrationTable::new(),
+            frozen: false,
+        }
+    }
+
+    fn find_block(&self, name: &str) -> Result<BlockId> {
+        self.block_lookup.get(name).copied().ok_or_else(|| Error::BlockNotFound(name.to_owned()))
+    }
+
+    /// Validates `state` against the schema of its block and finds its [StateId].
+    /// The block name may be written with or without the default namespace.
+    fn find_state(&self, state: &BlockState) -> Result<StateId> {
+        if let Some(&state_id) = self.state_lookup.get(state) {
+            return Ok(state_id);
+        }
+        let block_id = self.find_block(state.name())?;
+        let state = state.with_name(self.blocks[block_id.index()].name());
+        let state = self.schemas[block_id.index()].validate(&state)?;
+        // Every valid state was registered along with the block.
+        self.state_lookup.get(&state).copied().ok_or_else(|| Error::BlockNotFound(state.name().to_owned()))
+    }
+
+    /// Finds the [StateId] of `state`, dropping any properties that are unknown or invalid for the block.
+    /// Returns `None` if the block isn't registered, even if `state` was kept with [MissingBlockPolicy::KeepRaw].
+    fn find_closest_state(&self, state: &BlockState) -> Option<StateId> {
+        let block_id = self.block_lookup.get(state.name())?;
+        if let Some(&state_id) = self.state_lookup.get(state) {
+            return Some(state_id);
+        }
+        let schema = &self.schemas[block_id.index()];
+        let state = BlockState::new(state.name(), state.properties().iter().filter(|prop| {
+            schema.get(prop.name()).is_some_and(|def| def.values().contains(prop.value()))
+        }).cloned());
+        self.find_state(&state).ok()
+    }
+
+    /// Finds the [StateId] of `state`, which belongs to a block that isn't registered, adding it as the only state
+    /// of an [UnknownBlock] if it hasn't been kept before. The id is the same for every palette that has `state`.
+    fn keep_unknown_state(&mut self, state: &BlockState) -> Result<StateId> {
+        if let Some(&state_id) = self.state_lookup.get(state) {
+            return Ok(state_id);
+        }
+        if self.blocks.len() >= u32::MAX as usize || self.states.len() >= u32::MAX as usize {
+            return Err(Error::RegistryOverflow);
+        }
+        let block = Arc::new(UnknownBlock::new(state.name()));
+        let shapes = StateShapes::new(block.as_ref(), state, &mut ShapeCache::default());
+        let material = block.material(state);
+        let block_id = BlockId(self.blocks.len() as u32);
+        let state_id = StateId(self.states.len() as u32);
+        let state = Arc::new(state.clone());
+        self.blocks.push(block);
+        self.schemas.push(Arc::new(BlockSchema::empty()));
+        self.block_states.push(state_id.0..state_id.0 + 1);
+        self.block_ids.push(block_id);
+        self.state_lookup.insert(state.clone(), state_id);
+        self.states.push(state);
+        self.shapes.push(shapes);
+        self.materials.push(material);
+        Ok(state_id)
+    }
+
+    #[inline]
+    fn state_index(&self, id: StateId) -> Result<usize> {
+        if id.index() < self.states.len() {
+            Ok(id.index())
+        } else {
+            Err(Error::StateNotFound(id.0))
         }
     }
 }
@@ -50,7 +163,14 @@ pub struct BlockRegistry(Arc<RwLock<InnerBlockRegistry>>);
 
 impl BlockRegistry {
     pub fn new() -> Self {
-        Self(Arc::new(RwLock::new(InnerBlockRegistry::new())))
+        Self::default()
+    }
+
+    /// Creates a registry where block names without a `namespace:` prefix are in `namespace`.
+    /// [BlockRegistry::new] uses [DEFAULT_NAMESPACE](crate::util::identifier::DEFAULT_NAMESPACE).
+    pub fn with_namespace(namespace: &str) -> Result<Self> {
+        let lookup = IdentifierMap::new(namespace)?;
+        Ok(Self(Arc::new(RwLock::new(InnerBlockRegistry::new(lookup)))))
     }
 
     #[inline]
@@ -63,42 +183,264 @@ impl BlockRegistry {
         self.0.write().map_err(|_| Error::FailedToLock)
     }
 
+    /// Registers a block along with every valid state of that block.
     pub fn register_block<B: BlockBehavior + 'static + Sized>(&self, block: B) -> Result<BlockId> {
+        let schema = BlockSchema::new(block.name(), block.properties())?;
+        let state_count = schema.state_count()?;
+        let block = Arc::new(block);
+        // Shapes and materials are computed before locking in case the block uses the registry.
+        let mut shape_cache = ShapeCache::default();
+        let states = schema.enumerate(block.name()).map(|state| {
+            let shapes = StateShapes::new(block.as_ref(), &state, &mut shape_cache);
+            let material = block.material(&state);
+            block_material::validate(block.name(), &material)?;
+            Ok((Arc::new(state), shapes, material))
+        }).collect::<Result<Vec<_>>>()?;
         let mut reg = self.write_lock()?;
+        if reg.frozen {
+            return Err(Error::RegistryFrozen);
+        }
         if reg.blocks.len() > u32::MAX as usize {
             return Err(Error::RegistryOverflow);
         }
-        if reg.block_lookup.contains_key(block.name()) {
-            return Err(Error::DuplicateBlockEntry(block.name().to_owned()));
+        let identifier = reg.block_lookup.resolve(block.name())?;
+        if reg.block_lookup.contains(identifier.as_str()) {
+            return Err(Error::DuplicateBlockEntry(identifier.to_string()));
+        }
+        if reg.states.len() + state_count > u32::MAX as usize {
+            return Err(Error::RegistryOverflow);
         }
         let block_id = BlockId(reg.blocks.len() as u32);
-        let block = Arc::new(block);
+        let first_state = reg.states.len() as u32;
+        for (state, shapes, material) in states {
+            let state_id = StateId(reg.states.len() as u32);
+            reg.block_ids.push(block_id);
+            reg.state_lookup.insert(state.clone(), state_id);
+            reg.states.push(state);
+            reg.shapes.push(shapes);
+            reg.materials.push(material);
+        }
         reg.blocks.push(block.clone());
-        reg.block_lookup.insert(block.name().to_owned(), block_id);
+        reg.block_lookup.insert(identifier, block_id);
+        reg.schemas.push(Arc::new(schema));
+        let end_state = reg.states.len() as u32;
+        reg.block_states.push(first_state..end_state);
         drop(reg);
         block.on_register(self);
         Ok(block_id)
     }
 
-    pub fn register_state<S: RefOrOwned<BlockState>>(&self, state: S) -> Result<StateId> {
+    /// Finds the [StateId] of `state`.
+    /// 
+    /// Every valid state is registered along with its block, so this only validates `state`
+    /// against the block's [BlockSchema]. Properties missing from `state` take their default value.
+    pub fn find_state<S: RefOrOwned<BlockState>>(&self, state: S) -> Result<StateId> {
+        let reg = self.read_lock()?;
+        reg.find_state(state.reference())
+    }
+
+    /// Finds the [StateId] of the state that is `id` with the property `name` set to `value`.
+    /// 
+    /// For example: "this state with `facing` set to North".
+    pub fn with_property<S: AsRef<str>, P: Into<Property>>(&self, id: StateId, name: S, value: P) -> Result<StateId> {
+        let reg = self.read_lock()?;
+        let state = reg.states[reg.state_index(id)?].with_property(name.as_ref(), value);
+        reg.find_state(&state)
+    }
+
+    /// Finds the [StateId] of the state `id` after it is rotated or mirrored by `orientation` with [BlockBehavior::transform].
+    /// 
+    /// Properties whose transformed value isn't allowed by the block keep their value,
+    /// such as a `facing` property that only allows horizontal directions being pointed up.
+    /// A [Cardinal](crate::voxel::cardinal::Cardinal) property that would point up or down keeps its value as well.
+    pub fn transform_state(&self, id: StateId, orientation: Orientation) -> Result<StateId> {
+        // The block is called without holding the lock in case it uses the registry.
+        let block = self.get_block(id)?;
+        let state = self.get_state(id)?;
+        let transformed = block.transform(&state, orientation);
+        let reg = self.read_lock()?;
+        let block_id = reg.find_block(transformed.name())?;
+        let transformed = block_transform::keep_allowed(&reg.schemas[block_id.index()], &state, transformed);
+        reg.find_state(&transformed)
+    }
+
+    /// Gets the [StateId] of the state with every property set to its default value.
+    pub fn default_state<I: BlockGetterId>(&self, id: I) -> Result<StateId> {
+        let block_id = id.block_id(self)?;
+        let reg = self.read_lock()?;
+        Ok(StateId(reg.block_states[block_id.index()].start + reg.schemas[block_id.index()].default_index()))
+    }
+
+    /// Gets every [StateId] that belongs to a block.
+    pub fn block_states<I: BlockGetterId>(&self, id: I) -> Result<Vec<StateId>> {
+        let block_id = id.block_id(self)?;
+        let reg = self.read_lock()?;
+        Ok(reg.block_states[block_id.index()].clone().map(StateId).collect())
+    }
+
+    pub fn get_schema<I: BlockGetterId>(&self, id: I) -> Result<Arc<BlockSchema>> {
+        let block_id = id.block_id(self)?;
+        let reg = self.read_lock()?;
+        Ok(Arc::clone(&reg.schemas[block_id.index()]))
+    }
+
+    /// Stops any more blocks from being registered and returns an immutable snapshot of the registry.
+    /// 
+    /// The [FrozenBlockRegistry] doesn't lock, and gives out references rather than cloned [Arc]s,
+    /// so it should be used in hot paths (such as meshing) once startup registration is done.
+    /// Registering a block after freezing fails with [Error::RegistryFrozen].
+    /// 
+    /// Every tag is resolved while freezing, so this fails if a tag is invalid.
+    pub fn freeze(&self) -> Result<FrozenBlockRegistry> {
         let mut reg = self.write_lock()?;
-        if let Some(&state_id) = reg.state_lookup.get(state.reference()) {
-            Ok(state_id)
-        } else {
-            if reg.states.len() > u32::MAX as usize {
-                return Err(Error::RegistryOverflow);
-            }
-            let state = state.owned();
-            let Some(&block_id) = reg.block_lookup.get(state.name()) else {
-                return Err(Error::BlockNotFound(state.name().to_owned()));
+        let tags = TagResolver::new(&reg.tags, &reg.block_lookup, reg.blocks.len()).resolve_all()?;
+        reg.frozen = true;
+        Ok(FrozenBlockRegistry(Arc::new(FrozenInner {
+            blocks: reg.blocks.clone().into_boxed_slice(),
+            block_lookup: reg.block_lookup.clone(),
+            schemas: reg.schemas.clone().into_boxed_slice(),
+            block_states: reg.block_states.clone().into_boxed_slice(),
+            states: reg.states.clone().into_boxed_slice(),
+            block_ids: reg.block_ids.clone().into_boxed_slice(),
+            state_lookup: reg.state_lookup.clone(),
+            shapes: reg.shapes.clone().into_boxed_slice(),
+            materials: reg.materials.clone(),
+            tags,
+        })))
+    }
+
+    /// Returns `true` if [BlockRegistry::freeze] has been called.
+    pub fn is_frozen(&self) -> Result<bool> {
+        let reg = self.read_lock()?;
+        Ok(reg.frozen)
+    }
+
+    /// Adds `entries` to the block tag `name`, creating the tag if it doesn't exist.
+    /// 
+    /// Entries can be blocks or other tags, and can be given as strings where tags start with `#`.
+    /// Entries are only checked when the tag is resolved, so they may refer to blocks and tags that are registered later.
+    pub fn register_tag<S: Into<String>, E: Into<TagEntry>, It: IntoIterator<Item = E>>(&self, name: S, entries: It) -> Result<()> {
+        self.add_tag_entries(name.into(), entries.into_iter().map(E::into), false)
+    }
+
+    fn add_tag_entries<It: IntoIterator<Item = TagEntry>>(&self, name: String, entries: It, replace: bool) -> Result<()> {
+        let mut reg = self.write_lock()?;
+        if reg.frozen {
+            return Err(Error::RegistryFrozen);
+        }
+        let tag = reg.tags.entry(name).or_default();
+        if replace {
+            tag.clear();
+        }
+        tag.extend(entries);
+        Ok(())
+    }
+
+    /// Loads the block tag `name` from the json of a [TagFile].
+    pub fn load_tag_json<S: Into<String>>(&self, name: S, json: &str) -> Result<()> {
+        let name = name.into();
+        let file = TagFile::from_json(json).map_err(|err| Error::InvalidTagFile(name.clone(), err.to_string()))?;
+        self.add_tag_entries(name, file.entries(), file.replace)
+    }
+
+    /// Loads the block tag `name` from a [TagFile].
+    pub fn load_tag_file<S: Into<String>, P: AsRef<Path>>(&self, name: S, path: P) -> Result<()> {
+        let file = block_tag::read_tag_file(path.as_ref())?;
+        self.add_tag_entries(name.into(), file.entries(), file.replace)
+    }
+
+    /// Loads every `.json` [TagFile] under `dir`, returning the number of files that were loaded.
+    /// The name of each tag is its path relative to `dir` without the extension, such as `mineable/pickaxe`.
+    pub fn load_tag_dir<P: AsRef<Path>>(&self, dir: P) -> Result<usize> {
+        let files = block_tag::read_tag_dir(dir.as_ref())?;
+        let count = files.len();
+        for (name, file) in files {
+            self.add_tag_entries(name, file.entries(), file.replace)?;
+        }
+        Ok(count)
+    }
+
+    /// Resolves the block tag `name` into the set of blocks it contains, including the blocks of nested tags.
+    /// 
+    /// This resolves the tag on every call, so hot paths should use the tags of a [FrozenBlockRegistry] instead.
+    pub fn resolve_tag<S: AsRef<str>>(&self, name: S) -> Result<BlockSet> {
+        let reg = self.read_lock()?;
+        TagResolver::new(&reg.tags, &reg.block_lookup, reg.blocks.len()).resolve(name.as_ref())
+    }
+
+    /// Exports the [BlockState] of every registered [StateId] so that saved ids can be remapped with [BlockRegistry::remap].
+    /// This includes the states that were kept with [MissingBlockPolicy::KeepRaw].
+    pub fn palette(&self) -> Result<BlockPalette> {
+        let reg = self.read_lock()?;
+        Ok(BlockPalette::new(reg.states.iter().map(|state| BlockState::clone(state))))
+    }
+
+    /// Creates a [StateRemap] that translates [StateId]s saved with `palette` into [StateId]s of this registry.
+    /// 
+    /// Every migration newer than `version` (the [DataVersion] that the palette was saved with) is run on the saved
+    /// states first, so that renamed and split blocks still load. A palette saved by this registry uses
+    /// [BlockRegistry::data_version].
+    /// 
+    /// States of registered blocks are then matched by name and properties. Properties that the block no longer has,
+    /// or that have a value that is no longer allowed, are replaced with their default. States of blocks
+    /// that aren't registered are handled according to `policy`.
+    /// 
+    /// With [MissingBlockPolicy::KeepRaw], this fails with [Error::RegistryFrozen] once the registry is frozen,
+    /// since the snapshot wouldn't see the kept states.
+    pub fn remap(&self, palette: &BlockPalette, version: DataVersion, policy: MissingBlockPolicy) -> Result<StateRemap> {
+        let mut reg = self.write_lock()?;
+        if policy == MissingBlockPolicy::KeepRaw && reg.frozen {
+            return Err(Error::RegistryFrozen);
+        }
+        let mut table = Vec::with_capacity(palette.len());
+        let mut missing = Vec::new();
+        for (index, state) in palette.states().iter().enumerate() {
+            let saved_id = StateId(index as u32);
+            let migrated = reg.migrations.migrate(state, version);
+            let state = migrated.as_ref().unwrap_or(state);
+            let state_id = match (reg.find_closest_state(state), policy) {
+                (Some(state_id), _) => state_id,
+                (None, MissingBlockPolicy::Placeholder(placeholder)) => {
+                    missing.push(saved_id);
+                    placeholder
+                }
+                (None, MissingBlockPolicy::Fail) => return Err(Error::BlockNotFound(state.name().to_owned())),
+                (None, MissingBlockPolicy::KeepRaw) => {
+                    missing.push(saved_id);
+                    // The saved state is kept as it was saved, not as it was migrated.
+                    reg.keep_unknown_state(&palette.states()[index])?
+                }
             };
-            let state_id = StateId(reg.states.len() as u32);
-            reg.block_ids.push(block_id);
-            let state = Arc::new(state);
-            reg.state_lookup.insert(state.clone(), state_id);
-            reg.states.push(state);
-            Ok(state_id)
+            table.push(state_id);
         }
+        Ok(StateRemap::new(table, missing))
+    }
+
+    /// Adds a rule that updates [BlockState]s saved before `version` when they're loaded with [BlockRegistry::remap].
+    /// 
+    /// Migrations can be added after the registry is frozen, since they only affect loading.
+    pub fn add_migration(&self, version: DataVersion, rule: MigrationRule) -> Result<()> {
+        let mut reg = self.write_lock()?;
+        reg.migrations.add(version, rule)
+    }
+
+    /// The [DataVersion] of the newest migration, which is the version that block data should be saved with.
+    pub fn data_version(&self) -> Result<DataVersion> {
+        let reg = self.read_lock()?;
+        Ok(reg.migrations.current_version())
+    }
+
+    /// The namespace of block names that don't have a `namespace:` prefix.
+    pub fn default_namespace(&self) -> Result<String> {
+        let reg = self.read_lock()?;
+        Ok(reg.block_lookup.default_namespace().to_owned())
+    }
+
+    /// The full `namespace:path` [Identifier] of the block.
+    pub fn identifier<I: BlockGetterId>(&self, id: I) -> Result<Identifier> {
+        let block_id = id.block_id(self)?;
+        let reg = self.read_lock()?;
+        Ok(reg.block_lookup.resolve(reg.blocks[block_id.index()].name())?)
     }
 
     #[inline]
@@ -115,7 +457,7 @@ impl BlockRegistry {
     #[inline]
     pub fn get_state(&self, id: StateId) -> Result<Arc<BlockState>> {
         let reg = self.read_lock()?;
-        Ok(Arc::clone(&reg.states[id.index()]))
+        Ok(Arc::clone(&reg.states[reg.state_index(id)?]))
     }
 
     #[inline]
@@ -124,10 +466,45 @@ impl BlockRegistry {
         Ok(access(state))
     }
 
+    /// The collision shape of the state, rotated by [BlockBehavior::orientation].
+    #[inline]
+    pub fn collision_shape(&self, id: StateId) -> Result<Arc<BlockShape>> {
+        let reg = self.read_lock()?;
+        Ok(Arc::clone(&reg.shapes[reg.state_index(id)?].collision))
+    }
+
+    /// The outline shape of the state, rotated by [BlockBehavior::orientation].
+    #[inline]
+    pub fn outline_shape(&self, id: StateId) -> Result<Arc<BlockShape>> {
+        let reg = self.read_lock()?;
+        Ok(Arc::clone(&reg.shapes[reg.state_index(id)?].outline))
+    }
+
+    /// The [BlockMaterial] of the state.
+    #[inline]
+    pub fn material(&self, id: StateId) -> Result<BlockMaterial> {
+        let reg = self.read_lock()?;
+        reg.state_index(id)?;
+        Ok(reg.materials.get(id))
+    }
+
+    /// Overrides the [BlockMaterial] that [BlockBehavior::material] gave the state.
+    /// Fails with [Error::RegistryFrozen] once the registry is frozen, since the snapshot wouldn't see the change.
+    pub fn set_material(&self, id: StateId, material: BlockMaterial) -> Result<()> {
+        let mut reg = self.write_lock()?;
+        if reg.frozen {
+            return Err(Error::RegistryFrozen);
+        }
+        let block_name = reg.blocks[reg.block_ids[reg.state_index(id)?].index()].name().to_owned();
+        block_material::validate(&block_name, &material)?;
+        reg.materials.set(id, material);
+        Ok(())
+    }
+
     #[inline]
     pub fn block_id(&self, id: StateId) -> Result<BlockId> {
         let reg = self.read_lock()?;
-        let block_id = reg.block_ids[id.index()];
+        let block_id = reg.block_ids[reg.state_index(id)?];
         Ok(block_id)
     }
 }
@@ -140,6 +517,7 @@ mod sealed {
 
 pub trait BlockGetterId: sealed::BlockGetterSeal {
     fn get_block(self, registry: &BlockRegistry) -> Result<Arc<dyn BlockBehavior>>;
+    fn block_id(self, registry: &BlockRegistry) -> Result<BlockId>;
 }
 
 impl BlockGetterId for BlockId {
@@ -147,14 +525,24 @@ impl BlockGetterId for BlockId {
         let reg = registry.read_lock()?;
         Ok(Arc::clone(&reg.blocks[self.index()]))
     }
+
+    #[inline]
+    fn block_id(self, _registry: &BlockRegistry) -> Result<BlockId> {
+        Ok(self)
+    }
 }
 
 impl BlockGetterId for StateId {
     fn get_block(self, registry: &BlockRegistry) -> Result<Arc<dyn BlockBehavior>> {
         let reg = registry.read_lock()?;
-        let block_id = reg.block_ids[self.index()];
+        let block_id = reg.block_ids[reg.state_index(self)?];
         Ok(Arc::clone(&reg.blocks[block_id.index()]))
     }
+
+    #[inline]
+    fn block_id(self, registry: &BlockRegistry) -> Result<BlockId> {
+        registry.block_id(self)
+    }
 }
 
 impl<S: AsRef<str>> BlockGetterId for S {
@@ -166,6 +554,11 @@ impl<S: AsRef<str>> BlockGetterId for S {
             Err(Error::BlockNotFound(self.as_ref().to_owned()))
         }
     }
+
+    fn block_id(self, registry: &BlockRegistry) -> Result<BlockId> {
+        let reg = registry.read_lock()?;
+        reg.find_block(self.as_ref())
+    }
 }
 
 pub trait RefOrOwned<T> {
@@ -205,7 +598,7 @@ impl RefOrOwned<BlockState> for BlockState {
 
 #[cfg(test)]
 mod tests {
-    use std::{cell::RefCell, rc::Rc};
+    use std::sync::atomic::{AtomicBool, Ordering};
 
     use super::*;
     #[test]
@@ -223,25 +616,25 @@ mod tests {
                 "grass"
             }
         }
-        struct DebugBlock(&'static str, Rc<RefCell<bool>>);
+        struct DebugBlock(&'static str, Arc<AtomicBool>);
         impl BlockBehavior for DebugBlock {
             fn name(&self) -> &str {
                 self.0
             }
             fn on_register(&self, _registry: &BlockRegistry) {
-                self.1.replace(true);
+                self.1.store(true, Ordering::Release);
             }
         }
         let _dirt = reg.register_block(DirtBlock)?;
         let _grass = reg.register_block(GrassBlock)?;
         // for on_register call which will replace value with true.
-        let hello_cell = Rc::new(RefCell::new(false));
+        let hello_cell = Arc::new(AtomicBool::new(false));
         let _hello = reg.register_block(DebugBlock("hello", hello_cell.clone()))?;
-        debug_assert!(hello_cell.take());
-        let world_cell = Rc::new(RefCell::new(false));
+        debug_assert!(hello_cell.load(Ordering::Acquire));
+        let world_cell = Arc::new(AtomicBool::new(false));
         let world = reg.register_block(DebugBlock("world", world_cell.clone()))?;
-        debug_assert!(world_cell.take());
-        let hello1 = reg.register_state(BlockState::new("hello", []))?;
+        debug_assert!(world_cell.load(Ordering::Acquire));
+        let hello1 = reg.find_state(BlockState::new("hello", []))?;
         reg.access_state(StateId::AIR, |state| {
             debug_assert_eq!(state.name(), "air");
         })?;
@@ -259,4 +652,85 @@ mod tests {
         })?;
         Ok(())
     }
+
+    #[test]
+    fn schema_registry_test() -> Result<()> {
+        use crate::voxel::block::block_schema::PropertyDef;
+        use crate::voxel::cardinal::Cardinal;
+        struct DoorBlock;
+        impl BlockBehavior for DoorBlock {
+            fn name(&self) -> &str {
+                "door"
+            }
+            fn properties(&self) -> Vec<PropertyDef> {
+                vec![
+                    PropertyDef::cardinal("facing", Cardinal::North),
+                    PropertyDef::bool("open", false),
+                ]
+            }
+        }
+        let reg = BlockRegistry::new();
+        let door = reg.register_block(DoorBlock)?;
+        let states = reg.block_states(door)?;
+        debug_assert_eq!(states.len(), 8);
+        let default = reg.default_state("door")?;
+        debug_assert!(states.contains(&default));
+        reg.access_state(default, |state| {
+            debug_assert_eq!(*state, blockstate!(door[facing=Cardinal::North, open=false]));
+        })?;
+        // Missing properties take their default value.
+        debug_assert_eq!(reg.find_state(blockstate!(door))?, default);
+        let open_west = reg.find_state(blockstate!(door[facing=Cardinal::West, open=true]))?;
+        debug_assert!(states.contains(&open_west));
+        let open_north = reg.with_property(open_west, "facing", Cardinal::North)?;
+        debug_assert_eq!(open_north, reg.find_state(blockstate!(door[open=true]))?);
+        debug_assert!(matches!(
+            reg.find_state(blockstate!(door[facing="banana"])),
+            Err(Error::InvalidPropertyValue(..))
+        ));
+        debug_assert!(matches!(
+            reg.with_property(open_west, "locked", true),
+            Err(Error::UnknownProperty(..))
+        ));
+        debug_assert!(matches!(reg.with_property(StateId(u32::MAX), "open", true), Err(Error::StateNotFound(u32::MAX))));
+        Ok(())
+    }
+
+    #[test]
+    fn namespace_test() -> Result<()> {
+        use crate::voxel::block::block_schema::PropertyDef;
+        struct Lamp(&'static str);
+        impl BlockBehavior for Lamp {
+            fn name(&self) -> &str {
+                self.0
+            }
+            fn properties(&self) -> Vec<PropertyDef> {
+                vec![PropertyDef::bool("lit", false)]
+            }
+        }
+        let reg = BlockRegistry::with_namespace("game")?;
+        debug_assert_eq!(reg.default_namespace()?, "game");
+        let lamp = reg.register_block(Lamp("lamp"))?;
+        let pack_lamp = reg.register_block(Lamp("pack:lamp"))?;
+        debug_assert_ne!(lamp, pack_lamp);
+        // Bare names are in the registry's namespace.
+        debug_assert!(matches!(reg.register_block(Lamp("game:lamp")), Err(Error::DuplicateBlockEntry(_))));
+        debug_assert!(matches!(reg.register_block(Lamp("Bad Name")), Err(Error::InvalidIdentifier(_))));
+        debug_assert_eq!(reg.get_block("game:lamp")?.name(), "lamp");
+        debug_assert_eq!(reg.get_block("pack:lamp")?.name(), "pack:lamp");
+        debug_assert_eq!(reg.identifier(lamp)?.as_str(), "game:lamp");
+        debug_assert_eq!(reg.identifier("air")?.as_str(), "game:air");
+
+        // States keep the name the block was registered with, whichever way they're looked up.
+        let lit = reg.find_state(blockstate!(lamp[lit=true]))?;
+        debug_assert_eq!(reg.find_state(blockstate!(lamp[lit=true]).with_name("game:lamp"))?, lit);
+        debug_assert_ne!(reg.find_state(blockstate!(lamp[lit=true]).with_name("pack:lamp"))?, lit);
+        debug_assert_eq!(reg.get_state(lit)?.name(), "lamp");
+
+        let frozen = reg.freeze()?;
+        debug_assert_eq!(frozen.find_block("game:lamp")?, lamp);
+        debug_assert_eq!(frozen.find_state(&blockstate!(lamp[lit=true]).with_name("game:lamp"))?, lit);
+        debug_assert!(BlockRegistry::with_namespace("Bad Namespace").is_err());
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,300 @@
+use std::ops::RangeInclusive;
+
+use crate::math::axis::Axis;
+use crate::voxel::cardinal::Cardinal;
+use crate::voxel::direction::Direction;
+
+use super::block_property::{BlockProperty, Property};
+use super::block_state::BlockState;
+use super::error::{Error, Result};
+
+/// The values that a property declared in a [BlockSchema] is allowed to have.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PropertyValues {
+    /// `true` or `false`.
+    Bool,
+    /// Any [Property::Int] within the range.
+    Int(RangeInclusive<i64>),
+    /// One of the listed values.
+    List(Vec<Property>),
+}
+
+impl PropertyValues {
+    /// The number of values that are allowed.
+    pub fn count(&self) -> usize {
+        match self {
+            PropertyValues::Bool => 2,
+            PropertyValues::Int(range) => {
+                if range.is_empty() {
+                    0
+                } else {
+                    // Saturate so that the overflow check in BlockSchema::state_count catches it.
+                    (*range.end() as i128 - *range.start() as i128 + 1).min(usize::MAX as i128) as usize
+                }
+            }
+            PropertyValues::List(values) => values.len(),
+        }
+    }
+
+    pub fn contains(&self, value: &Property) -> bool {
+        self.index_of(value).is_some()
+    }
+
+    /// Gets the index of `value` in the order that [PropertyValues::get] uses.
+    pub fn index_of(&self, value: &Property) -> Option<usize> {
+        match (self, value) {
+            (PropertyValues::Bool, Property::Bool(value)) => Some(*value as usize),
+            (PropertyValues::Int(range), Property::Int(value)) if range.contains(value) => {
+                Some((*value as i128 - *range.start() as i128) as usize)
+            }
+            (PropertyValues::List(values), value) => values.iter().position(|item| item == value),
+            _ => None,
+        }
+    }
+
+    /// Gets the value at `index`. Bools are ordered `false`, `true`.
+    pub fn get(&self, index: usize) -> Option<Property> {
+        if index >= self.count() {
+            return None;
+        }
+        Some(match self {
+            PropertyValues::Bool => Property::Bool(index != 0),
+            PropertyValues::Int(range) => Property::Int((*range.start() as i128 + index as i128) as i64),
+            PropertyValues::List(values) => values[index].clone(),
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Property> + '_ {
+        (0..self.count()).map(|index| self.get(index).unwrap())
+    }
+}
+
+/// The declaration of a single property in a [BlockSchema].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PropertyDef {
+    name: String,
+    values: PropertyValues,
+    default: Property,
+}
+
+impl PropertyDef {
+    #[inline]
+    pub fn new<S: Into<String>, P: Into<Property>>(name: S, values: PropertyValues, default: P) -> Self {
+        Self {
+            name: name.into(),
+            values,
+            default: default.into(),
+        }
+    }
+
+    #[inline]
+    pub fn bool<S: Into<String>>(name: S, default: bool) -> Self {
+        Self::new(name, PropertyValues::Bool, default)
+    }
+
+    #[inline]
+    pub fn int<S: Into<String>>(name: S, range: RangeInclusive<i64>, default: i64) -> Self {
+        Self::new(name, PropertyValues::Int(range), default)
+    }
+
+    pub fn list<S: Into<String>, P: Into<Property>, It: IntoIterator<Item = P>, D: Into<Property>>(name: S, values: It, default: D) -> Self {
+        Self::new(name, PropertyValues::List(values.into_iter().map(P::into).collect()), default)
+    }
+
+    /// A property that can be any of the six [Direction]s.
+    #[inline]
+    pub fn direction<S: Into<String>>(name: S, default: Direction) -> Self {
+        Self::list(name, Direction::ALL, default)
+    }
+
+    /// A property that can be any of the four [Cardinal] directions.
+    #[inline]
+    pub fn cardinal<S: Into<String>>(name: S, default: Cardinal) -> Self {
+        Self::list(name, Cardinal::ALL, default)
+    }
+
+    /// A property that can be any [Axis].
+    #[inline]
+    pub fn axis<S: Into<String>>(name: S, default: Axis) -> Self {
+        Self::list(name, [Axis::X, Axis::Y, Axis::Z], default)
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    pub fn values(&self) -> &PropertyValues {
+        &self.values
+    }
+
+    #[inline]
+    pub fn default_value(&self) -> &Property {
+        &self.default
+    }
+}
+
+/// The properties that the states of a block are allowed to have.
+///
+/// Every property of a state must be declared in the schema and must have one of the
+/// allowed values. Properties that are missing from a state take the default value.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct BlockSchema {
+    /// Sorted by name, which is the same order as [BlockState::properties].
+    properties: Vec<PropertyDef>,
+}
+
+impl BlockSchema {
+    pub fn new<It: IntoIterator<Item = PropertyDef>>(block_name: &str, properties: It) -> Result<Self> {
+        let mut properties = properties.into_iter().collect::<Vec<_>>();
+        properties.sort_by(|a, b| a.name.cmp(&b.name));
+        for pair in properties.windows(2) {
+            if pair[0].name == pair[1].name {
+                return Err(Error::InvalidSchema(block_name.to_owned(), format!("property \"{}\" was declared more than once", pair[0].name)));
+            }
+        }
+        for def in properties.iter() {
+            if def.values.count() == 0 {
+                return Err(Error::InvalidSchema(block_name.to_owned(), format!("property \"{}\" has no allowed values", def.name)));
+            }
+            if !def.values.contains(&def.default) {
+                return Err(Error::InvalidSchema(block_name.to_owned(), format!("default value of property \"{}\" is not an allowed value", def.name)));
+            }
+        }
+        Ok(Self {
+            properties,
+        })
+    }
+
+    /// A schema with no properties. Blocks with an empty schema have exactly one state.
+    #[inline]
+    pub const fn empty() -> Self {
+        Self {
+            properties: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn properties(&self) -> &[PropertyDef] {
+        &self.properties
+    }
+
+    pub fn get<S: AsRef<str>>(&self, name: S) -> Option<&PropertyDef> {
+        self.properties
+            .binary_search_by(|def| def.name.as_str().cmp(name.as_ref()))
+            .ok()
+            .map(|index| &self.properties[index])
+    }
+
+    /// The number of valid states, or [Error::RegistryOverflow] if there are more than fit in a [u32].
+    pub fn state_count(&self) -> Result<usize> {
+        self.properties.iter().try_fold(1usize, |count, def| {
+            count.checked_mul(def.values.count())
+                .filter(|&count| count <= u32::MAX as usize)
+                .ok_or(Error::RegistryOverflow)
+        })
+    }
+
+    /// The state with every property set to its default value.
+    pub fn default_state<S: Into<String>>(&self, block_name: S) -> BlockState {
+        BlockState::new(block_name, self.properties.iter().map(|def| {
+            BlockProperty::new(def.name.clone(), def.default.clone())
+        }))
+    }
+
+    /// The index of the default state in the order of [BlockSchema::enumerate].
+    pub fn default_index(&self) -> u32 {
+        self.properties.iter().fold(0usize, |index, def| {
+            index * def.values.count() + def.values.index_of(&def.default).unwrap()
+        }) as u32
+    }
+
+    /// Validates `state` against the schema, returning the state with missing properties set to their defaults.
+    pub fn validate(&self, state: &BlockState) -> Result<BlockState> {
+        // Properties are sorted by name, so duplicates are next to each other.
+        for pair in state.properties().windows(2) {
+            if pair[0].name() == pair[1].name() {
+                return Err(Error::DuplicateProperty(state.name().to_owned(), pair[0].name().to_owned()));
+            }
+        }
+        for prop in state.properties() {
+            let Some(def) = self.get(prop.name()) else {
+                return Err(Error::UnknownProperty(state.name().to_owned(), prop.name().to_owned()));
+            };
+            if !def.values.contains(prop.value()) {
+                return Err(Error::InvalidPropertyValue(state.name().to_owned(), prop.name().to_owned()));
+            }
+        }
+        if state.properties().len() == self.properties.len() {
+            return Ok(state.clone());
+        }
+        Ok(BlockState::new(state.name(), self.properties.iter().map(|def| {
+            let value = state.get_property(&def.name);
+            if value.is_null() {
+                BlockProperty::new(def.name.clone(), def.default.clone())
+            } else {
+                BlockProperty::new(def.name.clone(), value.clone())
+            }
+        })))
+    }
+
+    /// Iterates every valid state of the block. The last property in the schema changes the fastest.
+    pub fn enumerate<'a>(&'a self, block_name: &'a str) -> impl Iterator<Item = BlockState> + 'a {
+        // state_count is checked by the registry before enumerating, so it won't overflow here.
+        let count = self.state_count().unwrap_or(0);
+        (0..count).map(move |mut index| {
+            let mut properties = Vec::with_capacity(self.properties.len());
+            for def in self.properties.iter().rev() {
+                let value_count = def.values.count();
+                properties.push(BlockProperty::new(def.name.clone(), def.values.get(index % value_count).unwrap()));
+                index /= value_count;
+            }
+            BlockState::new(block_name, properties)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockstate;
+    #[test]
+    fn schema_test() {
+        let schema = BlockSchema::new("door", [
+            PropertyDef::cardinal("facing", Cardinal::North),
+            PropertyDef::bool("open", false),
+            PropertyDef::int("power", 0..=15, 0),
+        ]).unwrap();
+        debug_assert_eq!(schema.state_count().unwrap(), 4 * 2 * 16);
+        let states = schema.enumerate("door").collect::<Vec<_>>();
+        debug_assert_eq!(states.len(), 4 * 2 * 16);
+        debug_assert_eq!(states[0], blockstate!(door[facing=Cardinal::West, open=false, power=0i64]));
+        debug_assert_eq!(states[1], blockstate!(door[facing=Cardinal::West, open=false, power=1i64]));
+        debug_assert!(states.iter().all(|state| schema.validate(state).is_ok_and(|valid| &valid == state)));
+
+        let partial = schema.validate(&blockstate!(door[open=true])).unwrap();
+        debug_assert_eq!(partial, blockstate!(door[facing=Cardinal::North, open=true, power=0i64]));
+        debug_assert!(matches!(
+            schema.validate(&blockstate!(door[facing="banana"])),
+            Err(Error::InvalidPropertyValue(..))
+        ));
+        debug_assert!(matches!(
+            schema.validate(&blockstate!(door[power=16i64])),
+            Err(Error::InvalidPropertyValue(..))
+        ));
+        debug_assert!(matches!(
+            schema.validate(&blockstate!(door[color="red"])),
+            Err(Error::UnknownProperty(..))
+        ));
+        // As many properties as the schema, but with one of them twice.
+        debug_assert!(matches!(
+            schema.validate(&blockstate!(door[open=true, open=false, power=3i64])),
+            Err(Error::DuplicateProperty(..))
+        ));
+        debug_assert!(matches!(
+            BlockSchema::new("bad", [PropertyDef::int("power", 0..=15, 16)]),
+            Err(Error::InvalidSchema(..))
+        ));
+    }
+}
@@ -0,0 +1,403 @@
+use std::sync::Arc;
+
+use glam::Vec3;
+use hashbrown::HashMap;
+use serde::{Serialize, Deserialize};
+
+use crate::math::axis::Axis;
+use crate::voxel::direction::Direction;
+use crate::voxel::face_flags::FaceFlags;
+use crate::voxel::orientation::{Orientation, Rotation};
+
+use super::block::BlockBehavior;
+use super::block_property::Property;
+use super::block_state::BlockState;
+
+/// How close a box edge has to be to the block boundary to count as touching it.
+const EPSILON: f32 = 1e-5;
+
+/// An axis-aligned box within a block. Coordinates are in blocks, so a full block is `(0, 0, 0)` to `(1, 1, 1)`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShapeBox {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl ShapeBox {
+    pub const FULL: ShapeBox = ShapeBox::new(Vec3::ZERO, Vec3::ONE);
+
+    #[inline]
+    pub const fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Creates a box from coordinates in sixteenths of a block.
+    #[inline]
+    pub fn pixels(min_x: f32, min_y: f32, min_z: f32, max_x: f32, max_y: f32, max_z: f32) -> Self {
+        Self::new(Vec3::new(min_x, min_y, min_z) / 16.0, Vec3::new(max_x, max_y, max_z) / 16.0)
+    }
+
+    #[inline]
+    pub fn size(self) -> Vec3 {
+        (self.max - self.min).max(Vec3::ZERO)
+    }
+
+    #[inline]
+    pub fn volume(self) -> f32 {
+        let size = self.size();
+        size.x * size.y * size.z
+    }
+
+    #[inline]
+    pub fn is_empty(self) -> bool {
+        self.volume() <= 0.0
+    }
+
+    /// Rotates and flips the box around the center of the block.
+    pub fn reorient(self, orientation: Orientation) -> Self {
+        let center = Vec3::splat(0.5);
+        let a = orientation.transform(self.min - center) + center;
+        let b = orientation.transform(self.max - center) + center;
+        Self::new(a.min(b), a.max(b))
+    }
+
+    /// The rectangle that the box covers on `face` (as `(min_u, min_v, max_u, max_v)`),
+    /// or `None` if the box doesn't touch that side of the block.
+    fn face_rect(self, face: Direction) -> Option<(f32, f32, f32, f32)> {
+        if self.is_empty() {
+            return None;
+        }
+        let axis = face.axis();
+        let touches = match face {
+            Direction::PosX | Direction::PosY | Direction::PosZ => self.max[axis as usize] >= 1.0 - EPSILON,
+            Direction::NegX | Direction::NegY | Direction::NegZ => self.min[axis as usize] <= EPSILON,
+        };
+        if !touches {
+            return None;
+        }
+        let (u, v) = match axis {
+            Axis::X => (1, 2),
+            Axis::Y => (0, 2),
+            Axis::Z => (0, 1),
+        };
+        Some((self.min[u], self.min[v], self.max[u], self.max[v]))
+    }
+}
+
+/// The shape of a block state, such as its collision or outline shape, made up of [ShapeBox]es.
+///
+/// The faces that the shape fully covers are computed when it is created, which is what occlusion uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockShape {
+    boxes: Vec<ShapeBox>,
+    full_faces: FaceFlags,
+}
+
+impl Default for BlockShape {
+    fn default() -> Self {
+        Self::full()
+    }
+}
+
+impl BlockShape {
+    pub fn new<It: IntoIterator<Item = ShapeBox>>(boxes: It) -> Self {
+        let boxes = boxes.into_iter().filter(|bounds| !bounds.is_empty()).collect::<Vec<_>>();
+        let mut full_faces = FaceFlags::NONE;
+        for face in Direction::ALL {
+            let rects = boxes.iter().filter_map(|bounds| bounds.face_rect(face)).collect::<Vec<_>>();
+            full_faces.set(face, covers_unit_square(&rects));
+        }
+        Self {
+            boxes,
+            full_faces,
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            boxes: Vec::new(),
+            full_faces: FaceFlags::NONE,
+        }
+    }
+
+    pub fn full() -> Self {
+        Self {
+            boxes: vec![ShapeBox::FULL],
+            full_faces: FaceFlags::ALL,
+        }
+    }
+
+    /// The bottom half of a block.
+    pub fn slab() -> Self {
+        Self::new([ShapeBox::pixels(0.0, 0.0, 0.0, 16.0, 8.0, 16.0)])
+    }
+
+    /// A bottom slab with the back (south) half of the top filled in, for stairs that face north.
+    pub fn stairs() -> Self {
+        Self::new([
+            ShapeBox::pixels(0.0, 0.0, 0.0, 16.0, 8.0, 16.0),
+            ShapeBox::pixels(0.0, 8.0, 8.0, 16.0, 16.0, 16.0),
+        ])
+    }
+
+    /// A 4x4 pixel post in the center of the block, such as a fence post.
+    pub fn post() -> Self {
+        Self::new([ShapeBox::pixels(6.0, 0.0, 6.0, 10.0, 16.0, 10.0)])
+    }
+
+    #[inline]
+    pub fn boxes(&self) -> &[ShapeBox] {
+        &self.boxes
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.boxes.is_empty()
+    }
+
+    /// Returns `true` if the shape fills the whole block.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.boxes.contains(&ShapeBox::FULL)
+    }
+
+    /// The faces of the block that the shape fully covers.
+    #[inline]
+    pub fn full_faces(&self) -> FaceFlags {
+        self.full_faces
+    }
+
+    /// Returns `true` if the shape fully covers `face` of the block, so the neighbor's face on that side is hidden.
+    #[inline]
+    pub fn covers_face(&self, face: Direction) -> bool {
+        self.full_faces.get(face)
+    }
+
+    /// Rotates and flips every box around the center of the block.
+    pub fn reorient(&self, orientation: Orientation) -> Self {
+        if orientation == Orientation::UNORIENTED {
+            return self.clone();
+        }
+        Self {
+            boxes: self.boxes.iter().map(|bounds| bounds.reorient(orientation)).collect(),
+            full_faces: self.full_faces.reorient(orientation),
+        }
+    }
+}
+
+/// Returns `true` if the union of `rects` covers the square from `(0, 0)` to `(1, 1)`.
+fn covers_unit_square(rects: &[(f32, f32, f32, f32)]) -> bool {
+    if rects.is_empty() {
+        return false;
+    }
+    // Split the square along every rect edge, then check that each cell is covered by some rect.
+    let mut us = vec![0.0, 1.0];
+    let mut vs = vec![0.0, 1.0];
+    for &(min_u, min_v, max_u, max_v) in rects {
+        us.extend([min_u.clamp(0.0, 1.0), max_u.clamp(0.0, 1.0)]);
+        vs.extend([min_v.clamp(0.0, 1.0), max_v.clamp(0.0, 1.0)]);
+    }
+    us.sort_by(f32::total_cmp);
+    us.dedup();
+    vs.sort_by(f32::total_cmp);
+    vs.dedup();
+    us.windows(2).filter(|u| u[1] - u[0] > EPSILON).all(|u| {
+        vs.windows(2).filter(|v| v[1] - v[0] > EPSILON).all(|v| {
+            let (center_u, center_v) = ((u[0] + u[1]) * 0.5, (v[0] + v[1]) * 0.5);
+            rects.iter().any(|&(min_u, min_v, max_u, max_v)| {
+                min_u <= center_u && center_u <= max_u && min_v <= center_v && center_v <= max_v
+            })
+        })
+    })
+}
+
+/// The orientation that turns the north-facing (unoriented) shape of a block to face `facing`.
+/// Up and down are reached by tipping the block forwards or backwards.
+pub fn facing_orientation(facing: Direction) -> Orientation {
+    let up = match facing {
+        Direction::PosY => Direction::PosZ,
+        Direction::NegY => Direction::NegZ,
+        _ => Direction::PosY,
+    };
+    match Rotation::from_up_and_forward(up, facing) {
+        Some(rotation) => rotation.orientation(),
+        None => unreachable!(),
+    }
+}
+
+/// The orientation that [BlockBehavior::orientation] returns by default: the value of the first
+/// [Orientation](Property::Orientation) property, or else the `facing` property if it's a
+/// [Direction](Property::Direction) or [Cardinal](Property::Cardinal).
+pub fn state_orientation(state: &BlockState) -> Orientation {
+    let orientation = state.properties().iter().find_map(|prop| match prop.value() {
+        Property::Orientation(orientation) => Some(*orientation),
+        _ => None,
+    });
+    if let Some(orientation) = orientation {
+        return orientation;
+    }
+    match state.get_property("facing") {
+        Property::Direction(direction) => facing_orientation(*direction),
+        Property::Cardinal(cardinal) => facing_orientation(cardinal.direction()),
+        _ => Orientation::UNORIENTED,
+    }
+}
+
+/// The collision and outline shapes of a state, oriented by [BlockBehavior::orientation].
+#[derive(Debug, Clone)]
+pub(super) struct StateShapes {
+    pub(super) collision: Arc<BlockShape>,
+    pub(super) outline: Arc<BlockShape>,
+}
+
+/// Keeps one copy of each distinct [BlockShape], keyed by the bits of its boxes.
+#[derive(Debug, Default)]
+pub(super) struct ShapeCache(HashMap<Vec<[u32; 6]>, Arc<BlockShape>>);
+
+impl ShapeCache {
+    /// Gets the cached shape that is equal to `shape`, adding `shape` if there isn't one.
+    pub(super) fn intern(&mut self, shape: BlockShape) -> Arc<BlockShape> {
+        // Adding 0.0 turns -0.0 into 0.0, so that boxes that compare equal have the same key.
+        let key = shape.boxes.iter().map(|bounds| {
+            let [a, b, c] = (bounds.min + 0.0).to_array().map(f32::to_bits);
+            let [d, e, f] = (bounds.max + 0.0).to_array().map(f32::to_bits);
+            [a, b, c, d, e, f]
+        }).collect();
+        Arc::clone(self.0.entry(key).or_insert_with(|| Arc::new(shape)))
+    }
+}
+
+impl StateShapes {
+    /// Gets the shapes of `state` from `block`, reusing any equal shape in `cache`.
+    /// Most states of a block share their shapes, so this keeps one copy of each.
+    pub(super) fn new(block: &dyn BlockBehavior, state: &BlockState, cache: &mut ShapeCache) -> Self {
+        let orientation = block.orientation(state);
+        let collision = cache.intern(block.collision_shape(state).reorient(orientation));
+        let outline = cache.intern(block.outline_shape(state).reorient(orientation));
+        Self {
+            collision,
+            outline,
+        }
+    }
+
+    /// The shapes of air.
+    pub(super) fn empty() -> Self {
+        let empty = Arc::new(BlockShape::empty());
+        Self {
+            collision: Arc::clone(&empty),
+            outline: empty,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockstate;
+    use crate::voxel::block::block_registry::BlockRegistry;
+    use crate::voxel::block::block_schema::PropertyDef;
+    use crate::voxel::block::id::StateId;
+    use crate::voxel::cardinal::Cardinal;
+
+    #[test]
+    fn shape_faces_test() {
+        debug_assert_eq!(BlockShape::full().full_faces(), FaceFlags::ALL);
+        debug_assert_eq!(BlockShape::new([ShapeBox::FULL]), BlockShape::full());
+        debug_assert_eq!(BlockShape::empty().full_faces(), FaceFlags::NONE);
+        debug_assert_eq!(BlockShape::slab().full_faces(), FaceFlags::NEG_Y);
+        debug_assert_eq!(BlockShape::post().full_faces(), FaceFlags::NONE);
+        debug_assert_eq!(BlockShape::stairs().full_faces(), FaceFlags::NEG_Y | FaceFlags::POS_Z);
+        // Two halves that together cover a face.
+        let halves = BlockShape::new([
+            ShapeBox::pixels(0.0, 0.0, 0.0, 8.0, 16.0, 16.0),
+            ShapeBox::pixels(8.0, 0.0, 0.0, 16.0, 16.0, 16.0),
+        ]);
+        debug_assert_eq!(halves.full_faces(), FaceFlags::ALL);
+        debug_assert!(!halves.is_full());
+        let gap = BlockShape::new([
+            ShapeBox::pixels(0.0, 0.0, 0.0, 7.0, 16.0, 16.0),
+            ShapeBox::pixels(8.0, 0.0, 0.0, 16.0, 16.0, 16.0),
+        ]);
+        debug_assert_eq!(gap.full_faces(), FaceFlags::NEG_X | FaceFlags::POS_X);
+
+        for face in Direction::ALL {
+            let orientation = facing_orientation(face);
+            debug_assert_eq!(orientation.forward(), face);
+            let stairs = BlockShape::stairs().reorient(orientation);
+            debug_assert!(stairs.covers_face(orientation.reface(Direction::PosZ)));
+            debug_assert!(stairs.covers_face(orientation.reface(Direction::NegY)));
+            // The reoriented faces agree with the faces computed from the reoriented boxes.
+            debug_assert_eq!(BlockShape::new(stairs.boxes().iter().copied()), stairs);
+        }
+    }
+
+    #[test]
+    fn shape_cache_test() {
+        let mut cache = ShapeCache::default();
+        let slab = cache.intern(BlockShape::slab());
+        debug_assert!(Arc::ptr_eq(&slab, &cache.intern(BlockShape::slab())));
+        // -0.0 compares equal to 0.0, so it gets the same shape.
+        let negative_zero = BlockShape::new([ShapeBox::new(Vec3::new(-0.0, 0.0, 0.0), Vec3::new(1.0, 0.5, 1.0))]);
+        debug_assert_eq!(negative_zero, BlockShape::slab());
+        debug_assert!(Arc::ptr_eq(&slab, &cache.intern(negative_zero)));
+        debug_assert!(!Arc::ptr_eq(&slab, &cache.intern(BlockShape::full())));
+    }
+
+    struct Slab;
+    impl BlockBehavior for Slab {
+        fn name(&self) -> &str {
+            "slab"
+        }
+
+        fn properties(&self) -> Vec<PropertyDef> {
+            vec![PropertyDef::direction("facing", Direction::NegZ)]
+        }
+
+        fn collision_shape(&self, _state: &BlockState) -> BlockShape {
+            BlockShape::slab()
+        }
+    }
+
+    struct Stairs;
+    impl BlockBehavior for Stairs {
+        fn name(&self) -> &str {
+            "stairs"
+        }
+
+        fn properties(&self) -> Vec<PropertyDef> {
+            vec![PropertyDef::cardinal("facing", Cardinal::North)]
+        }
+
+        fn collision_shape(&self, _state: &BlockState) -> BlockShape {
+            BlockShape::stairs()
+        }
+
+        fn outline_shape(&self, _state: &BlockState) -> BlockShape {
+            BlockShape::full()
+        }
+    }
+
+    #[test]
+    fn state_shape_test() {
+        let registry = BlockRegistry::new();
+        registry.register_block(Slab).unwrap();
+        registry.register_block(Stairs).unwrap();
+        debug_assert!(registry.collision_shape(StateId::AIR).unwrap().is_empty());
+        let east = registry.find_state(blockstate!(stairs[facing=Cardinal::East])).unwrap();
+        let shape = registry.collision_shape(east).unwrap();
+        debug_assert_eq!(shape.full_faces(), FaceFlags::NEG_Y | FaceFlags::NEG_X);
+        debug_assert!(registry.outline_shape(east).unwrap().is_full());
+        // Facing up tips the slab backwards, so its bottom ends up on the north side.
+        let slab = registry.find_state(blockstate!(slab[facing=Direction::PosY])).unwrap();
+        debug_assert_eq!(registry.collision_shape(slab).unwrap().full_faces(), FaceFlags::NEG_Z);
+
+        let frozen = registry.freeze().unwrap();
+        debug_assert_eq!(frozen.collision_shape(east), &*shape);
+        debug_assert!(frozen.covers_face(east, Direction::NegX));
+        debug_assert!(!frozen.covers_face(east, Direction::PosX));
+        // States with the same shape share it.
+        let north = frozen.find_state(&blockstate!(stairs[facing=Cardinal::North])).unwrap();
+        debug_assert!(!std::ptr::eq(frozen.collision_shape(north), frozen.outline_shape(north)));
+        debug_assert!(std::ptr::eq(frozen.outline_shape(north), frozen.outline_shape(east)));
+    }
+}
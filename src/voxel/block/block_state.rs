@@ -1,4 +1,5 @@
 use serde::{Serialize, Deserialize};
+use crate::io::*;
 use super::block_property::Property;
 use super::block_property::BlockProperty;
 
@@ -31,13 +32,52 @@ impl BlockState {
 
     pub fn get_property<S: AsRef<str>>(&self, name: S) -> &Property {
         if let Ok(index) = self.sorted_properties.binary_search_by(|prop| {
-            name.as_ref().cmp(&prop.name)
+            prop.name.as_str().cmp(name.as_ref())
         }) {
             &self.sorted_properties[index].value
         } else {
             &Property::NULL
         }
     }
+
+    /// Returns a copy of this state with the block name replaced by `block_name`.
+    pub fn with_name<S: Into<String>>(&self, block_name: S) -> BlockState {
+        Self {
+            block_name: block_name.into(),
+            sorted_properties: self.sorted_properties.clone(),
+        }
+    }
+
+    /// Returns a copy of this state with the property `name` set to `value`.
+    pub fn with_property<S: Into<String>, P: Into<Property>>(&self, name: S, value: P) -> BlockState {
+        let property = BlockProperty::new(name, value);
+        let mut sorted_properties = self.sorted_properties.clone();
+        match sorted_properties.binary_search_by(|prop| prop.name.cmp(&property.name)) {
+            Ok(index) => sorted_properties[index] = property,
+            Err(index) => sorted_properties.insert(index, property),
+        }
+        Self {
+            block_name: self.block_name.clone(),
+            sorted_properties,
+        }
+    }
+}
+
+impl Readable for BlockState {
+    fn read_from<R: std::io::Read>(reader: &mut R) -> crate::prelude::VoxelResult<Self> {
+        let block_name = String::read_from(reader)?;
+        let properties: Vec<BlockProperty> = read_vec(reader)?;
+        Ok(BlockState::new(block_name, properties))
+    }
+}
+
+impl Writeable for BlockState {
+    fn write_to<W: std::io::Write>(&self, writer: &mut W) -> crate::prelude::VoxelResult<u64> {
+        Ok(
+            self.block_name.write_to(writer)? +
+            write_slice(writer, &self.sorted_properties)?
+        )
+    }
 }
 
 impl<S: AsRef<str>> std::ops::Index<S> for BlockState {
@@ -0,0 +1,836 @@
+//! The text syntax for [BlockState], [Property], and [PropertyArray].
+//!
+//! ```text
+//! chest[facing=west,locked=false,color=#ff00ff]
+//! ```
+//!
+//! | Property         | Syntax                                          |
+//! |------------------|-------------------------------------------------|
+//! | `Null`           | `null`                                          |
+//! | `Bool`           | `true`, `false`                                 |
+//! | `Int`            | `-12`, `0x7f`, `0b101`                          |
+//! | `String`         | `"text with \"escapes\"\n"`                     |
+//! | `Direction`      | `pos_y`, `neg_x`, ...                           |
+//! | `Cardinal`       | `west`, `north`, `east`, `south`                |
+//! | `Rotation`       | `rotation(pos_y,1)`                             |
+//! | `Flip`           | `flip(x\|z)`, `flip()`                          |
+//! | `Orientation`    | `orientation(rotation(pos_x,2),flip(y))`        |
+//! | `Axis`           | `x`, `y`, `z`                                   |
+//! | `IVec2`..`IVec4` | `ivec2(1,2)`, `ivec3(1,2,3)`, `ivec4(1,2,3,4)`  |
+//! | `FaceFlags`      | `faces(pos_y\|neg_x)`, `faces()`                |
+//! | `AxisFlags`      | `axes(x\|y)`, `axes()`                          |
+//! | `BitFlags8`..    | `bits8(0xf0)`, ..., `bits128(0x1)`              |
+//! | `Color`          | `color(alice_blue)`                             |
+//! | `Rgb`            | `#ff00ff`                                       |
+//! | `Rgba`           | `#ff00ff80`                                     |
+//! | `Range`          | `0..16`                                         |
+//! | `RangeInclusive` | `0..=15`                                        |
+//! | `Bytes`          | `b"raw\x00bytes"`                               |
+//! | `Map`            | `{key=1,"spaced key"=true}`                     |
+//! | `Array`          | `[]`, `[int;1,2,3]`, `[any;1,"two",[]]`         |
+//!
+//! Names that contain characters other than `A-Z`, `a-z`, `0-9`, `_`, `-`, `.`, `:`, and `/` are quoted.
+//! Whitespace is allowed between tokens, but formatting never writes any, so formatting and parsing round-trip exactly.
+//! Packed values that don't fit the readable form (such as a [Rotation] with an invalid up direction) are written as their raw number, like `rotation(31)`.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter, Write};
+use std::ops::{Range, RangeInclusive};
+use std::str::FromStr;
+
+use glam::{IVec2, IVec3, IVec4};
+
+use crate::math::axis::Axis;
+use crate::math::axis_flags::AxisFlags;
+use crate::math::bit::{BitFlags8, BitFlags16, BitFlags32, BitFlags64, BitFlags128};
+use crate::rendering::color::{Color, Rgb, Rgba};
+use crate::util::scanner::{self, Scanner, SyntaxError, SyntaxResult};
+use crate::voxel::cardinal::Cardinal;
+use crate::voxel::direction::Direction;
+use crate::voxel::face_flags::FaceFlags;
+use crate::voxel::orientation::{Flip, Orientation, Rotation};
+
+use super::block_property::{property_table, BlockProperty, Property, PropertyArray};
+use super::block_state::BlockState;
+
+/// Indexed by the discriminant of [Direction].
+const DIRECTION_NAMES: [&str; 6] = ["pos_y", "pos_x", "pos_z", "neg_y", "neg_x", "neg_z"];
+const DIRECTIONS: [Direction; 6] = [Direction::PosY, Direction::PosX, Direction::PosZ, Direction::NegY, Direction::NegX, Direction::NegZ];
+/// Indexed by the discriminant of [Cardinal].
+const CARDINAL_NAMES: [&str; 4] = ["west", "north", "east", "south"];
+const CARDINALS: [Cardinal; 4] = [Cardinal::West, Cardinal::North, Cardinal::East, Cardinal::South];
+/// Indexed by the discriminant of [Axis].
+const AXIS_NAMES: [&str; 3] = ["x", "y", "z"];
+const AXES: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+/// A type that has a text form in the block state syntax.
+trait Syntax: Sized {
+    /// The element type name used in arrays, and the function name for values written as `name(...)`.
+    const NAME: &'static str;
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result;
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self>;
+}
+
+fn parse_named<T: Copy>(scanner: &mut Scanner, kind: &str, names: &[&str], values: &[T]) -> SyntaxResult<T> {
+    let start = scanner.position();
+    let word = scanner.word()?;
+    names.iter()
+        .position(|&name| name == word)
+        .map(|index| values[index])
+        .ok_or_else(|| scanner.error_at(start, format!("Unknown {kind} `{word}`")))
+}
+
+/// Parses `name(` with optional whitespace after the opening parenthesis.
+fn open_call(scanner: &mut Scanner, name: &str) -> SyntaxResult<()> {
+    let start = scanner.position();
+    if scanner.word()? != name {
+        return Err(scanner.error_at(start, format!("Expected `{name}(...)`")));
+    }
+    scanner.expect('(')?;
+    scanner.skip_whitespace();
+    Ok(())
+}
+
+fn close_call(scanner: &mut Scanner) -> SyntaxResult<()> {
+    scanner.skip_whitespace();
+    scanner.expect(')')
+}
+
+fn separator(scanner: &mut Scanner) -> SyntaxResult<()> {
+    scanner.skip_whitespace();
+    scanner.expect(',')?;
+    scanner.skip_whitespace();
+    Ok(())
+}
+
+/// Writes the names of the set bits separated by `|`, or the raw value if a bit without a name is set.
+fn write_flags(f: &mut Formatter<'_>, name: &str, bits: u8, names: &[&str]) -> fmt::Result {
+    write!(f, "{name}(")?;
+    if (bits >> names.len()) != 0 {
+        write!(f, "{bits}")?;
+    } else {
+        let mut sep = false;
+        for (index, bit_name) in names.iter().enumerate() {
+            if bits & (1 << index) != 0 {
+                if sep {
+                    f.write_char('|')?;
+                }
+                f.write_str(bit_name)?;
+                sep = true;
+            }
+        }
+    }
+    f.write_char(')')
+}
+
+fn parse_flags(scanner: &mut Scanner, name: &str, names: &[&str]) -> SyntaxResult<u8> {
+    open_call(scanner, name)?;
+    let bits = match scanner.peek() {
+        Some(')') => 0,
+        Some(c) if c.is_ascii_digit() => scanner.integer::<u8>()?,
+        _ => {
+            let mut bits = 0u8;
+            loop {
+                let start = scanner.position();
+                let word = scanner.word()?;
+                let Some(index) = names.iter().position(|&bit_name| bit_name == word) else {
+                    return Err(scanner.error_at(start, format!("Unknown {name} flag `{word}`")));
+                };
+                bits |= 1 << index;
+                scanner.skip_whitespace();
+                if !scanner.eat('|') {
+                    break bits;
+                }
+                scanner.skip_whitespace();
+            }
+        }
+    };
+    close_call(scanner)?;
+    Ok(bits)
+}
+
+/// Parses `#` followed by exactly `N * 2` hex digits.
+fn parse_hex_bytes<const N: usize>(scanner: &mut Scanner) -> SyntaxResult<[u8; N]> {
+    scanner.expect('#')?;
+    let start = scanner.position();
+    let hex = scanner.take_while(|c| c.is_ascii_hexdigit());
+    if hex.len() != N * 2 {
+        return Err(scanner.error_at(start, format!("Expected {} hex digits", N * 2)));
+    }
+    let mut bytes = [0u8; N];
+    for (index, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).unwrap();
+    }
+    Ok(bytes)
+}
+
+impl Syntax for bool {
+    const NAME: &'static str = "bool";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        parse_named(scanner, "bool", &["false", "true"], &[false, true])
+    }
+}
+
+impl Syntax for i64 {
+    const NAME: &'static str = "int";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        scanner.integer()
+    }
+}
+
+impl Syntax for String {
+    const NAME: &'static str = "string";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        scanner::write_quoted(f, self)
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        scanner.quoted()
+    }
+}
+
+impl Syntax for Direction {
+    const NAME: &'static str = "direction";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(DIRECTION_NAMES[*self as usize])
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        parse_named(scanner, "direction", &DIRECTION_NAMES, &DIRECTIONS)
+    }
+}
+
+impl Syntax for Cardinal {
+    const NAME: &'static str = "cardinal";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(CARDINAL_NAMES[*self as usize])
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        parse_named(scanner, "cardinal", &CARDINAL_NAMES, &CARDINALS)
+    }
+}
+
+impl Syntax for Axis {
+    const NAME: &'static str = "axis";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(AXIS_NAMES[*self as usize])
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        parse_named(scanner, "axis", &AXIS_NAMES, &AXES)
+    }
+}
+
+impl Syntax for Rotation {
+    const NAME: &'static str = "rotation";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.0 < 24 {
+            write!(f, "rotation({},{})", DIRECTION_NAMES[self.up() as usize], self.angle())
+        } else {
+            write!(f, "rotation({})", self.0)
+        }
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        open_call(scanner, Self::NAME)?;
+        let rotation = if scanner.peek().is_some_and(|c| c.is_ascii_digit()) {
+            Rotation(scanner.integer()?)
+        } else {
+            let up = Direction::parse_syntax(scanner)?;
+            separator(scanner)?;
+            let start = scanner.position();
+            let angle = scanner.integer::<i32>()?;
+            if !(0..4).contains(&angle) {
+                return Err(scanner.error_at(start, "Rotation angle must be between 0 and 3"));
+            }
+            Rotation::new(up, angle)
+        };
+        close_call(scanner)?;
+        Ok(rotation)
+    }
+}
+
+impl Syntax for Flip {
+    const NAME: &'static str = "flip";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_flags(f, Self::NAME, self.0, &AXIS_NAMES)
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        parse_flags(scanner, Self::NAME, &AXIS_NAMES).map(Flip)
+    }
+}
+
+impl Syntax for Orientation {
+    const NAME: &'static str = "orientation";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("orientation(")?;
+        self.rotation().write_syntax(f)?;
+        f.write_char(',')?;
+        self.flip().write_syntax(f)?;
+        f.write_char(')')
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        let start = scanner.position();
+        open_call(scanner, Self::NAME)?;
+        let rotation = Rotation::parse_syntax(scanner)?;
+        separator(scanner)?;
+        let flip = Flip::parse_syntax(scanner)?;
+        close_call(scanner)?;
+        if rotation.0 >= 32 || flip.0 >= 8 {
+            return Err(scanner.error_at(start, "Rotation or flip out of range for orientation"));
+        }
+        Ok(Orientation::new(rotation, flip))
+    }
+}
+
+macro_rules! ivec_syntax {
+    ($($type:ident($name:literal, $length:literal);)+) => {
+        $(
+            impl Syntax for $type {
+                const NAME: &'static str = $name;
+                fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                    write!(f, "{}(", $name)?;
+                    for (index, component) in self.to_array().iter().enumerate() {
+                        if index != 0 {
+                            f.write_char(',')?;
+                        }
+                        write!(f, "{component}")?;
+                    }
+                    f.write_char(')')
+                }
+
+                fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+                    open_call(scanner, $name)?;
+                    let mut components = [0i32; $length];
+                    for (index, component) in components.iter_mut().enumerate() {
+                        if index != 0 {
+                            separator(scanner)?;
+                        }
+                        *component = scanner.integer()?;
+                    }
+                    close_call(scanner)?;
+                    Ok($type::from_array(components))
+                }
+            }
+        )+
+    };
+}
+
+ivec_syntax!(
+    IVec2("ivec2", 2);
+    IVec3("ivec3", 3);
+    IVec4("ivec4", 4);
+);
+
+impl Syntax for FaceFlags {
+    const NAME: &'static str = "faces";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_flags(f, Self::NAME, self.inner(), &DIRECTION_NAMES)
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        parse_flags(scanner, Self::NAME, &DIRECTION_NAMES).map(FaceFlags::from)
+    }
+}
+
+impl Syntax for AxisFlags {
+    const NAME: &'static str = "axes";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_flags(f, Self::NAME, self.inner(), &AXIS_NAMES)
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        parse_flags(scanner, Self::NAME, &AXIS_NAMES).map(AxisFlags::from_u8)
+    }
+}
+
+macro_rules! bitflags_syntax {
+    ($($type:ident($name:literal);)+) => {
+        $(
+            impl Syntax for $type {
+                const NAME: &'static str = $name;
+                fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                    write!(f, concat!($name, "({:#x})"), self.0)
+                }
+
+                fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+                    open_call(scanner, $name)?;
+                    let bits = scanner.integer()?;
+                    close_call(scanner)?;
+                    Ok($type(bits))
+                }
+            }
+        )+
+    };
+}
+
+bitflags_syntax!(
+    BitFlags8("bits8");
+    BitFlags16("bits16");
+    BitFlags32("bits32");
+    BitFlags64("bits64");
+    BitFlags128("bits128");
+);
+
+impl Syntax for Color {
+    const NAME: &'static str = "color";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "color({})", self.lower_snake_name())
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        open_call(scanner, Self::NAME)?;
+        let start = scanner.position();
+        let name = scanner.word()?;
+        let color = Color::from_name(name).ok_or_else(|| scanner.error_at(start, format!("Unknown color `{name}`")))?;
+        close_call(scanner)?;
+        Ok(color)
+    }
+}
+
+impl Syntax for Rgb {
+    const NAME: &'static str = "rgb";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        parse_hex_bytes(scanner).map(Rgb::from_bytes)
+    }
+}
+
+impl Syntax for Rgba {
+    const NAME: &'static str = "rgba";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        parse_hex_bytes(scanner).map(Rgba::from_bytes)
+    }
+}
+
+impl Syntax for Range<i64> {
+    const NAME: &'static str = "range";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        let start = scanner.integer()?;
+        if scanner.rest().starts_with("..=") {
+            return Err(scanner.error("Expected `..`, found `..=`"));
+        }
+        if !scanner.eat_str("..") {
+            return Err(scanner.unexpected("`..`"));
+        }
+        Ok(start..scanner.integer()?)
+    }
+}
+
+impl Syntax for RangeInclusive<i64> {
+    const NAME: &'static str = "range_inclusive";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..={}", self.start(), self.end())
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        let start = scanner.integer()?;
+        if !scanner.eat_str("..=") {
+            return Err(scanner.unexpected("`..=`"));
+        }
+        Ok(start..=scanner.integer()?)
+    }
+}
+
+impl Syntax for Vec<u8> {
+    const NAME: &'static str = "bytes";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        scanner::write_quoted_bytes(f, self)
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        scanner.expect('b')?;
+        scanner.quoted_bytes()
+    }
+}
+
+impl Syntax for BTreeMap<String, Property> {
+    const NAME: &'static str = "map";
+    fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_char('{')?;
+        for (index, (key, value)) in self.iter().enumerate() {
+            if index != 0 {
+                f.write_char(',')?;
+            }
+            scanner::write_name(f, key)?;
+            f.write_char('=')?;
+            value.write_syntax(f)?;
+        }
+        f.write_char('}')
+    }
+
+    fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+        scanner.expect('{')?;
+        let mut map = BTreeMap::new();
+        parse_list(scanner, '}', |scanner| {
+            let start = scanner.position();
+            let key = scanner::parse_name(scanner)?;
+            scanner.skip_whitespace();
+            scanner.expect('=')?;
+            scanner.skip_whitespace();
+            let value = Property::parse_syntax(scanner)?;
+            if map.contains_key(&key) {
+                return Err(scanner.error_at(start, format!("Duplicate key `{key}`")));
+            }
+            map.insert(key, value);
+            Ok(())
+        })?;
+        Ok(map)
+    }
+}
+
+/// Parses `item,item,...` followed by `close`. The opening delimiter must already be consumed.
+/// A trailing comma is allowed.
+fn parse_list<F: FnMut(&mut Scanner) -> SyntaxResult<()>>(scanner: &mut Scanner, close: char, mut item: F) -> SyntaxResult<()> {
+    loop {
+        scanner.skip_whitespace();
+        if scanner.eat(close) {
+            return Ok(());
+        }
+        item(scanner)?;
+        scanner.skip_whitespace();
+        if !scanner.eat(',') {
+            scanner.expect(close)?;
+            return Ok(());
+        }
+    }
+}
+
+fn write_items<T: Syntax>(f: &mut Formatter<'_>, items: &[T]) -> fmt::Result {
+    write!(f, "[{};", T::NAME)?;
+    for (index, item) in items.iter().enumerate() {
+        if index != 0 {
+            f.write_char(',')?;
+        }
+        item.write_syntax(f)?;
+    }
+    f.write_char(']')
+}
+
+fn parse_items<T: Syntax>(scanner: &mut Scanner) -> SyntaxResult<Vec<T>> {
+    let mut items = Vec::new();
+    parse_list(scanner, ']', |scanner| {
+        items.push(T::parse_syntax(scanner)?);
+        Ok(())
+    })?;
+    Ok(items)
+}
+
+macro_rules! array_syntax {
+    ($({$id:literal $name:ident($type:ty)})+) => {
+        impl Syntax for PropertyArray {
+            const NAME: &'static str = "array";
+            fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                match self {
+                    PropertyArray::Empty => f.write_str("[]"),
+                    $(
+                        PropertyArray::$name(items) => write_items(f, items),
+                    )+
+                    PropertyArray::Any(items) => write_items(f, items),
+                }
+            }
+
+            fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+                scanner.expect('[')?;
+                scanner.skip_whitespace();
+                if scanner.eat(']') {
+                    return Ok(PropertyArray::Empty);
+                }
+                let start = scanner.position();
+                let type_name = scanner.word()?;
+                scanner.skip_whitespace();
+                scanner.expect(';')?;
+                $(
+                    if type_name == <$type as Syntax>::NAME {
+                        return Ok(PropertyArray::$name(parse_items(scanner)?));
+                    }
+                )+
+                if type_name == <Property as Syntax>::NAME {
+                    return Ok(PropertyArray::Any(parse_items(scanner)?));
+                }
+                Err(scanner.error_at(start, format!("Unknown array type `{type_name}`")))
+            }
+        }
+
+        impl Syntax for Property {
+            const NAME: &'static str = "any";
+            fn write_syntax(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                match self {
+                    Property::Null => f.write_str("null"),
+                    $(
+                        Property::$name(value) => value.write_syntax(f),
+                    )+
+                }
+            }
+
+            fn parse_syntax(scanner: &mut Scanner) -> SyntaxResult<Self> {
+                parse_property(scanner)
+            }
+        }
+    };
+}
+
+property_table!(array_syntax);
+
+fn parse_property(scanner: &mut Scanner) -> SyntaxResult<Property> {
+    let start = scanner.position();
+    Ok(match scanner.peek() {
+        Some('"') => Property::String(scanner.quoted()?),
+        Some('{') => Property::Map(BTreeMap::parse_syntax(scanner)?),
+        Some('[') => Property::Array(PropertyArray::parse_syntax(scanner)?),
+        Some('#') => {
+            let digits = scanner.rest()[1..].find(|c: char| !c.is_ascii_hexdigit()).unwrap_or(scanner.rest().len() - 1);
+            if digits == 8 {
+                Property::Rgba(Rgba::parse_syntax(scanner)?)
+            } else {
+                Property::Rgb(Rgb::parse_syntax(scanner)?)
+            }
+        }
+        Some('b') if scanner.rest().starts_with("b\"") => Property::Bytes(Vec::parse_syntax(scanner)?),
+        Some(c) if c.is_ascii_digit() || c == '-' || c == '+' => {
+            let value = scanner.integer()?;
+            if scanner.eat_str("..=") {
+                Property::RangeInclusive(value..=scanner.integer()?)
+            } else if scanner.eat_str("..") {
+                Property::Range(value..scanner.integer()?)
+            } else {
+                Property::Int(value)
+            }
+        }
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            let word = scanner.peek_word();
+            match word {
+                "null" => {
+                    scanner.word()?;
+                    Property::Null
+                }
+                "true" | "false" => Property::Bool(bool::parse_syntax(scanner)?),
+                "rotation" => Property::Rotation(Rotation::parse_syntax(scanner)?),
+                "flip" => Property::Flip(Flip::parse_syntax(scanner)?),
+                "orientation" => Property::Orientation(Orientation::parse_syntax(scanner)?),
+                "ivec2" => Property::IVec2(IVec2::parse_syntax(scanner)?),
+                "ivec3" => Property::IVec3(IVec3::parse_syntax(scanner)?),
+                "ivec4" => Property::IVec4(IVec4::parse_syntax(scanner)?),
+                "faces" => Property::FaceFlags(FaceFlags::parse_syntax(scanner)?),
+                "axes" => Property::AxisFlags(AxisFlags::parse_syntax(scanner)?),
+                "bits8" => Property::BitFlags8(BitFlags8::parse_syntax(scanner)?),
+                "bits16" => Property::BitFlags16(BitFlags16::parse_syntax(scanner)?),
+                "bits32" => Property::BitFlags32(BitFlags32::parse_syntax(scanner)?),
+                "bits64" => Property::BitFlags64(BitFlags64::parse_syntax(scanner)?),
+                "bits128" => Property::BitFlags128(BitFlags128::parse_syntax(scanner)?),
+                "color" => Property::Color(Color::parse_syntax(scanner)?),
+                word if DIRECTION_NAMES.contains(&word) => Property::Direction(Direction::parse_syntax(scanner)?),
+                word if CARDINAL_NAMES.contains(&word) => Property::Cardinal(Cardinal::parse_syntax(scanner)?),
+                word if AXIS_NAMES.contains(&word) => Property::Axis(Axis::parse_syntax(scanner)?),
+                word => return Err(scanner.error_at(start, format!("Unknown value `{word}`"))),
+            }
+        }
+        _ => return Err(scanner.unexpected("a value")),
+    })
+}
+
+fn parse_block_state(scanner: &mut Scanner) -> SyntaxResult<BlockState> {
+    scanner.skip_whitespace();
+    let name = scanner::parse_name(scanner)?;
+    let mut properties = Vec::new();
+    scanner.skip_whitespace();
+    if scanner.eat('[') {
+        parse_list(scanner, ']', |scanner| {
+            let key = scanner::parse_name(scanner)?;
+            scanner.skip_whitespace();
+            scanner.expect('=')?;
+            scanner.skip_whitespace();
+            properties.push(BlockProperty::new(key, Property::parse_syntax(scanner)?));
+            Ok(())
+        })?;
+    }
+    Ok(BlockState::new(name, properties))
+}
+
+/// Parses the whole of `source` with `parse`, allowing surrounding whitespace.
+fn parse_all<T, F: FnOnce(&mut Scanner) -> SyntaxResult<T>>(source: &str, parse: F) -> SyntaxResult<T> {
+    let mut scanner = Scanner::new(source);
+    scanner.skip_whitespace();
+    let value = parse(&mut scanner)?;
+    scanner.expect_eof()?;
+    Ok(value)
+}
+
+impl Display for Property {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write_syntax(f)
+    }
+}
+
+impl FromStr for Property {
+    type Err = SyntaxError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_all(s, Property::parse_syntax)
+    }
+}
+
+impl Display for PropertyArray {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write_syntax(f)
+    }
+}
+
+impl FromStr for PropertyArray {
+    type Err = SyntaxError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_all(s, PropertyArray::parse_syntax)
+    }
+}
+
+impl Display for BlockProperty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        scanner::write_name(f, self.name())?;
+        f.write_char('=')?;
+        self.value().write_syntax(f)
+    }
+}
+
+impl Display for BlockState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        scanner::write_name(f, self.name())?;
+        if self.properties().is_empty() {
+            return Ok(());
+        }
+        f.write_char('[')?;
+        for (index, property) in self.properties().iter().enumerate() {
+            if index != 0 {
+                f.write_char(',')?;
+            }
+            write!(f, "{property}")?;
+        }
+        f.write_char(']')
+    }
+}
+
+impl FromStr for BlockState {
+    type Err = SyntaxError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_all(s, parse_block_state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockstate;
+
+    fn round_trip(property: Property) {
+        let text = property.to_string();
+        let parsed = text.parse::<Property>().unwrap_or_else(|err| panic!("{text}: {err}"));
+        debug_assert_eq!(parsed, property, "{text}");
+        debug_assert_eq!(parsed.to_string(), text);
+    }
+
+    #[test]
+    fn syntax_round_trip_test() {
+        let state = blockstate!(chest[facing=Cardinal::West, locked=false, color=Rgb::new(255, 0, 255)]);
+        debug_assert_eq!(state.to_string(), "chest[color=#ff00ff,facing=west,locked=false]");
+        debug_assert_eq!("chest[facing=west,locked=false,color=#ff00ff]".parse::<BlockState>().unwrap(), state);
+        debug_assert_eq!(" chest [ facing = west , locked = false , color = #ff00ff , ] ".parse::<BlockState>().unwrap(), state);
+        debug_assert_eq!("air".parse::<BlockState>().unwrap(), blockstate!(air));
+        debug_assert_eq!(blockstate!(air).to_string(), "air");
+        let quoted = blockstate!("odd name"["odd key"=1i64]);
+        debug_assert_eq!(quoted.to_string(), "\"odd name\"[\"odd key\"=1]");
+        debug_assert_eq!(quoted.to_string().parse::<BlockState>().unwrap(), quoted);
+
+        let mut map = BTreeMap::new();
+        map.insert("inner".to_owned(), Property::Array(PropertyArray::Int(vec![1, -2, 3])));
+        map.insert("with space".to_owned(), Property::String("tab\t\"quote\"".to_owned()));
+        let properties = [
+            Property::Null,
+            Property::Bool(true),
+            Property::Int(i64::MIN),
+            Property::Int(i64::MAX),
+            Property::String("Hello, \"world\"!\n\u{1}".to_owned()),
+            Property::Direction(Direction::NegZ),
+            Property::Cardinal(Cardinal::South),
+            Property::Rotation(Rotation::new(Direction::PosX, 3)),
+            Property::Rotation(Rotation(30)),
+            Property::Flip(Flip::XZ),
+            Property::Flip(Flip::NONE),
+            Property::Orientation(Orientation::new(Rotation::new(Direction::NegY, 2), Flip::Y)),
+            Property::Axis(Axis::Z),
+            Property::IVec2(IVec2::new(-1, 2)),
+            Property::IVec3(IVec3::new(1, -2, 3)),
+            Property::IVec4(IVec4::new(1, 2, -3, i32::MIN)),
+            Property::FaceFlags(FaceFlags::from(0b101001)),
+            Property::FaceFlags(FaceFlags::from(0b1100_0000)),
+            Property::AxisFlags(AxisFlags::XZ),
+            Property::AxisFlags(AxisFlags::NONE),
+            Property::BitFlags8(BitFlags8(0xa5)),
+            Property::BitFlags16(BitFlags16(0xbeef)),
+            Property::BitFlags32(BitFlags32(u32::MAX)),
+            Property::BitFlags64(BitFlags64(1 << 63)),
+            Property::BitFlags128(BitFlags128(u128::MAX)),
+            Property::Color(Color::AliceBlue),
+            Property::Rgb(Rgb::new(1, 2, 255)),
+            Property::Rgba(Rgba::new(1, 2, 3, 4)),
+            Property::Range(-5..5),
+            Property::RangeInclusive(0..=-1),
+            Property::Bytes(vec![0, b'a', b'"', b'\\', 0x7f, 0xff]),
+            Property::Map(map.clone()),
+            Property::Map(BTreeMap::new()),
+            Property::Array(PropertyArray::Empty),
+            Property::Array(PropertyArray::Bool(vec![])),
+            Property::Array(PropertyArray::Cardinal(vec![Cardinal::North, Cardinal::East])),
+            Property::Array(PropertyArray::Rgba(vec![Rgba::new(0, 0, 0, 0)])),
+            Property::Array(PropertyArray::Range(vec![0..1, 2..3])),
+            Property::Array(PropertyArray::Map(vec![map.clone(), BTreeMap::new()])),
+            Property::Array(PropertyArray::Array(vec![PropertyArray::Empty, PropertyArray::String(vec!["a".to_owned()])])),
+            Property::Array(PropertyArray::Any(vec![Property::Null, Property::Int(1), Property::Array(PropertyArray::Empty)])),
+        ];
+        for property in properties.iter() {
+            round_trip(property.clone());
+        }
+        let everything = BlockState::new("everything", properties.iter().enumerate().map(|(index, property)| {
+            BlockProperty::new(format!("p{index}"), property.clone())
+        }));
+        let text = everything.to_string();
+        debug_assert_eq!(text.parse::<BlockState>().unwrap(), everything);
+    }
+
+    #[test]
+    fn syntax_error_test() {
+        fn error(text: &str) -> SyntaxError {
+            text.parse::<BlockState>().unwrap_err()
+        }
+        debug_assert_eq!(error("chest[facing=up]"), SyntaxError::new(13, "Unknown value `up`"));
+        debug_assert_eq!(error("chest[facing=west"), SyntaxError::new(17, "Expected `]`, found end of input"));
+        debug_assert_eq!(error("chest[color=#ff00f]").position, 13);
+        debug_assert_eq!(error("chest[a=rotation(pos_y,4)]").position, 23);
+        debug_assert_eq!(error("chest[a=[int;1,true]]").position, 15);
+        debug_assert_eq!(error("chest[a=[float;1]]"), SyntaxError::new(9, "Unknown array type `float`"));
+        debug_assert_eq!(error("chest[a={b=1,b=2}]"), SyntaxError::new(13, "Duplicate key `b`"));
+        debug_assert_eq!(error("chest[a=\"open]").reason, "Unterminated string");
+        debug_assert_eq!(error("chest[a=1] extra").position, 11);
+        debug_assert_eq!(error("chest[a=300000000000000000000]").position, 8);
+        debug_assert_eq!(error("chest[=1]").position, 6);
+    }
+}
@@ -0,0 +1,342 @@
+use std::path::Path;
+
+use hashbrown::HashMap;
+use serde::{Serialize, Deserialize};
+
+use super::error::{Error, Result};
+use super::id::BlockId;
+use crate::util::identifier::IdentifierMap;
+
+/// An entry in a block tag. Either a block or another tag whose blocks are included.
+///
+/// When converted from a string, names that start with `#` are tags, everything else is a block.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TagEntry {
+    Block(String),
+    Tag(String),
+}
+
+impl TagEntry {
+    #[inline]
+    pub fn block<S: Into<String>>(name: S) -> Self {
+        Self::Block(name.into())
+    }
+
+    #[inline]
+    pub fn tag<S: Into<String>>(name: S) -> Self {
+        Self::Tag(name.into())
+    }
+}
+
+impl From<&str> for TagEntry {
+    fn from(value: &str) -> Self {
+        match value.strip_prefix('#') {
+            Some(tag) => Self::Tag(tag.to_owned()),
+            None => Self::Block(value.to_owned()),
+        }
+    }
+}
+
+impl From<String> for TagEntry {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self::from(value.as_str())
+    }
+}
+
+impl std::fmt::Display for TagEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TagEntry::Block(name) => write!(f, "{name}"),
+            TagEntry::Tag(name) => write!(f, "#{name}"),
+        }
+    }
+}
+
+/// A set of blocks stored as a bitset indexed by [BlockId].
+#[derive(Debug, Default, Clone)]
+pub struct BlockSet {
+    bits: Vec<u64>,
+}
+
+impl BlockSet {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            bits: Vec::new(),
+        }
+    }
+
+    /// Creates an empty set that can hold `block_count` blocks without growing.
+    #[inline]
+    pub fn with_capacity(block_count: usize) -> Self {
+        Self {
+            bits: vec![0; block_count.div_ceil(64)],
+        }
+    }
+
+    #[inline]
+    pub fn contains(&self, id: BlockId) -> bool {
+        let index = id.index();
+        self.bits.get(index / 64).is_some_and(|word| word & (1 << (index % 64)) != 0)
+    }
+
+    /// Returns `true` if the block was not already in the set.
+    pub fn insert(&mut self, id: BlockId) -> bool {
+        let index = id.index();
+        if index / 64 >= self.bits.len() {
+            self.bits.resize(index / 64 + 1, 0);
+        }
+        let word = &mut self.bits[index / 64];
+        let bit = 1 << (index % 64);
+        let inserted = *word & bit == 0;
+        *word |= bit;
+        inserted
+    }
+
+    /// Returns `true` if the block was in the set.
+    pub fn remove(&mut self, id: BlockId) -> bool {
+        let index = id.index();
+        let Some(word) = self.bits.get_mut(index / 64) else {
+            return false;
+        };
+        let bit = 1 << (index % 64);
+        let removed = *word & bit != 0;
+        *word &= !bit;
+        removed
+    }
+
+    /// Adds every block in `other` to this set.
+    pub fn union_with(&mut self, other: &BlockSet) {
+        if other.bits.len() > self.bits.len() {
+            self.bits.resize(other.bits.len(), 0);
+        }
+        self.bits.iter_mut().zip(other.bits.iter()).for_each(|(word, other)| *word |= other);
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|&word| word == 0)
+    }
+
+    /// Iterates the [BlockId]s in the set in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = BlockId> + '_ {
+        self.bits.iter().enumerate().flat_map(|(word_index, &word)| {
+            (0..64u32).filter(move |bit| word & (1 << bit) != 0).map(move |bit| BlockId((word_index * 64) as u32 + bit))
+        })
+    }
+}
+
+impl PartialEq for BlockSet {
+    fn eq(&self, other: &Self) -> bool {
+        let (short, long) = if self.bits.len() <= other.bits.len() {
+            (&self.bits, &other.bits)
+        } else {
+            (&other.bits, &self.bits)
+        };
+        short.iter().zip(long.iter()).all(|(a, b)| a == b) && long[short.len()..].iter().all(|&word| word == 0)
+    }
+}
+
+impl Eq for BlockSet {}
+
+impl FromIterator<BlockId> for BlockSet {
+    fn from_iter<T: IntoIterator<Item = BlockId>>(iter: T) -> Self {
+        let mut set = BlockSet::new();
+        iter.into_iter().for_each(|id| {
+            set.insert(id);
+        });
+        set
+    }
+}
+
+/// The format of a tag data file.
+///
+/// ```json
+/// {
+///     "replace": false,
+///     "values": ["oak_log", "birch_log", "#stripped_logs"]
+/// }
+/// ```
+/// If `replace` is `true`, the entries replace any entries the tag already had instead of being added to them.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagFile {
+    #[serde(default)]
+    pub replace: bool,
+    pub values: Vec<String>,
+}
+
+impl TagFile {
+    pub fn from_json(json: &str) -> std::result::Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = TagEntry> + '_ {
+        self.values.iter().map(|value| TagEntry::from(value.as_str()))
+    }
+}
+
+/// Reads every `.json` file under `dir` as a [TagFile].
+/// The name of each tag is its path relative to `dir` without the extension, such as `mineable/pickaxe`.
+pub(super) fn read_tag_dir(dir: &Path) -> Result<Vec<(String, TagFile)>> {
+    fn visit(root: &Path, dir: &Path, files: &mut Vec<(String, TagFile)>) -> Result<()> {
+        let entries = std::fs::read_dir(dir).map_err(|err| Error::InvalidTagFile(dir.display().to_string(), err.to_string()))?;
+        let mut paths = entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect::<Vec<_>>();
+        // Sorted so that tags are loaded in the same order on every platform.
+        paths.sort();
+        for path in paths {
+            if path.is_dir() {
+                visit(root, &path, files)?;
+            } else if path.extension().is_some_and(|ext| ext == "json") {
+                let name = path.strip_prefix(root).unwrap().with_extension("");
+                let name = name.components().map(|part| part.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+                files.push((name, read_tag_file(&path)?));
+            }
+        }
+        Ok(())
+    }
+    let mut files = Vec::new();
+    visit(dir, dir, &mut files)?;
+    Ok(files)
+}
+
+pub(super) fn read_tag_file(path: &Path) -> Result<TagFile> {
+    let json = std::fs::read_to_string(path).map_err(|err| Error::InvalidTagFile(path.display().to_string(), err.to_string()))?;
+    TagFile::from_json(&json).map_err(|err| Error::InvalidTagFile(path.display().to_string(), err.to_string()))
+}
+
+/// Resolves tags into [BlockSet]s, following nested tags and detecting cycles.
+pub(super) struct TagResolver<'a> {
+    tags: &'a HashMap<String, Vec<TagEntry>>,
+    block_lookup: &'a IdentifierMap<BlockId>,
+    block_count: usize,
+    resolved: HashMap<String, BlockSet>,
+    visiting: Vec<String>,
+}
+
+impl<'a> TagResolver<'a> {
+    pub(super) fn new(tags: &'a HashMap<String, Vec<TagEntry>>, block_lookup: &'a IdentifierMap<BlockId>, block_count: usize) -> Self {
+        Self {
+            tags,
+            block_lookup,
+            block_count,
+            resolved: HashMap::new(),
+            visiting: Vec::new(),
+        }
+    }
+
+    pub(super) fn resolve(&mut self, name: &str) -> Result<BlockSet> {
+        if let Some(set) = self.resolved.get(name) {
+            return Ok(set.clone());
+        }
+        if self.visiting.iter().any(|visiting| visiting == name) {
+            return Err(Error::TagCycle(name.to_owned()));
+        }
+        let entries = self.tags.get(name).ok_or_else(|| Error::TagNotFound(name.to_owned()))?;
+        self.visiting.push(name.to_owned());
+        let mut set = BlockSet::with_capacity(self.block_count);
+        for entry in entries.iter() {
+            match entry {
+                TagEntry::Block(block) => {
+                    let id = self.block_lookup.get(block).ok_or_else(|| Error::BlockNotFound(block.clone()))?;
+                    set.insert(*id);
+                }
+                TagEntry::Tag(tag) => set.union_with(&self.resolve(tag)?),
+            }
+        }
+        self.visiting.pop();
+        self.resolved.insert(name.to_owned(), set.clone());
+        Ok(set)
+    }
+
+    /// Resolves every tag.
+    pub(super) fn resolve_all(mut self) -> Result<HashMap<String, BlockSet>> {
+        for name in self.tags.keys() {
+            self.resolve(name)?;
+        }
+        Ok(self.resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voxel::block::block::BlockBehavior;
+    use crate::voxel::block::block_registry::BlockRegistry;
+
+    struct Named(&'static str);
+    impl BlockBehavior for Named {
+        fn name(&self) -> &str {
+            self.0
+        }
+    }
+
+    #[test]
+    fn block_set_test() {
+        let mut set = BlockSet::new();
+        debug_assert!(set.is_empty());
+        let inserted = [set.insert(BlockId(3)), set.insert(BlockId(3)), set.insert(BlockId(130))];
+        debug_assert_eq!(inserted, [true, false, true]);
+        debug_assert!(set.contains(BlockId(130)));
+        debug_assert!(!set.contains(BlockId(4)));
+        debug_assert!(!set.contains(BlockId(100_000)));
+        debug_assert_eq!(set.iter().collect::<Vec<_>>(), vec![BlockId(3), BlockId(130)]);
+        let removed = set.remove(BlockId(130));
+        debug_assert!(removed);
+        debug_assert_eq!(set.len(), 1);
+        let mut other = BlockSet::with_capacity(1000);
+        other.insert(BlockId(3));
+        debug_assert_eq!(set, other);
+    }
+
+    #[test]
+    fn block_tag_test() {
+        let registry = BlockRegistry::new();
+        let oak = registry.register_block(Named("oak_log")).unwrap();
+        let birch = registry.register_block(Named("birch_log")).unwrap();
+        let planks = registry.register_block(Named("planks")).unwrap();
+        let stone = registry.register_block(Named("stone")).unwrap();
+        registry.register_tag("logs", ["oak_log"]).unwrap();
+        // Declaring a tag again adds to it.
+        registry.register_tag("logs", [TagEntry::block("birch_log")]).unwrap();
+        registry.load_tag_json("burnable", r##"{ "values": ["#logs", "planks"] }"##).unwrap();
+        let burnable = registry.resolve_tag("burnable").unwrap();
+        debug_assert_eq!(burnable.iter().collect::<Vec<_>>(), vec![oak, birch, planks]);
+        debug_assert!(!burnable.contains(stone));
+
+        registry.load_tag_json("logs", r#"{ "replace": true, "values": ["oak_log"] }"#).unwrap();
+        debug_assert!(!registry.resolve_tag("burnable").unwrap().contains(birch));
+
+        registry.register_tag("a", ["#b"]).unwrap();
+        registry.register_tag("b", ["stone", "#a"]).unwrap();
+        debug_assert!(matches!(registry.resolve_tag("a"), Err(Error::TagCycle(..))));
+        debug_assert!(matches!(registry.resolve_tag("missing"), Err(Error::TagNotFound(..))));
+        debug_assert!(matches!(registry.load_tag_json("bad", r#"{ "values": "stone" }"#), Err(Error::InvalidTagFile(..))));
+        // A broken tag stops the registry from freezing.
+        debug_assert!(matches!(registry.freeze(), Err(Error::TagCycle(..))));
+        registry.load_tag_json("b", r#"{ "replace": true, "values": ["stone"] }"#).unwrap();
+        let frozen = registry.freeze().unwrap();
+        debug_assert!(frozen.has_tag(stone, "a"));
+        debug_assert!(frozen.has_tag(oak, "burnable"));
+        debug_assert!(!frozen.has_tag(stone, "burnable"));
+        debug_assert!(!frozen.has_tag(stone, "missing"));
+        debug_assert!(matches!(registry.register_tag("late", ["stone"]), Err(Error::RegistryFrozen)));
+    }
+
+    #[test]
+    fn tag_dir_test() {
+        let dir = std::env::temp_dir().join(format!("hexahedron_tag_dir_test_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("mineable")).unwrap();
+        std::fs::write(dir.join("logs.json"), r#"{ "values": ["oak_log"] }"#).unwrap();
+        std::fs::write(dir.join("mineable").join("axe.json"), r##"{ "values": ["#logs"] }"##).unwrap();
+        let registry = BlockRegistry::new();
+        let oak = registry.register_block(Named("oak_log")).unwrap();
+        let loaded = registry.load_tag_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        debug_assert_eq!(loaded.unwrap(), 2);
+        debug_assert!(registry.resolve_tag("mineable/axe").unwrap().contains(oak));
+    }
+}
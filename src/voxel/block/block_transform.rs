@@ -0,0 +1,159 @@
+//! Transforming [BlockState]s when blocks are rotated or mirrored, such as when a structure is placed.
+
+use crate::voxel::cardinal::Cardinal;
+use crate::voxel::orientation::Orientation;
+
+use super::block_property::{BlockProperty, Property};
+use super::block_schema::BlockSchema;
+use super::block_state::BlockState;
+
+impl Property {
+    /// Gets the value after `orientation` is applied.
+    ///
+    /// [Direction](Property::Direction), [Cardinal](Property::Cardinal), [Axis](Property::Axis),
+    /// [Orientation](Property::Orientation), [FaceFlags](Property::FaceFlags) and [AxisFlags](Property::AxisFlags)
+    /// values are transformed. Returns `None` for any other value, and for a [Cardinal] that would point up or down.
+    pub fn reorient(&self, orientation: Orientation) -> Option<Property> {
+        Some(match self {
+            Property::Direction(direction) => Property::Direction(orientation.reface(*direction)),
+            Property::Cardinal(cardinal) => Property::Cardinal(Cardinal::from_direction(orientation.reface(cardinal.direction()))?),
+            Property::Axis(axis) => Property::Axis(axis.reorient(orientation)),
+            Property::Orientation(inner) => Property::Orientation(inner.reorient(orientation)),
+            Property::FaceFlags(flags) => Property::FaceFlags(flags.reorient(orientation)),
+            Property::AxisFlags(flags) => Property::AxisFlags(flags.reorient(orientation)),
+            _ => return None,
+        })
+    }
+}
+
+impl BlockState {
+    /// Applies `orientation` to every property with [Property::reorient].
+    /// Properties that aren't affected by orientation keep their value, and so does a [Cardinal] that would
+    /// point up or down, since it has no vertical values.
+    pub fn reorient(&self, orientation: Orientation) -> BlockState {
+        BlockState::new(self.name(), self.properties().iter().map(|prop| {
+            match prop.value().reorient(orientation) {
+                Some(value) => BlockProperty::new(prop.name(), value),
+                None => prop.clone(),
+            }
+        }))
+    }
+}
+
+/// Puts back the `original` value of any property of `transformed` whose new value isn't allowed by `schema`,
+/// such as a `facing` property that only allows horizontal directions.
+pub(super) fn keep_allowed(schema: &BlockSchema, original: &BlockState, transformed: BlockState) -> BlockState {
+    if schema.validate(&transformed).is_ok() {
+        return transformed;
+    }
+    BlockState::new(transformed.name(), transformed.properties().iter().map(|prop| {
+        match schema.get(prop.name()) {
+            Some(def) if !def.values().contains(prop.value()) && !original.get_property(prop.name()).is_null() => {
+                BlockProperty::new(prop.name(), original.get_property(prop.name()).clone())
+            }
+            _ => prop.clone(),
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use hashbrown::HashSet;
+
+    use super::*;
+    use crate::blockstate;
+    use crate::math::axis::Axis;
+    use crate::math::axis_flags::AxisFlags;
+    use crate::voxel::block::block::BlockBehavior;
+    use crate::voxel::block::block_registry::BlockRegistry;
+    use crate::voxel::block::block_schema::PropertyDef;
+    use crate::voxel::direction::Direction;
+    use crate::voxel::face_flags::FaceFlags;
+    use crate::voxel::orientation::{self, Flip, Rotation};
+
+    /// Every orientation in the orientation tables, reduced to the 48 orientations that move the faces differently.
+    fn all_orientations() -> Vec<Orientation> {
+        let mut seen = HashSet::new();
+        orientation::table_orientations()
+            .filter(|orientation| seen.insert(Direction::ALL.map(|face| orientation.reface(face))))
+            .collect()
+    }
+
+    #[test]
+    fn reorient_test() {
+        let orientations = all_orientations();
+        debug_assert_eq!(orientations.len(), 48);
+        let state = blockstate!(thing[
+            facing=Direction::PosZ,
+            horizontal=Cardinal::East,
+            axis=Axis::X,
+            attached=FaceFlags::POS_X | FaceFlags::NEG_Y,
+            axes=AxisFlags::XY,
+            orientation=Orientation::new(Rotation::new(Direction::PosX, 1), Flip::NONE),
+            age=3i64,
+        ]);
+        for orientation in orientations {
+            let transformed = state.reorient(orientation);
+            debug_assert_eq!(transformed["facing"], Property::Direction(orientation.reface(Direction::PosZ)));
+            match Cardinal::from_direction(orientation.reface(Direction::PosX)) {
+                Some(cardinal) => debug_assert_eq!(transformed["horizontal"], Property::Cardinal(cardinal)),
+                None => debug_assert_eq!(transformed["horizontal"], Property::Cardinal(Cardinal::East)),
+            }
+            debug_assert_eq!(transformed["axis"], Property::Axis(orientation.reface(Direction::PosX).axis()));
+            let Property::FaceFlags(attached) = transformed["attached"] else { panic!() };
+            for face in Direction::ALL {
+                let expected = face == Direction::PosX || face == Direction::NegY;
+                debug_assert_eq!(attached.get(orientation.reface(face)), expected);
+            }
+            let Property::AxisFlags(axes) = transformed["axes"] else { panic!() };
+            debug_assert_eq!(axes, AxisFlags::from(orientation.reface(Direction::PosX).axis()) | orientation.reface(Direction::PosY).axis());
+            let Property::Orientation(inner) = transformed["orientation"] else { panic!() };
+            let Property::Orientation(original) = state["orientation"] else { panic!() };
+            for face in Direction::ALL {
+                debug_assert_eq!(inner.reface(face), orientation.reface(original.reface(face)));
+                // The face coordinates from the tables agree with applying the two orientations one after the other.
+                let (uv, target) = ((1i32, 2i32), inner.reface(face));
+                let expected = orientation.map_face_coord(target, original.map_face_coord(original.reface(face), uv));
+                debug_assert_eq!(inner.map_face_coord(target, uv), expected);
+            }
+            debug_assert_eq!(transformed["age"], Property::Int(3));
+
+            // Applying the inverse gets back the original state, unless the cardinal would have pointed up or down.
+            if Cardinal::from_direction(orientation.reface(Direction::PosX)).is_some() {
+                debug_assert_eq!(transformed.reorient(orientation.invert()), state);
+            }
+        }
+    }
+
+    struct Stairs;
+    impl BlockBehavior for Stairs {
+        fn name(&self) -> &str {
+            "stairs"
+        }
+
+        fn properties(&self) -> Vec<PropertyDef> {
+            vec![
+                PropertyDef::list("facing", [Direction::NegZ, Direction::PosX, Direction::PosZ, Direction::NegX], Direction::NegZ),
+                PropertyDef::axis("axis", Axis::Y),
+            ]
+        }
+    }
+
+    #[test]
+    fn transform_state_test() {
+        let registry = BlockRegistry::new();
+        registry.register_block(Stairs).unwrap();
+        let stairs = registry.find_state(blockstate!(stairs[facing=Direction::NegZ, axis=Axis::X])).unwrap();
+        let turned = registry.transform_state(stairs, Orientation::ROTATE_Y).unwrap();
+        let expected = Orientation::ROTATE_Y.reface(Direction::NegZ);
+        debug_assert_ne!(expected, Direction::NegZ);
+        debug_assert_eq!(registry.get_state(turned).unwrap()["facing"], Property::Direction(expected));
+        debug_assert_eq!(registry.get_state(turned).unwrap()["axis"], Property::Axis(Axis::Z));
+        // `facing` can't point up or down, so it keeps its value while `axis` is still transformed.
+        let upright = registry.find_state(blockstate!(stairs[facing=Direction::NegZ, axis=Axis::Y])).unwrap();
+        let tipped = registry.transform_state(upright, Orientation::ROTATE_X).unwrap();
+        debug_assert_eq!(*registry.get_state(tipped).unwrap(), blockstate!(stairs[facing=Direction::NegZ, axis=Axis::Z]));
+        let frozen = registry.freeze().unwrap();
+        debug_assert_eq!(frozen.transform_state(stairs, Orientation::ROTATE_Y).unwrap(), turned);
+    }
+}
@@ -1,4 +1,7 @@
 use super::super::block::BlockBehavior;
+use super::super::block_material::BlockMaterial;
+use super::super::block_shape::BlockShape;
+use super::super::block_state::BlockState;
 
 pub struct AirBlock;
 
@@ -14,4 +17,12 @@ impl BlockBehavior for AirBlock {
     fn description(&self) -> Option<&str> {
         Some("A block of air.")
     }
+
+    fn collision_shape(&self, _state: &BlockState) -> BlockShape {
+        BlockShape::empty()
+    }
+
+    fn material(&self, _state: &BlockState) -> BlockMaterial {
+        BlockMaterial::AIR
+    }
 }
\ No newline at end of file
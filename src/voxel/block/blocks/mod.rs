@@ -1,2 +1,4 @@
 pub mod airblock;
-pub use airblock::AirBlock;
\ No newline at end of file
+pub mod unknownblock;
+pub use airblock::AirBlock;
+pub use unknownblock::UnknownBlock;
\ No newline at end of file
@@ -0,0 +1,23 @@
+use super::super::block::BlockBehavior;
+
+/// Stands in for a block that isn't registered, so that its saved states can be loaded and saved again.
+/// Each state kept with [MissingBlockPolicy::KeepRaw](super::super::block_palette::MissingBlockPolicy::KeepRaw)
+/// gets its own [UnknownBlock] with the name of the saved state.
+pub struct UnknownBlock(String);
+
+impl UnknownBlock {
+    #[inline]
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self(name.into())
+    }
+}
+
+impl BlockBehavior for UnknownBlock {
+    fn name(&self) -> &str {
+        &self.0
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("A block that isn't registered.")
+    }
+}
@@ -0,0 +1,527 @@
+//! Blocks that are defined in JSON instead of Rust.
+//!
+//! ```json
+//! {
+//!     "name": "marble_pillar",
+//!     "display_name": "Marble Pillar",
+//!     "description": "A polished marble pillar.",
+//!     "properties": {
+//!         "axis": { "type": "axis", "default": "y" },
+//!         "cracks": { "type": "int", "min": 0, "max": 3 },
+//!         "trim": { "type": "string", "values": ["none", "gold"] },
+//!         "facing": { "type": "list", "values": ["west", "east"], "default": "east" }
+//!     },
+//!     "light_emission": 0,
+//!     "light_opacity": 15,
+//!     "transparency": "opaque",
+//!     "hardness": 1.5,
+//!     "state_overrides": [
+//!         { "when": { "trim": "\"gold\"" }, "light_emission": 4 }
+//!     ],
+//!     "collision": [[2, 0, 2, 14, 16, 14]],
+//!     "tags": ["decorative", "mineable/pickaxe"]
+//! }
+//! ```
+//! `list` values and `when` values use the [BlockState] text syntax, see [block_syntax](super::block_syntax).
+//! Material fields that are left out take their value from [BlockMaterial::DEFAULT].
+//! Collision boxes are `[min_x, min_y, min_z, max_x, max_y, max_z]` in sixteenths of a block.
+//! `collision` can also be `"full"` (the default) or `"empty"`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Serialize, Deserialize};
+
+use crate::math::axis::Axis;
+use crate::voxel::cardinal::Cardinal;
+use crate::voxel::direction::Direction;
+
+use super::block::BlockBehavior;
+use super::block_material::{BlockMaterial, Transparency};
+use super::block_property::Property;
+use super::block_registry::BlockRegistry;
+use super::block_schema::{BlockSchema, PropertyDef, PropertyValues};
+use super::block_shape::{BlockShape, ShapeBox};
+use super::block_state::BlockState;
+use super::block_tag::TagEntry;
+use super::error::{Error, Result};
+use super::id::BlockId;
+
+/// The JSON format of a data-driven block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlockDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub properties: BTreeMap<String, PropertyDefinition>,
+    /// The light level that the block emits, from 0 to 15.
+    #[serde(default)]
+    pub light_emission: u8,
+    /// How much light is absorbed by the block, from 0 (transparent) to 15 (opaque).
+    #[serde(default = "BlockDefinition::default_light_opacity")]
+    pub light_opacity: u8,
+    #[serde(default)]
+    pub transparency: Transparency,
+    #[serde(default = "BlockDefinition::default_friction")]
+    pub friction: f32,
+    #[serde(default = "BlockDefinition::default_hardness")]
+    pub hardness: f32,
+    #[serde(default = "BlockDefinition::default_blast_resistance")]
+    pub blast_resistance: f32,
+    /// Material changes for the states that match, applied in order.
+    #[serde(default)]
+    pub state_overrides: Vec<StateOverride>,
+    #[serde(default)]
+    pub collision: ShapeDefinition,
+    /// The block tags that the block is added to when it is registered.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl BlockDefinition {
+    const fn default_light_opacity() -> u8 {
+        BlockMaterial::DEFAULT.light_opacity
+    }
+
+    const fn default_friction() -> f32 {
+        BlockMaterial::DEFAULT.friction
+    }
+
+    const fn default_hardness() -> f32 {
+        BlockMaterial::DEFAULT.hardness
+    }
+
+    const fn default_blast_resistance() -> f32 {
+        BlockMaterial::DEFAULT.blast_resistance
+    }
+
+    /// The [BlockMaterial] of states that don't match any of the [BlockDefinition::state_overrides].
+    pub fn material(&self) -> BlockMaterial {
+        BlockMaterial {
+            light_emission: self.light_emission,
+            light_opacity: self.light_opacity,
+            transparency: self.transparency,
+            friction: self.friction,
+            hardness: self.hardness,
+            blast_resistance: self.blast_resistance,
+        }
+    }
+
+    /// Parses a definition from JSON. `source` is the name of the file (or other source) used in errors.
+    pub fn from_json(source: &str, json: &str) -> Result<Self> {
+        let deserializer = &mut serde_json::Deserializer::from_str(json);
+        serde_path_to_error::deserialize(deserializer).map_err(|err| {
+            let field = err.path().to_string();
+            Error::InvalidBlockDefinition(source.to_owned(), field, err.into_inner().to_string())
+        })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|err| {
+            Error::InvalidBlockDefinition(path.display().to_string(), ".".to_owned(), err.to_string())
+        })?;
+        Self::from_json(&path.display().to_string(), &json)
+    }
+}
+
+/// Material fields that replace the block's values for states whose properties match `when`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StateOverride {
+    /// Property values that a state needs to have, written in the [BlockState] text syntax.
+    pub when: BTreeMap<String, String>,
+    #[serde(default)]
+    pub light_emission: Option<u8>,
+    #[serde(default)]
+    pub light_opacity: Option<u8>,
+    #[serde(default)]
+    pub transparency: Option<Transparency>,
+    #[serde(default)]
+    pub friction: Option<f32>,
+    #[serde(default)]
+    pub hardness: Option<f32>,
+    #[serde(default)]
+    pub blast_resistance: Option<f32>,
+}
+
+impl StateOverride {
+    pub fn apply(&self, material: BlockMaterial) -> BlockMaterial {
+        BlockMaterial {
+            light_emission: self.light_emission.unwrap_or(material.light_emission),
+            light_opacity: self.light_opacity.unwrap_or(material.light_opacity),
+            transparency: self.transparency.unwrap_or(material.transparency),
+            friction: self.friction.unwrap_or(material.friction),
+            hardness: self.hardness.unwrap_or(material.hardness),
+            blast_resistance: self.blast_resistance.unwrap_or(material.blast_resistance),
+        }
+    }
+}
+
+/// The declaration of a property in a [BlockDefinition].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum PropertyDefinition {
+    Bool {
+        #[serde(default)]
+        default: bool,
+    },
+    /// Defaults to `min`.
+    Int {
+        min: i64,
+        max: i64,
+        #[serde(default)]
+        default: Option<i64>,
+    },
+    /// One of the listed strings. Defaults to the first value.
+    String {
+        values: Vec<String>,
+        #[serde(default)]
+        default: Option<String>,
+    },
+    /// Defaults to `pos_y`.
+    Direction {
+        #[serde(default)]
+        default: Option<String>,
+    },
+    /// Defaults to `north`.
+    Cardinal {
+        #[serde(default)]
+        default: Option<String>,
+    },
+    /// Defaults to `y`.
+    Axis {
+        #[serde(default)]
+        default: Option<String>,
+    },
+    /// Values written in the [BlockState] text syntax. Defaults to the first value.
+    List {
+        values: Vec<String>,
+        #[serde(default)]
+        default: Option<String>,
+    },
+}
+
+impl PropertyDefinition {
+    /// Converts the definition to a [PropertyDef]. `field` is the path of the definition, used in errors.
+    fn to_def(&self, name: &str, source: &str, field: &str) -> Result<PropertyDef> {
+        let error = |subfield: &str, reason: String| {
+            Error::InvalidBlockDefinition(source.to_owned(), format!("{field}.{subfield}"), reason)
+        };
+        let parse = |text: &str, subfield: &str| {
+            text.parse::<Property>().map_err(|err| error(subfield, err.to_string()))
+        };
+        let (values, default) = match self {
+            PropertyDefinition::Bool { default } => (PropertyValues::Bool, Property::Bool(*default)),
+            PropertyDefinition::Int { min, max, default } => {
+                (PropertyValues::Int(*min..=*max), Property::Int(default.unwrap_or(*min)))
+            }
+            PropertyDefinition::String { values, default } => {
+                let default = default.as_ref().or(values.first()).cloned().unwrap_or_default();
+                (PropertyValues::List(values.iter().map(Property::from).collect()), Property::String(default))
+            }
+            PropertyDefinition::Direction { default } => {
+                let default = default.as_deref().map(|text| parse(text, "default")).transpose()?;
+                (PropertyValues::List(Direction::ALL.map(Property::from).to_vec()), default.unwrap_or(Property::Direction(Direction::PosY)))
+            }
+            PropertyDefinition::Cardinal { default } => {
+                let default = default.as_deref().map(|text| parse(text, "default")).transpose()?;
+                (PropertyValues::List(Cardinal::ALL.map(Property::from).to_vec()), default.unwrap_or(Property::Cardinal(Cardinal::North)))
+            }
+            PropertyDefinition::Axis { default } => {
+                let default = default.as_deref().map(|text| parse(text, "default")).transpose()?;
+                (PropertyValues::List([Axis::X, Axis::Y, Axis::Z].map(Property::from).to_vec()), default.unwrap_or(Property::Axis(Axis::Y)))
+            }
+            PropertyDefinition::List { values, default } => {
+                let values = values.iter().enumerate().map(|(index, text)| {
+                    parse(text, &format!("values[{index}]"))
+                }).collect::<Result<Vec<_>>>()?;
+                let default = match default {
+                    Some(text) => parse(text, "default")?,
+                    None => values.first().cloned().unwrap_or_default(),
+                };
+                (PropertyValues::List(values), default)
+            }
+        };
+        if values.count() == 0 {
+            return Err(error("values", "there are no allowed values".to_owned()));
+        }
+        if !values.contains(&default) {
+            return Err(error("default", format!("{default} is not an allowed value")));
+        }
+        Ok(PropertyDef::new(name, values, default))
+    }
+}
+
+/// The collision shape in a [BlockDefinition].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ShapeDefinition {
+    Named(NamedShape),
+    /// Boxes as `[min_x, min_y, min_z, max_x, max_y, max_z]` in sixteenths of a block.
+    Boxes(Vec<[f32; 6]>),
+}
+
+impl Default for ShapeDefinition {
+    fn default() -> Self {
+        ShapeDefinition::Named(NamedShape::Full)
+    }
+}
+
+impl ShapeDefinition {
+    pub fn to_shape(&self) -> BlockShape {
+        match self {
+            ShapeDefinition::Named(NamedShape::Full) => BlockShape::full(),
+            ShapeDefinition::Named(NamedShape::Empty) => BlockShape::empty(),
+            ShapeDefinition::Boxes(boxes) => BlockShape::new(boxes.iter().map(|&[min_x, min_y, min_z, max_x, max_y, max_z]| {
+                ShapeBox::pixels(min_x, min_y, min_z, max_x, max_y, max_z)
+            })),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NamedShape {
+    #[default]
+    Full,
+    Empty,
+}
+
+/// A [BlockBehavior] that is defined by a [BlockDefinition].
+#[derive(Debug, Clone)]
+pub struct DataBlock {
+    definition: BlockDefinition,
+    properties: Vec<PropertyDef>,
+    collision: BlockShape,
+    /// The parsed `when` of each of the [BlockDefinition::state_overrides].
+    conditions: Vec<Vec<(String, Property)>>,
+}
+
+impl DataBlock {
+    /// Validates `definition`. `source` is the name of the file (or other source) used in errors.
+    pub fn new(source: &str, definition: BlockDefinition) -> Result<Self> {
+        let error = |field: String, reason: String| Error::InvalidBlockDefinition(source.to_owned(), field, reason);
+        if definition.name.is_empty() {
+            return Err(error("name".to_owned(), "name can't be empty".to_owned()));
+        }
+        definition.material().validate().map_err(|(field, reason)| error(field.to_owned(), reason))?;
+        if let ShapeDefinition::Boxes(boxes) = &definition.collision {
+            for (index, bounds) in boxes.iter().enumerate() {
+                if (0..3).any(|axis| bounds[axis] > bounds[axis + 3]) {
+                    return Err(error(format!("collision[{index}]"), "min is greater than max".to_owned()));
+                }
+            }
+        }
+        let properties = definition.properties.iter().map(|(name, def)| {
+            def.to_def(name, source, &format!("properties.{name}"))
+        }).collect::<Result<Vec<_>>>()?;
+        BlockSchema::new(&definition.name, properties.iter().cloned())
+            .and_then(|schema| schema.state_count())
+            .map_err(|err| error("properties".to_owned(), err.to_string()))?;
+        let conditions = definition.state_overrides.iter().enumerate().map(|(index, state_override)| {
+            let field = format!("state_overrides[{index}]");
+            let material = state_override.apply(definition.material());
+            material.validate().map_err(|(name, reason)| error(format!("{field}.{name}"), reason))?;
+            state_override.when.iter().map(|(name, text)| {
+                let field = format!("{field}.when.{name}");
+                let Some(def) = properties.iter().find(|def| def.name() == name) else {
+                    return Err(error(field, format!("\"{name}\" is not a property of the block")));
+                };
+                let value = text.parse::<Property>().map_err(|err| error(field.clone(), err.to_string()))?;
+                if !def.values().contains(&value) {
+                    return Err(error(field, format!("{value} is not an allowed value")));
+                }
+                Ok((name.clone(), value))
+            }).collect::<Result<Vec<_>>>()
+        }).collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            collision: definition.collision.to_shape(),
+            definition,
+            properties,
+            conditions,
+        })
+    }
+
+    pub fn from_json(source: &str, json: &str) -> Result<Self> {
+        Self::new(source, BlockDefinition::from_json(source, json)?)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        Self::new(&path.display().to_string(), BlockDefinition::from_file(path)?)
+    }
+
+    #[inline]
+    pub fn definition(&self) -> &BlockDefinition {
+        &self.definition
+    }
+
+    #[inline]
+    pub fn tags(&self) -> &[String] {
+        &self.definition.tags
+    }
+}
+
+impl BlockBehavior for DataBlock {
+    fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    fn display_name(&self) -> Option<&str> {
+        self.definition.display_name.as_deref()
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.definition.description.as_deref()
+    }
+
+    fn properties(&self) -> Vec<PropertyDef> {
+        self.properties.clone()
+    }
+
+    fn collision_shape(&self, _state: &BlockState) -> BlockShape {
+        self.collision.clone()
+    }
+
+    fn material(&self, state: &BlockState) -> BlockMaterial {
+        self.definition.state_overrides.iter().zip(&self.conditions)
+            .filter(|(_, when)| when.iter().all(|(name, value)| state.get_property(name) == value))
+            .fold(self.definition.material(), |material, (state_override, _)| state_override.apply(material))
+    }
+}
+
+impl BlockRegistry {
+    /// Registers a [DataBlock] and adds it to its tags.
+    pub fn register_data_block(&self, block: DataBlock) -> Result<BlockId> {
+        let name = block.definition.name.clone();
+        let tags = block.definition.tags.clone();
+        let id = self.register_block(block)?;
+        for tag in tags {
+            self.register_tag(tag, [TagEntry::Block(name.clone())])?;
+        }
+        Ok(id)
+    }
+
+    /// Registers a block from a JSON [BlockDefinition]. `source` is the name used in errors.
+    pub fn load_block_json(&self, source: &str, json: &str) -> Result<BlockId> {
+        self.register_data_block(DataBlock::from_json(source, json)?)
+    }
+
+    /// Registers a block from a JSON [BlockDefinition] file.
+    pub fn load_block_file<P: AsRef<Path>>(&self, path: P) -> Result<BlockId> {
+        self.register_data_block(DataBlock::from_file(path)?)
+    }
+
+    /// Registers a block from every `.json` file in `dir` (in file name order), returning their [BlockId]s.
+    pub fn load_block_dir<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<BlockId>> {
+        let dir = dir.as_ref();
+        let entries = std::fs::read_dir(dir).map_err(|err| {
+            Error::InvalidBlockDefinition(dir.display().to_string(), ".".to_owned(), err.to_string())
+        })?;
+        let mut paths = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.into_iter().map(|path| self.load_block_file(path)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockstate;
+
+    const PILLAR: &str = r#"{
+        "name": "marble_pillar",
+        "display_name": "Marble Pillar",
+        "properties": {
+            "axis": { "type": "axis" },
+            "cracks": { "type": "int", "min": 0, "max": 3, "default": 1 },
+            "trim": { "type": "string", "values": ["none", "gold"] },
+            "facing": { "type": "list", "values": ["west", "east"], "default": "east" }
+        },
+        "light_emission": 4,
+        "hardness": 1.5,
+        "state_overrides": [
+            { "when": { "trim": "\"gold\"" }, "light_emission": 10 },
+            { "when": { "trim": "\"gold\"", "cracks": "3" }, "light_emission": 2, "transparency": "cutout" }
+        ],
+        "collision": [[2, 0, 2, 14, 16, 14]],
+        "tags": ["decorative", "mineable/pickaxe"]
+    }"#;
+
+    fn error(json: &str) -> (String, String) {
+        match DataBlock::from_json("blocks/test.json", json) {
+            Err(Error::InvalidBlockDefinition(file, field, _)) => {
+                debug_assert_eq!(file, "blocks/test.json");
+                (file, field)
+            }
+            Err(err) => panic!("Unexpected error: {err}"),
+            Ok(_) => panic!("Expected an error."),
+        }
+    }
+
+    #[test]
+    fn data_block_test() {
+        let registry = BlockRegistry::new();
+        let pillar = registry.load_block_json("blocks/marble_pillar.json", PILLAR).unwrap();
+        debug_assert_eq!(registry.block_states(pillar).unwrap().len(), 3 * 4 * 2 * 2);
+        let block = registry.get_block(pillar).unwrap();
+        debug_assert_eq!(block.display_name(), Some("Marble Pillar"));
+        debug_assert_eq!(block.description(), None);
+        let default = registry.default_state(pillar).unwrap();
+        debug_assert_eq!(
+            *registry.get_state(default).unwrap(),
+            blockstate!(marble_pillar[axis=Axis::Y, cracks=1i64, facing=Cardinal::East, trim="none"])
+        );
+        debug_assert!(registry.resolve_tag("mineable/pickaxe").unwrap().contains(pillar));
+        let data = DataBlock::from_json("pillar", PILLAR).unwrap();
+        let material = BlockMaterial { light_emission: 4, hardness: 1.5, ..BlockMaterial::DEFAULT };
+        debug_assert_eq!(data.definition().material(), material);
+        debug_assert_eq!(registry.material(default).unwrap(), material);
+        let gold = registry.with_property(default, "trim", "gold").unwrap();
+        debug_assert_eq!(registry.material(gold).unwrap().light_emission, 10);
+        let cracked = registry.with_property(gold, "cracks", 3i64).unwrap();
+        debug_assert_eq!(registry.material(cracked).unwrap(), BlockMaterial { light_emission: 2, transparency: Transparency::Cutout, ..material });
+        debug_assert_eq!(data.definition().collision, ShapeDefinition::Boxes(vec![[2.0, 0.0, 2.0, 14.0, 16.0, 14.0]]));
+        let shape = registry.collision_shape(default).unwrap();
+        debug_assert_eq!(shape.boxes(), &[ShapeBox::pixels(2.0, 0.0, 2.0, 14.0, 16.0, 14.0)]);
+
+        let simple = DataBlock::from_json("simple", r#"{ "name": "simple", "collision": "empty" }"#).unwrap();
+        debug_assert!(simple.collision_shape(&blockstate!(simple)).is_empty());
+        debug_assert!(simple.properties().is_empty());
+    }
+
+    #[test]
+    fn data_block_error_test() {
+        debug_assert_eq!(error(r#"{ "name": "a", "light_emission": "bright" }"#).1, "light_emission");
+        debug_assert_eq!(error(r#"{ "name": "a", "light_emission": 16 }"#).1, "light_emission");
+        debug_assert_eq!(error(r#"{ "name": "a", "colour": "red" }"#).1, "colour");
+        debug_assert_eq!(error(r#"{ "display_name": "a" }"#).1, ".");
+        debug_assert_eq!(error(r#"{ "name": "a", "properties": { "age": { "type": "int", "min": 0 } } }"#).1, "properties.age");
+        debug_assert_eq!(error(r#"{ "name": "a", "properties": { "age": { "type": "float" } } }"#).1, "properties.age.type");
+        debug_assert_eq!(
+            error(r#"{ "name": "a", "properties": { "age": { "type": "int", "min": 0, "max": 3, "default": 4 } } }"#).1,
+            "properties.age.default"
+        );
+        debug_assert_eq!(
+            error(r#"{ "name": "a", "properties": { "facing": { "type": "list", "values": ["west", "up"] } } }"#).1,
+            "properties.facing.values[1]"
+        );
+        debug_assert_eq!(error(r#"{ "name": "a", "collision": [[0, 0, 0, 16, 16, 16], [4, 4, 4, 2, 8, 8]] }"#).1, "collision[1]");
+        debug_assert_eq!(error(r#"{ "name": "a", "tags": [1] }"#).1, "tags[0]");
+        debug_assert_eq!(error(r#"{ "name": "a", "transparency": "glassy" }"#).1, "transparency");
+        debug_assert_eq!(error(r#"{ "name": "a", "state_overrides": [{ "when": {}, "light_opacity": 20 }] }"#).1, "state_overrides[0].light_opacity");
+        debug_assert_eq!(error(r#"{ "name": "a", "state_overrides": [{ "when": { "lit": "true" } }] }"#).1, "state_overrides[0].when.lit");
+        debug_assert_eq!(
+            error(r#"{ "name": "a", "properties": { "lit": { "type": "bool" } }, "state_overrides": [{ "when": { "lit": "3" } }] }"#).1,
+            "state_overrides[0].when.lit"
+        );
+    }
+}
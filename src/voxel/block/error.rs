@@ -14,6 +14,34 @@ pub enum Error {
     InvalidPropertyId(u8),
     #[error("Invalid conversion.")]
     InvalidConversion,
+    #[error("Invalid schema for block \"{0}\": {1}.")]
+    InvalidSchema(String, String),
+    #[error("Block \"{0}\" has no property \"{1}\".")]
+    UnknownProperty(String, String),
+    #[error("Block \"{0}\" has property \"{1}\" more than once.")]
+    DuplicateProperty(String, String),
+    #[error("Invalid value for property \"{1}\" of block \"{0}\".")]
+    InvalidPropertyValue(String, String),
+    #[error("BlockRegistry is frozen, no more blocks can be registered.")]
+    RegistryFrozen,
+    #[error("Block tag not found \"{0}\".")]
+    TagNotFound(String),
+    #[error("Block tag \"{0}\" includes itself.")]
+    TagCycle(String),
+    #[error("Invalid block tag file \"{0}\": {1}.")]
+    InvalidTagFile(String, String),
+    #[error("StateId ({0}) is not in the palette.")]
+    StateNotInPalette(u32),
+    #[error("StateId ({0}) is not in the registry.")]
+    StateNotFound(u32),
+    #[error("Invalid block definition \"{0}\" at \"{1}\": {2}.")]
+    InvalidBlockDefinition(String, String, String),
+    #[error("Invalid material for block \"{0}\": {1}.")]
+    InvalidMaterial(String, String),
+    #[error("Invalid migration: {0}.")]
+    InvalidMigration(String),
+    #[error("{0}")]
+    InvalidIdentifier(#[from] crate::util::identifier::IdentifierError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
\ No newline at end of file
@@ -0,0 +1,267 @@
+use hashbrown::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::util::identifier::IdentifierMap;
+use crate::voxel::direction::Direction;
+use crate::voxel::orientation::Orientation;
+
+use super::block::BlockBehavior;
+use super::block_material::{BlockMaterial, MaterialTable, Transparency};
+use super::block_schema::BlockSchema;
+use super::block_shape::{BlockShape, StateShapes};
+use super::block_state::BlockState;
+use super::block_tag::BlockSet;
+use super::block_transform;
+use super::error::{Error, Result};
+use super::id::{BlockId, StateId};
+
+/// The same tables as the [BlockRegistry](super::block_registry::BlockRegistry), but they can no longer change.
+pub(super) struct FrozenInner {
+    pub(super) blocks: Box<[Arc<dyn BlockBehavior>]>,
+    pub(super) block_lookup: IdentifierMap<BlockId>,
+    pub(super) schemas: Box<[Arc<BlockSchema>]>,
+    pub(super) block_states: Box<[Range<u32>]>,
+    pub(super) states: Box<[Arc<BlockState>]>,
+    pub(super) block_ids: Box<[BlockId]>,
+    pub(super) state_lookup: HashMap<Arc<BlockState>, StateId>,
+    pub(super) shapes: Box<[StateShapes]>,
+    pub(super) materials: MaterialTable,
+    pub(super) tags: HashMap<String, BlockSet>,
+}
+
+/// An immutable snapshot of a [BlockRegistry](super::block_registry::BlockRegistry), created with
+/// [BlockRegistry::freeze](super::block_registry::BlockRegistry::freeze).
+///
+/// Lookups are plain slice indexing without any locking, and blocks and states are borrowed instead of cloned.
+/// Like slice indexing, lookups panic if the id is not from this registry.
+/// Cloning is cheap and clones can be sent to other threads.
+#[derive(Clone)]
+pub struct FrozenBlockRegistry(pub(super) Arc<FrozenInner>);
+
+impl FrozenBlockRegistry {
+    /// Gets the block that `id` is a state of.
+    #[inline]
+    pub fn get_block(&self, id: StateId) -> &dyn BlockBehavior {
+        self.block(self.0.block_ids[id.index()])
+    }
+
+    #[inline]
+    pub fn block(&self, id: BlockId) -> &dyn BlockBehavior {
+        self.0.blocks[id.index()].as_ref()
+    }
+
+    #[inline]
+    pub fn get_state(&self, id: StateId) -> &BlockState {
+        &self.0.states[id.index()]
+    }
+
+    #[inline]
+    pub fn block_id(&self, id: StateId) -> BlockId {
+        self.0.block_ids[id.index()]
+    }
+
+    #[inline]
+    pub fn get_schema(&self, id: BlockId) -> &BlockSchema {
+        &self.0.schemas[id.index()]
+    }
+
+    /// The collision shape of the state, rotated by [BlockBehavior::orientation].
+    #[inline]
+    pub fn collision_shape(&self, id: StateId) -> &BlockShape {
+        &self.0.shapes[id.index()].collision
+    }
+
+    /// The outline shape of the state, rotated by [BlockBehavior::orientation].
+    #[inline]
+    pub fn outline_shape(&self, id: StateId) -> &BlockShape {
+        &self.0.shapes[id.index()].outline
+    }
+
+    /// Returns `true` if the collision shape of the state fully covers `face`, hiding the neighboring face on that side.
+    #[inline]
+    pub fn covers_face(&self, id: StateId, face: Direction) -> bool {
+        self.0.shapes[id.index()].collision.covers_face(face)
+    }
+
+    #[inline]
+    pub fn material(&self, id: StateId) -> BlockMaterial {
+        self.0.materials.get(id)
+    }
+
+    #[inline]
+    pub fn light_emission(&self, id: StateId) -> u8 {
+        self.0.materials.light_emission()[id.index()]
+    }
+
+    #[inline]
+    pub fn light_opacity(&self, id: StateId) -> u8 {
+        self.0.materials.light_opacity()[id.index()]
+    }
+
+    #[inline]
+    pub fn transparency(&self, id: StateId) -> Transparency {
+        self.0.materials.transparency()[id.index()]
+    }
+
+    #[inline]
+    pub fn friction(&self, id: StateId) -> f32 {
+        self.0.materials.friction()[id.index()]
+    }
+
+    #[inline]
+    pub fn hardness(&self, id: StateId) -> f32 {
+        self.0.materials.hardness()[id.index()]
+    }
+
+    #[inline]
+    pub fn blast_resistance(&self, id: StateId) -> f32 {
+        self.0.materials.blast_resistance()[id.index()]
+    }
+
+    /// The light emission of every state, indexed by [StateId].
+    #[inline]
+    pub fn light_emission_table(&self) -> &[u8] {
+        self.0.materials.light_emission()
+    }
+
+    /// The light opacity of every state, indexed by [StateId].
+    #[inline]
+    pub fn light_opacity_table(&self) -> &[u8] {
+        self.0.materials.light_opacity()
+    }
+
+    /// Every [StateId] that belongs to the block.
+    #[inline]
+    pub fn block_states(&self, id: BlockId) -> impl Iterator<Item = StateId> {
+        self.0.block_states[id.index()].clone().map(StateId)
+    }
+
+    /// The [StateId] of the state with every property set to its default value.
+    #[inline]
+    pub fn default_state(&self, id: BlockId) -> StateId {
+        StateId(self.0.block_states[id.index()].start + self.0.schemas[id.index()].default_index())
+    }
+
+    #[inline]
+    pub fn find_block<S: AsRef<str>>(&self, name: S) -> Result<BlockId> {
+        self.0.block_lookup.get(name.as_ref()).copied().ok_or_else(|| Error::BlockNotFound(name.as_ref().to_owned()))
+    }
+
+    /// Finds the [StateId] of `state`. Properties missing from `state` take their default value.
+    pub fn find_state(&self, state: &BlockState) -> Result<StateId> {
+        if let Some(&state_id) = self.0.state_lookup.get(state) {
+            return Ok(state_id);
+        }
+        let block_id = self.find_block(state.name())?;
+        let state = state.with_name(self.block(block_id).name());
+        let state = self.get_schema(block_id).validate(&state)?;
+        self.0.state_lookup.get(&state).copied().ok_or_else(|| Error::BlockNotFound(state.name().to_owned()))
+    }
+
+    /// Finds the [StateId] of the state `id` after it is rotated or mirrored by `orientation`.
+    /// See [BlockRegistry::transform_state](super::block_registry::BlockRegistry::transform_state).
+    pub fn transform_state(&self, id: StateId, orientation: Orientation) -> Result<StateId> {
+        let state = self.get_state(id);
+        let transformed = self.get_block(id).transform(state, orientation);
+        let block_id = self.find_block(transformed.name())?;
+        self.find_state(&block_transform::keep_allowed(self.get_schema(block_id), state, transformed))
+    }
+
+    /// Gets the resolved block tag `name`.
+    /// Hot paths should get the [BlockSet] once and then use [BlockSet::contains].
+    #[inline]
+    pub fn tag<S: AsRef<str>>(&self, name: S) -> Option<&BlockSet> {
+        self.0.tags.get(name.as_ref())
+    }
+
+    /// Returns `true` if the block is in the tag `name`. Returns `false` if the tag doesn't exist.
+    #[inline]
+    pub fn has_tag<S: AsRef<str>>(&self, id: BlockId, name: S) -> bool {
+        self.tag(name).is_some_and(|tag| tag.contains(id))
+    }
+
+    #[inline]
+    pub fn block_count(&self) -> usize {
+        self.0.blocks.len()
+    }
+
+    #[inline]
+    pub fn state_count(&self) -> usize {
+        self.0.states.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockstate;
+    use crate::voxel::block::block_registry::BlockRegistry;
+    use crate::voxel::block::block_schema::PropertyDef;
+
+    struct Lever;
+    impl BlockBehavior for Lever {
+        fn name(&self) -> &str {
+            "lever"
+        }
+
+        fn properties(&self) -> Vec<PropertyDef> {
+            vec![PropertyDef::bool("powered", false), PropertyDef::int("delay", 1..=4, 1)]
+        }
+    }
+
+    struct Stone;
+    impl BlockBehavior for Stone {
+        fn name(&self) -> &str {
+            "stone"
+        }
+    }
+
+    #[test]
+    fn frozen_registry_test() {
+        let registry = BlockRegistry::new();
+        let lever = registry.register_block(Lever).unwrap();
+        let frozen = registry.freeze().unwrap();
+        debug_assert!(registry.is_frozen().unwrap());
+        debug_assert!(matches!(registry.register_block(Stone), Err(Error::RegistryFrozen)));
+        // Stone wasn't registered, so the snapshot and the registry still agree.
+        debug_assert_eq!(frozen.block_count(), 2);
+        debug_assert_eq!(frozen.state_count(), 1 + 8);
+        debug_assert!(frozen.find_block("stone").is_err());
+
+        let default = frozen.default_state(lever);
+        debug_assert_eq!(default, registry.default_state(lever).unwrap());
+        debug_assert_eq!(frozen.find_state(&blockstate!(lever)).unwrap(), default);
+        debug_assert_eq!(*frozen.get_state(default), blockstate!(lever[powered=false, delay=1i64]));
+        debug_assert_eq!(frozen.get_block(StateId::AIR).name(), "air");
+
+        let states = frozen.block_states(lever).collect::<Vec<_>>();
+        std::thread::scope(|scope| {
+            let workers = (0..4).map(|worker| {
+                let frozen = frozen.clone();
+                let states = &states;
+                scope.spawn(move || {
+                    let mut found = 0;
+                    for round in 0..1000 {
+                        let id = states[(worker + round) % states.len()];
+                        debug_assert_eq!(frozen.get_block(id).name(), "lever");
+                        debug_assert_eq!(frozen.block_id(id), lever);
+                        let state = frozen.get_state(id);
+                        debug_assert_eq!(frozen.find_state(state).unwrap(), id);
+                        found += 1;
+                    }
+                    found
+                })
+            }).collect::<Vec<_>>();
+            for worker in workers {
+                debug_assert_eq!(worker.join().unwrap(), 1000);
+            }
+        });
+        // The registry is still readable from other threads as well.
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                debug_assert_eq!(registry.block_states(lever).unwrap(), states);
+            });
+        });
+    }
+}
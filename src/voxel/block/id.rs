@@ -1,9 +1,16 @@
+use bytemuck::{Pod, Zeroable};
 use serde::{Serialize, Deserialize};
 
+use crate::io::*;
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct BlockId(pub(crate) u32);
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+/// Slices of [StateId]s, such as the blocks of a section, can be written in bulk with [hexio::pod].
+/// The bulk functions only need [PodScalar](hexio::pod::PodScalar), and with [Endian::Big](hexio::pod::Endian::Big)
+/// they write the same bytes as writing each id with this crate's [Writeable].
+#[repr(transparent)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Pod, Zeroable)]
 pub struct StateId(pub(crate) u32);
 
 impl BlockId {
@@ -32,4 +39,44 @@ impl StateId {
     pub fn is_air(self) -> bool {
         self.0 == 0
     }
-}
\ No newline at end of file
+}
+
+impl Readable for StateId {
+    fn read_from<R: std::io::Read>(reader: &mut R) -> crate::prelude::VoxelResult<Self> {
+        Ok(StateId(u32::read_from(reader)?))
+    }
+}
+
+impl Writeable for StateId {
+    fn write_to<W: std::io::Write>(&self, writer: &mut W) -> crate::prelude::VoxelResult<u64> {
+        self.0.write_to(writer)
+    }
+}
+
+impl hexio::pod::PodScalar for StateId {
+    #[inline]
+    fn swap_bytes(self) -> Self {
+        StateId(self.0.swap_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use hexio::pod::{read_pod_vec, write_pod_slice, Endian};
+
+    use super::*;
+
+    #[test]
+    fn state_id_pod_test() {
+        let ids = [StateId::AIR, StateId(1), StateId(0x01020304)];
+        let mut buffer = Vec::new();
+        write_pod_slice(&mut buffer, &ids, Endian::Big).unwrap();
+        // The same bytes as writing each id with Writeable.
+        let mut expected = Vec::new();
+        ids.iter().for_each(|id| _ = id.write_to(&mut expected).unwrap());
+        debug_assert_eq!(buffer, expected);
+        debug_assert_eq!(read_pod_vec::<StateId, _>(&mut Cursor::new(&buffer), ids.len(), Endian::Big).unwrap(), ids);
+    }
+}
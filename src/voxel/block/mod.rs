@@ -1,7 +1,18 @@
 pub mod block;
+pub mod block_context;
 pub mod block_registry;
+pub mod frozen_registry;
 pub mod id;
 pub mod block_state;
 pub mod block_property;
+pub mod block_schema;
+pub mod block_shape;
+pub mod block_material;
+pub mod block_palette;
+pub mod block_migration;
+pub mod block_tag;
+pub mod block_syntax;
+pub mod block_transform;
+pub mod data_block;
 pub mod error;
-pub mod blocks;
\ No newline at end of file
+pub mod blocks;
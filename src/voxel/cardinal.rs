@@ -1,6 +1,8 @@
 use bytemuck::NoUninit;
 use serde::{Serialize, Deserialize};
 
+use super::direction::Direction;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, NoUninit, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum Cardinal {
@@ -80,6 +82,29 @@ impl Cardinal {
         }
     }
 
+    /// The horizontal [Direction] that the [Cardinal] points towards.
+    #[inline]
+    pub const fn direction(self) -> Direction {
+        match self {
+            Cardinal::West => Direction::NegX,
+            Cardinal::North => Direction::NegZ,
+            Cardinal::East => Direction::PosX,
+            Cardinal::South => Direction::PosZ,
+        }
+    }
+
+    /// Returns `None` for [Direction::PosY] and [Direction::NegY].
+    #[inline]
+    pub const fn from_direction(direction: Direction) -> Option<Self> {
+        match direction {
+            Direction::NegX => Some(Cardinal::West),
+            Direction::NegZ => Some(Cardinal::North),
+            Direction::PosX => Some(Cardinal::East),
+            Direction::PosZ => Some(Cardinal::South),
+            Direction::PosY | Direction::NegY => None,
+        }
+    }
+
     /// Gets the [Cardinal] as a single bit based on discriminant.
     #[inline]
     pub const fn bit(self) -> u8 {
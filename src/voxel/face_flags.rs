@@ -1,6 +1,7 @@
 use crate::prelude::BoolExtension;
 
 use super::direction::Direction;
+use super::orientation::Orientation;
 use paste::paste;
 use bytemuck::NoUninit;
 use serde::{Serialize, Deserialize};
@@ -123,6 +124,15 @@ impl FaceFlags {
         self.0 = 0;
     }
 
+    /// Moves each flag to the face that its face ends up on after `orientation` is applied.
+    pub fn reorient(self, orientation: Orientation) -> Self {
+        let mut result = FaceFlags::NONE;
+        for face in Direction::ALL {
+            result.set(orientation.reface(face), self.get(face));
+        }
+        result
+    }
+
     pub const fn any(self) -> bool {
         self.0 != 0
     }
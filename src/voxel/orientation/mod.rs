@@ -17,4 +17,14 @@ pub const fn unpack_flip_and_rotation(packed: u8) -> (Flip, Rotation) {
     let flip = packed & 0b111;
     let rotation = packed >> 3;
     (Flip(flip), Rotation(rotation))
+}
+
+/// Every orientation that [orient_table] has entries for, one per (rotation, flip) pair.
+#[cfg(test)]
+pub(crate) fn table_orientations() -> impl Iterator<Item = Orientation> {
+    (0..orient_table::MAP_COORD_TABLE.len() / 6).map(|entry| {
+        let (rotation, flip) = (Rotation((entry % 24) as u8), Flip((entry / 24) as u8));
+        debug_assert!(crate::voxel::direction::Direction::ALL.iter().all(|&face| orient_table::table_index(rotation, flip, face) / 6 == entry));
+        Orientation::new(rotation, flip)
+    })
 }
\ No newline at end of file
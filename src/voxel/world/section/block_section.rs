@@ -1,4 +1,4 @@
-use crate::{prelude::{OptionExtension, Replace}, util::change::Change, voxel::block::id::StateId};
+use crate::{prelude::{OptionExtension, Replace}, util::change::Change, voxel::block::{block_palette::StateRemap, error::Result, id::StateId}};
 
 use super::SectionIndex;
 
@@ -54,6 +54,21 @@ impl<const W: i32> BlockSection<W> {
         blocks[index]
     }
 
+    /// Translates [StateId]s that were saved with a different registry into the current registry's [StateId]s.
+    /// This should be called after the section is loaded, with a [StateRemap] from
+    /// [BlockRegistry::remap](crate::voxel::block::block_registry::BlockRegistry::remap) so that old blocks are migrated.
+    pub fn remap(&mut self, remap: &StateRemap) -> Result<()> {
+        let Some(blocks) = &mut self.blocks else {
+            return Ok(());
+        };
+        remap.apply(blocks)?;
+        self.non_air_count = blocks.iter().filter(|id| !id.is_air()).count() as u16;
+        if self.non_air_count == 0 {
+            self.blocks.drop();
+        }
+        Ok(())
+    }
+
     pub fn is_allocated(&self) -> bool {
         self.blocks.is_some()
     }
@@ -51,7 +51,7 @@ mod tests {
             }
         }
         reg.register_block(DebugBlock("dirt")).unwrap();
-        let dirt = reg.register_state(blockstate!(dirt)).unwrap();
+        let dirt = reg.find_state(blockstate!(dirt)).unwrap();
         let mut section = Section::<8>::new();
         let mut update_queue = UpdateQueue::new();
 